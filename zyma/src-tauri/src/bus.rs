@@ -1,14 +1,21 @@
 use serde::{Serialize, Deserialize};
+use crate::models::WorkspaceFolder;
 use tokio::sync::broadcast;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "payload")] // 方便 JSON 序列化
 pub enum ZymaEvent {
     WorkspaceChanged(String),
+    // 多根工作区的根目录列表发生变化 (主根目录不变)
+    WorkspaceFoldersChanged(Vec<WorkspaceFolder>),
     FileSaved(String),
     FileCreated(String),
-    FileDeleted(String),
+    // trashed 为 true 表示移入了回收站，可以还原
+    FileDeleted { path: String, trashed: bool },
+    FileMoved { from: String, to: String },
     WindowFocused(bool),
+    // 设置发生变化 (手动编辑、其他窗口保存或工作区设置文件修改)，内容为变化的顶层键
+    SettingsChanged(Vec<String>),
     // 未来可扩展：Git事件、LSP事件等
 }
 
@@ -17,6 +24,12 @@ pub struct EventBus {
     tx: broadcast::Sender<ZymaEvent>,
 }
 
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl EventBus {
     pub fn new() -> Self {
         // 容量 100，超过处理速度会丢弃旧消息（Lagged），这对于即时事件是合理的
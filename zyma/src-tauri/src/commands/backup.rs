@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+use crate::commands::caller::IpcCaller;
+use crate::models::DirtyBuffer;
+use crate::services::backup::BackupService;
+
+/// 备份未保存的缓冲区 (由前端按防抖间隔调用)
+/// 备份会在下次启动时恢复为可保存的缓冲区，仅对用户开放
+#[tauri::command]
+pub async fn backup_buffer(backups: State<'_, Arc<BackupService>>, mut buffer: DirtyBuffer, caller: IpcCaller) -> Result<(), String> {
+    caller.require_user()?;
+    buffer.timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+    backups.backup(&buffer).await
+}
+
+/// 缓冲区已保存、已还原或被关闭时丢弃备份
+#[tauri::command]
+pub async fn backup_discard(backups: State<'_, Arc<BackupService>>, id: String, caller: IpcCaller) -> Result<(), String> {
+    caller.require_user()?;
+    backups.discard(&id).await
+}
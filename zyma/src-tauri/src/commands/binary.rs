@@ -0,0 +1,74 @@
+use tauri::{State, Emitter};
+use crate::bus::{EventBus, ZymaEvent};
+use crate::commands::fs::WorkspaceService;
+use crate::services::vfs::{FileSystem, FsError};
+use crate::commands::caller::IpcCaller;
+use crate::services::binary::{self, HexDump};
+use crate::services::history::HistorySource;
+
+/// 十六进制视图分页读取 (每行 16 字节，附带偏移与 ASCII 列)
+#[tauri::command]
+pub async fn fs_hex_dump(
+    ws: State<'_, WorkspaceService>,
+    path: String,
+    offset: Option<u64>,
+    rows: Option<usize>,
+    caller: IpcCaller
+) -> Result<HexDump, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.allow_path(&path);
+    binary::hex_dump(&fs, &path, offset.unwrap_or(0), rows.unwrap_or(256)).await
+}
+
+/// 从 offset 开始覆盖写入字节 (bytes 为数组，或 hex 为十六进制字符串)，返回新的版本标记
+/// 传入 expected_version 时若文件已被外部修改则返回 Conflict
+/// 默认在内存中修改后整体保存 (原子替换、备份与本地历史)，超大文件返回 FileTooLarge；
+/// 用户确认后传入 in_place 才原地写入，此时不做备份也不记录历史，且必须带上 expected_version
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn fs_patch_bytes(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    offset: u64,
+    bytes: Option<Vec<u8>>,
+    hex: Option<String>,
+    expected_version: Option<String>,
+    in_place: Option<bool>,
+    caller: IpcCaller
+) -> Result<String, FsError> {
+    let data = match (bytes, hex) {
+        (Some(b), _) => b,
+        (None, Some(h)) => binary::parse_hex(&h)?,
+        (None, None) => return Err("Either bytes or hex is required".to_string().into()),
+    };
+    let fs = ws.fs_for(caller.0);
+    let in_place = in_place.unwrap_or(false);
+    if in_place {
+        // 跳过备份与历史只能由用户本人确认；原地写入无法回退，必须确认写入的是用户看到的版本
+        caller.require_user()?;
+        if expected_version.is_none() {
+            return Err("In-place writes require expected_version".to_string().into());
+        }
+    }
+    if let Some(expected) = expected_version {
+        fs.check_version(&path, &expected).await?;
+    }
+    let version = if in_place {
+        fs.write_range(&path, offset, &data).await?;
+        let content = fs.read_bytes(&path).await?;
+        fs.version_of(&path, &content).await?
+    } else {
+        let stat = fs.stat(&path).await?;
+        if stat.is_large {
+            return Err(FsError::FileTooLarge { path, size: stat.size });
+        }
+        let mut content = fs.read_bytes(&path).await?;
+        binary::apply_patch(&mut content, offset, &data)?;
+        ws.write_with_history(&fs, &path, &content, HistorySource::for_caller(caller.0)).await?
+    };
+    let _ = app_handle.emit("file_saved", &path);
+    bus.publish(ZymaEvent::FileSaved(path));
+    Ok(version)
+}
@@ -0,0 +1,39 @@
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+use tauri::{Manager, Runtime, State, Webview};
+use crate::services::sandbox::{Caller, CallerRegistry};
+
+/// 界面携带用户令牌的请求头
+pub const CALLER_HEADER: &str = "zyma-caller";
+
+/// 由后端认定的调用方，作为命令参数使用 (不从参数中反序列化，前端无法伪造)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpcCaller(pub Caller);
+
+impl IpcCaller {
+    /// 执行程序、修改工作区与设置、读写密钥等操作只允许用户本人发起
+    pub fn require_user(&self) -> Result<(), String> {
+        match self.0 {
+            Caller::User => Ok(()),
+            caller => Err(format!("This operation is not available to {:?} callers", caller)),
+        }
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for IpcCaller {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let webview = command.message.webview_ref();
+        let token = command.message.headers().get(CALLER_HEADER).and_then(|v| v.to_str().ok());
+        // 未注册 CallerRegistry 时按最低权限处理
+        let caller = match webview.try_state::<CallerRegistry>() {
+            Some(registry) => registry.resolve(webview.label(), command.name, token),
+            None => Caller::Plugin,
+        };
+        Ok(IpcCaller(caller))
+    }
+}
+
+/// 界面加载时领取本窗口的用户令牌 (每次页面加载仅一次)
+#[tauri::command]
+pub fn caller_claim_token<R: Runtime>(webview: Webview<R>, registry: State<'_, CallerRegistry>) -> Result<String, String> {
+    registry.claim_user_token(webview.label())
+}
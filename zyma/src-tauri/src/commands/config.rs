@@ -1,21 +1,33 @@
-use std::fs;
+use std::sync::{Arc, OnceLock};
+use serde_json::{Map, Value};
+use tauri::State;
+use crate::commands::caller::IpcCaller;
+use crate::commands::secrets::blocking;
+use crate::commands::fs::WorkspaceService;
 use crate::models::AppSettings;
+use crate::services::FileSystem;
+use crate::services::config_file::{self, SettingsIssue};
+use crate::services::secrets::{ai_api_key_id, SecretService};
+use crate::services::settings_store::SettingsStore;
+use crate::services::settings::{self, EffectiveSettings, SettingsLayer, SettingsScope, SettingsTarget};
 
+/// 用户设置文件 (配置目录下的 settings.json，见 services::paths)
 pub fn get_config_path() -> std::path::PathBuf {
-    let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))
-        .unwrap_or_else(|_| ".".to_string());
-    std::path::Path::new(&home).join(".zyma_config.json")
+    crate::services::paths::dirs().settings_file()
 }
 
-#[tauri::command]
-pub fn load_settings() -> Result<AppSettings, String> {
-    let path = get_config_path();
-    let mut settings = if path.exists() {
-        let content = fs::read_to_string(&path).unwrap_or_default();
-        serde_json::from_str::<AppSettings>(&content).unwrap_or_default()
-    } else {
-        AppSettings::default()
-    };
+static STORE: OnceLock<SettingsStore> = OnceLock::new();
+
+/// 进程内唯一的用户设置存储，所有窗口与后端的读写都经过它
+pub fn store() -> &'static SettingsStore {
+    STORE.get_or_init(|| SettingsStore::new(get_config_path()))
+}
+
+/// 只读取用户配置 (不合并工作区设置)，供后端读取会话、窗口状态等使用
+pub fn load_user_settings() -> Result<AppSettings, String> {
+    // 个别无效的键只回落到默认值，不会重置整份配置
+    #[allow(unused_mut)]
+    let mut settings = store().settings();
 
     #[cfg(windows)]
     {
@@ -29,13 +41,176 @@ pub fn load_settings() -> Result<AppSettings, String> {
     Ok(settings)
 }
 
+/// 读改写用户配置 (在存储的锁内完成，并发的写入不会互相覆盖)
+pub fn update_user_settings<F: FnOnce(&mut AppSettings)>(f: F) -> Result<(), String> {
+    store().update(f).map(|_| ())
+}
+
+// 用户层的键值 (已迁移到当前版本并去掉无效的键)
+fn user_values() -> Map<String, Value> {
+    store().values()
+}
+
+fn workspace_targets(ws: &WorkspaceService, resource: Option<&str>) -> Vec<(SettingsScope, SettingsTarget)> {
+    let workspace_file = crate::commands::workspace::current_workspace_file();
+    settings::locate(&ws.fs.roots(), workspace_file.as_deref(), resource)
+}
+
+/// 合并 用户 < 工作区 < 文件夹 (< 语言专属) 各层后的生效设置
+/// resource 用于在多根工作区中选择文件夹层，language 用于叠加 `"[rust]"` 这类覆盖块
+pub fn effective_settings(ws: &WorkspaceService, resource: Option<&str>, language: Option<&str>) -> Result<EffectiveSettings, String> {
+    let mut user = user_values();
+    user.insert("context_menu".to_string(), Value::Bool(load_user_settings()?.context_menu));
+    let mut layers = vec![SettingsLayer { scope: SettingsScope::User, values: user }];
+    for (scope, target) in workspace_targets(ws, resource) {
+        // 仓库里损坏的设置文件不应影响用户设置
+        match target.read() {
+            Ok(values) => layers.push(SettingsLayer { scope, values }),
+            Err(e) => log::warn!("Skipping {:?} settings: {}", scope, e),
+        }
+    }
+    settings::resolve(&layers, language)
+}
+
+// 返回前端的设置不含密钥 (旧版配置中尚未迁移的明文密钥同样隐去)
+fn redacted(mut effective: EffectiveSettings) -> EffectiveSettings {
+    effective.settings.ai_api_key = None;
+    effective
+}
+
+/// 读取生效设置，附带每个键的来源 (sources)
+#[tauri::command]
+pub fn load_settings(ws: State<'_, WorkspaceService>, path: Option<String>, language: Option<String>) -> Result<EffectiveSettings, String> {
+    effective_settings(&ws, path.as_deref(), language.as_deref()).map(redacted)
+}
+
+/// 保存设置面板回传的完整设置：只有相对生效值改动过的键写入用户配置，
+/// 来自工作区的值不会被顺带写进用户配置
+#[tauri::command]
+pub async fn save_settings(ws: State<'_, WorkspaceService>, secrets: State<'_, Arc<SecretService>>, settings: Map<String, Value>, caller: IpcCaller) -> Result<(), String> {
+    caller.require_user()?;
+    let mut settings = settings;
+    // 密钥不写入配置：新填写的密钥存入密钥存储，配置中只保留引用；清空则删除
+    let secret_ref = match settings.remove("ai_api_key") {
+        Some(Value::String(key)) => {
+            // 密钥绑定到填写时选择的服务商
+            let provider = match settings.get("ai_provider") {
+                Some(Value::String(p)) => Some(p.clone()),
+                _ => load_user_settings()?.ai_provider,
+            };
+            let id = ai_api_key_id(provider.as_deref());
+            let secrets = secrets.inner().clone();
+            Some(blocking(move || {
+                if key.is_empty() {
+                    secrets.delete(&id)?;
+                    Ok(Value::Null)
+                } else {
+                    secrets.set(&id, &key)?;
+                    Ok(Value::String(id))
+                }
+            }).await?)
+        }
+        _ => None,
+    };
+    let effective = effective_settings(&ws, None, None)?;
+    store().update_values(|user| {
+        *user = settings::user_layer_from_effective(&settings, &effective, user)?;
+        if let Some(reference) = secret_ref {
+            user.remove("ai_api_key");
+            user.insert("ai_api_key_ref".to_string(), reference);
+        }
+        Ok(())
+    }).map(|_| ())
+}
+
+/// 修改指定作用域的设置，值为 null 表示删除该键 (回落到下一层)；返回修改后的生效设置
+#[tauri::command]
+pub fn settings_update(
+    ws: State<'_, WorkspaceService>,
+    scope: SettingsScope,
+    values: Map<String, Value>,
+    language: Option<String>,
+    path: Option<String>,
+    caller: IpcCaller
+) -> Result<EffectiveSettings, String> {
+    caller.require_user()?;
+    if values.contains_key("ai_api_key") {
+        return Err("API keys are stored with secrets_set, not in settings".to_string());
+    }
+    match scope {
+        SettingsScope::Default => return Err("Default settings are read-only".to_string()),
+        SettingsScope::User => {
+            store().update_values(|current| settings::apply_patch(current, scope, &values, language.as_deref()))?;
+        }
+        _ => {
+            let targets = workspace_targets(&ws, path.as_deref());
+            // 单根工作区中文件夹层与工作区层是同一个文件
+            let (scope, target) = targets.iter().find(|(s, _)| *s == scope)
+                .or_else(|| targets.iter().find(|(s, _)| *s == SettingsScope::Workspace))
+                .cloned()
+                .ok_or_else(|| "No workspace is open".to_string())?;
+            let mut current = target.read()?;
+            settings::apply_patch(&mut current, scope, &values, language.as_deref())?;
+            target.write(&current)?;
+            store().layer_changed(scope, target.path(), &current);
+        }
+    }
+    effective_settings(&ws, path.as_deref(), language.as_deref()).map(redacted)
+}
+
+/// 配置文件的 JSON Schema
+#[tauri::command]
+pub fn settings_schema() -> Value {
+    config_file::schema()
+}
+
+/// 用户配置中的问题 (语法错误或类型不符的键，附行列号)，只读取不修改文件；启动时读取到有问题的配置已另存为 `.corrupt`
 #[tauri::command]
-pub fn save_settings(settings: AppSettings) -> Result<(), String> {
-    let path = get_config_path();
-    // 关键：在保存设置前，由于前端可能不包含最新的窗口位置，
-    // 我们需要先读取磁盘上的旧配置，合并 windows 字段后再保存。
-    // 但如果 AppSettings 已经全量通过前端同步了，则直接保存。
-    
-    let content = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(path, content).map_err(|e| e.to_string())
-}
\ No newline at end of file
+pub fn settings_diagnostics() -> Vec<SettingsIssue> {
+    config_file::load(&get_config_path()).issues
+}
+
+/// 工作区监听到的文件变化中若包含工作区或文件夹设置文件，通知设置变化
+pub fn workspace_files_changed(ws: &WorkspaceService, paths: &[String]) {
+    let relevant = paths.iter().any(|p| p.ends_with(settings::FOLDER_SETTINGS_FILE) || crate::services::workspace::is_workspace_file(p));
+    if !relevant {
+        return;
+    }
+    for path in paths {
+        for (scope, target) in workspace_targets(ws, Some(path)) {
+            if target.path().to_string_lossy().replace('\\', "/") == *path {
+                store().layer_changed(scope, target.path(), &target.read().unwrap_or_default());
+            }
+        }
+    }
+}
+
+/// 监听用户配置文件所在目录 (编辑器保存时常以替换文件的方式写入)，持有期间有效
+pub struct SettingsWatcher(#[allow(dead_code)] std::sync::Mutex<notify::RecommendedWatcher>);
+
+/// 开始监听用户配置，文件被手动编辑或其他进程修改后重新加载
+pub fn watch_user_settings() -> Result<SettingsWatcher, String> {
+    use notify::Watcher;
+    let path = store().path().to_path_buf();
+    let dir = path.parent().map(|d| d.to_path_buf()).ok_or_else(|| "Invalid settings path".to_string())?;
+    // 首次启动时配置目录可能尚不存在
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if event.paths.iter().any(|p| p == &path) {
+                let _ = tx.send(());
+            }
+        }
+    }).map_err(|e| e.to_string())?;
+    watcher.watch(&dir, notify::RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+
+    // 合并连续的写入事件，等文件写完再读取
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(std::time::Duration::from_millis(150)).is_ok() {}
+            store().reload();
+        }
+    });
+    Ok(SettingsWatcher(std::sync::Mutex::new(watcher)))
+}
@@ -1,22 +1,104 @@
 use std::path::PathBuf;
 use tauri::{State, Emitter};
-use crate::models::FileItem;
+use crate::models::{FileItem, FileReadResponse, WriteOptions};
 use crate::bus::{EventBus, ZymaEvent};
-use crate::services::vfs::{FileSystem, LocalFileSystem, FileStat};
+use crate::services::vfs::{FileSystem, LocalFileSystem, FileStat, FsError};
+use crate::services::{atomic, encoding, permissions, workspace};
+use crate::services::atomic::BackupPolicy;
+use crate::services::transfer::{ConflictPolicy, TransferProgress};
+use crate::commands::caller::IpcCaller;
+use crate::services::sandbox::{Access, Caller, SandboxedFs};
+use crate::services::archive::{self, ArchiveCache, ArchiveFs};
+use crate::services::mount::{MountTable, SharedFs};
+use crate::services::history::{HistoryService, HistorySource};
 
 pub struct WorkspaceService {
-    pub fs: Box<dyn FileSystem + Send + Sync>,
+    /// 按 URI scheme 分发的挂载表，不带 scheme 的路径交给默认文件系统
+    pub fs: MountTable,
+    /// 已打开的压缩包 (zip:/a.zip!/inner 路径)
+    pub archives: ArchiveCache,
+    /// 本地历史快照 (默认不落盘，由应用启动时指定存储目录)
+    pub history: HistoryService,
 }
 
 impl WorkspaceService {
     pub fn new(initial_path: PathBuf) -> Self {
-        Self {
-            fs: Box::new(LocalFileSystem::new(initial_path)),
-        }
+        Self::with_fs(Box::new(LocalFileSystem::new(initial_path)))
     }
     
     pub fn with_fs(fs: Box<dyn FileSystem + Send + Sync>) -> Self {
-        Self { fs }
+        Self { fs: MountTable::new(fs), archives: ArchiveCache::new(), history: HistoryService::default() }
+    }
+
+    /// 以指定调用方身份访问文件系统，插件与 Agent 会被强制限制在工作区内
+    /// 压缩包内的路径 (zip:/a.zip!/inner) 以只读方式透明访问
+    pub fn fs_for(&self, caller: Caller) -> ArchiveFs<'_, SandboxedFs<'_>> {
+        ArchiveFs::new(SandboxedFs::new(&self.fs, caller), &self.archives)
+    }
+
+    /// 直接遍历本地磁盘的命令 (搜索等) 不经过 FileSystem 的读操作，入口处按调用方做一次沙箱检查
+    pub fn check_read(&self, caller: Caller, path: &str) -> Result<(), FsError> {
+        let fs = self.fs_for(caller);
+        fs.check_access(path, Access::Read, fs.sandbox_mode())
+    }
+
+    /// 用户确认后允许打开超大的压缩包及包内条目 (非压缩包路径忽略)
+    pub fn force_archive(&self, path: &str) {
+        if let Some((archive_path, _)) = archive::split(path) {
+            self.archives.force(archive_path);
+            self.archives.force(path);
+        }
+    }
+
+    /// 为 scheme 注册文件系统实现 (虚拟文档、远程存储等)
+    pub fn mount(&self, scheme: &str, fs: SharedFs) -> Result<(), FsError> {
+        self.fs.mount(scheme, fs)
+    }
+
+    /// 写入文件并记录本地历史，返回新的版本标记
+    /// 磁盘上的旧内容若未被记录过 (外部修改)，会先补记一份，保证可以回退
+    pub async fn write_with_history(&self, fs: &dyn FileSystem, path: &str, bytes: &[u8], source: HistorySource) -> Result<String, FsError> {
+        self.write_with_history_via(fs, path, bytes, source, fs.write_bytes(path, bytes)).await
+    }
+
+    /// 同 write_with_history，但实际写入由调用方提供 (如提权写入)，在记录写入前快照之后才执行
+    pub async fn write_with_history_via(
+        &self,
+        fs: &dyn FileSystem,
+        path: &str,
+        bytes: &[u8],
+        source: HistorySource,
+        write: impl std::future::Future<Output = Result<(), FsError>>,
+    ) -> Result<String, FsError> {
+        self.snapshot_before_write(fs, path).await;
+        write.await?;
+        self.record_history(path, bytes, source).await;
+        fs.version_of(path, bytes).await
+    }
+
+    /// 记录写入前磁盘上的内容 (与最近一份快照相同时不会重复记录)
+    pub async fn snapshot_before_write(&self, fs: &dyn FileSystem, path: &str) {
+        if !self.history.enabled() {
+            return;
+        }
+        let small_file = fs.stat(path).await.map(|s| s.file_type == "file" && !s.is_large).unwrap_or(false);
+        if small_file {
+            if let Ok(previous) = fs.read_bytes(path).await {
+                self.record_history(path, &previous, HistorySource::External).await;
+            }
+        }
+    }
+
+    /// 本地历史按规范化的绝对路径记录，`foo.txt`、`/abs/foo.txt` 与 `file:/abs/foo.txt` 共用一条时间线
+    pub fn history_key(&self, path: &str) -> String {
+        self.fs.absolute_path(path)
+    }
+
+    /// 记录历史失败不影响保存本身，只写日志
+    pub async fn record_history(&self, path: &str, bytes: &[u8], source: HistorySource) {
+        if let Err(e) = self.history.record(&self.history_key(path), bytes, source).await {
+            log::warn!("Failed to record local history for {}: {}", path, e);
+        }
     }
 }
 
@@ -25,81 +107,282 @@ pub async fn get_cwd(ws: State<'_, WorkspaceService>) -> Result<String, String>
     Ok(ws.fs.get_cwd())
 }
 
+/// 切换工作区；传入 .zyma-workspace 文件时打开多根工作区
+/// 离开的工作区会话会被保存，新工作区的会话通过 "session_loaded" 事件推送
 #[tauri::command]
 pub async fn fs_set_cwd(
     app_handle: tauri::AppHandle,
     ws: State<'_, WorkspaceService>, 
     bus: State<'_, EventBus>,
-    path: String
-) -> Result<(), String> {
-    ws.fs.set_cwd(&path)?;
+    path: String,
+    caller: IpcCaller
+) -> Result<(), FsError> {
+    if workspace::is_workspace_file(&path) {
+        crate::commands::workspace::open_workspace_file(&app_handle, &ws, &bus, &path, caller.0)?;
+        return Ok(());
+    }
+    ws.fs_for(caller.0).set_cwd(&path)?;
     
     // 归一化路径以便去重和一致性显示
     let normalized_path = path.replace("\\", "/");
-    let lower_path = normalized_path.to_lowercase();
 
     // 自动记录到最近工作区
-    if let Ok(mut settings) = crate::commands::config::load_settings() {
-        // 去重并插入首位 (大小写不敏感，防止 Windows 下盘符大小写导致的重复)
-        settings.recent_workspaces.retain(|p| {
-            p.replace("\\", "/").to_lowercase() != lower_path
-        });
-        settings.recent_workspaces.insert(0, normalized_path.clone());
-        // 只保留最近 10 个
-        if settings.recent_workspaces.len() > 10 {
-            settings.recent_workspaces.truncate(10);
-        }
-        let _ = crate::commands::config::save_settings(settings);
-    }
+    crate::commands::workspace::record_workspace(&normalized_path, None, &ws.fs.roots());
 
     let _ = app_handle.emit("workspace_changed", &normalized_path);
+    crate::commands::session::switch_session(&app_handle, &ws.fs.get_cwd());
     bus.publish(ZymaEvent::WorkspaceChanged(normalized_path));
     Ok(())
 }
 
+/// 超过大文件阈值的压缩包返回 FileTooLarge，用户确认后以 force 重新展开
 #[tauri::command]
-pub async fn read_dir(ws: State<'_, WorkspaceService>, path: String) -> Result<Vec<FileItem>, String> {
-    ws.fs.read_dir(&path).await
+pub async fn read_dir(ws: State<'_, WorkspaceService>, path: String, force: Option<bool>, caller: IpcCaller) -> Result<Vec<FileItem>, FsError> {
+    if force.unwrap_or(false) {
+        ws.force_archive(&path);
+    }
+    ws.fs_for(caller.0).read_dir(&path).await
 }
 
+/// 用户打开的文件会被登记，受限沙箱模式下仍可继续读写
+/// 超过大文件阈值时返回 FileTooLarge，除非 force 为 true (此时改用 fs_read_lines 分页读取更合适)
 #[tauri::command]
-pub async fn read_file(ws: State<'_, WorkspaceService>, path: String) -> Result<crate::models::FileReadResponse, String> {
-    ws.fs.read_file(&path).await
+pub async fn read_file(ws: State<'_, WorkspaceService>, path: String, force: Option<bool>, caller: IpcCaller) -> Result<FileReadResponse, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.allow_path(&path);
+    if force.unwrap_or(false) {
+        ws.force_archive(&path);
+    } else if let Ok(stat) = fs.stat(&path).await {
+        if stat.is_large {
+            return Err(FsError::FileTooLarge { path, size: stat.size });
+        }
+    }
+    fs.read_file(&path).await
 }
 
+/// 保存文件并返回新的版本标记
+/// 传入 expected_version 时若磁盘已被外部修改，则返回 Conflict 而不是覆盖
 #[tauri::command]
-pub async fn write_file(app_handle: tauri::AppHandle, ws: State<'_, WorkspaceService>, bus: State<'_, EventBus>, path: String, content: String) -> Result<(), String> {
-    ws.fs.write_file(&path, &content).await?;
+#[allow(clippy::too_many_arguments)]
+pub async fn write_file(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    content: String,
+    options: Option<WriteOptions>,
+    expected_version: Option<String>,
+    caller: IpcCaller
+) -> Result<String, FsError> {
+    let fs = ws.fs_for(caller.0);
+    if let Some(expected) = expected_version {
+        fs.check_version(&path, &expected).await?;
+    }
+    let bytes = encoding::encode(&content, &options.unwrap_or_default())?;
+    let version = ws.write_with_history(&fs, &path, &bytes, HistorySource::for_caller(caller.0)).await?;
     let _ = app_handle.emit("file_saved", &path);
     bus.publish(ZymaEvent::FileSaved(path));
-    Ok(())
+    Ok(version)
+}
+
+/// 以管理员身份保存 (write_file 返回 not_writable 后由用户确认发起)
+/// 仅接受本地绝对路径；提权写入只对用户开放，插件与 Agent 不可调用
+#[tauri::command]
+pub async fn write_file_elevated(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    content: String,
+    options: Option<WriteOptions>,
+    caller: IpcCaller
+) -> Result<String, FsError> {
+    caller.require_user()?;
+    if !std::path::Path::new(&path).is_absolute() {
+        return Err(format!("Saving as administrator requires an absolute local path: {}", path).into());
+    }
+    ws.fs.check_access(&path, Access::Write, ws.fs.sandbox_mode())?;
+    let bytes = encoding::encode(&content, &options.unwrap_or_default())?;
+    // 备份策略与普通保存一致，由辅助进程在同一次提权中完成复制
+    let target = std::path::Path::new(&path);
+    let policy = BackupPolicy::from_settings(&crate::commands::config::store().settings());
+    let backup = atomic::plan_backup(target, policy).await;
+    let write = permissions::write_elevated(target, &bytes, backup);
+    let version = ws.write_with_history_via(&ws.fs, &path, &bytes, HistorySource::Save, write).await?;
+    let _ = app_handle.emit("file_saved", &path);
+    bus.publish(ZymaEvent::FileSaved(path));
+    Ok(version)
+}
+
+#[tauri::command]
+pub fn list_encodings() -> Vec<String> {
+    encoding::COMMON_ENCODINGS.iter().map(|e| e.to_string()).collect()
 }
 
+/// 以指定编码重新打开文件 (纠正自动检测的误判)
 #[tauri::command]
-pub async fn create_file(ws: State<'_, WorkspaceService>, bus: State<'_, EventBus>, path: String) -> Result<(), String> {
-    ws.fs.create_file(&path).await?;
+pub async fn read_file_with_encoding(ws: State<'_, WorkspaceService>, path: String, encoding: String, caller: IpcCaller) -> Result<FileReadResponse, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.allow_path(&path);
+    let bytes = fs.read_bytes(&path).await?;
+    let mut res = encoding::decode_with(&bytes, &encoding)?;
+    res.version = Some(fs.version_of(&path, &bytes).await?);
+    Ok(res)
+}
+
+/// 以指定编码保存文件，不可编码的字符会以结构化错误返回其行列位置
+/// expected_version 与 write_file 相同，用于检测外部修改
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn write_file_with_encoding(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    content: String,
+    encoding: String,
+    has_bom: Option<bool>,
+    line_ending: Option<String>,
+    expected_version: Option<String>,
+    caller: IpcCaller
+) -> Result<String, FsError> {
+    let fs = ws.fs_for(caller.0);
+    if let Some(expected) = expected_version {
+        fs.check_version(&path, &expected).await?;
+    }
+    let options = WriteOptions { encoding: Some(encoding), has_bom: has_bom.unwrap_or(false), line_ending };
+    let bytes = encoding::encode(&content, &options)?;
+    let version = ws.write_with_history(&fs, &path, &bytes, HistorySource::for_caller(caller.0)).await?;
+    let _ = app_handle.emit("file_saved", &path);
+    bus.publish(ZymaEvent::FileSaved(path));
+    Ok(version)
+}
+
+#[tauri::command]
+pub async fn create_file(ws: State<'_, WorkspaceService>, bus: State<'_, EventBus>, path: String, caller: IpcCaller) -> Result<(), FsError> {
+    ws.fs_for(caller.0).create_file(&path).await?;
     bus.publish(ZymaEvent::FileCreated(path));
     Ok(())
 }
 
 #[tauri::command]
-pub async fn create_dir(ws: State<'_, WorkspaceService>, path: String) -> Result<(), String> {
-    ws.fs.create_dir(&path).await
+pub async fn create_dir(ws: State<'_, WorkspaceService>, path: String, caller: IpcCaller) -> Result<(), FsError> {
+    ws.fs_for(caller.0).create_dir(&path).await
 }
 
+/// 删除文件或目录，默认移入回收站；permanent 为 true 时永久删除
+/// 返回是否进入了回收站
 #[tauri::command]
-pub async fn remove_item(ws: State<'_, WorkspaceService>, bus: State<'_, EventBus>, path: String) -> Result<(), String> {
-    ws.fs.remove_item(&path).await?;
-    bus.publish(ZymaEvent::FileDeleted(path));
+pub async fn remove_item(ws: State<'_, WorkspaceService>, bus: State<'_, EventBus>, path: String, permanent: Option<bool>, caller: IpcCaller) -> Result<bool, FsError> {
+    let fs = ws.fs_for(caller.0);
+    let trashed = if permanent.unwrap_or(false) {
+        fs.remove_item(&path).await?;
+        false
+    } else {
+        fs.trash_item(&path).await?
+    };
+    bus.publish(ZymaEvent::FileDeleted { path, trashed });
+    Ok(trashed)
+}
+
+#[tauri::command]
+pub async fn rename_item(ws: State<'_, WorkspaceService>, bus: State<'_, EventBus>, at: String, to: String, caller: IpcCaller) -> Result<(), FsError> {
+    ws.fs_for(caller.0).rename_item(&at, &to).await?;
+    bus.publish(ZymaEvent::FileMoved { from: at, to });
     Ok(())
 }
 
+/// 复制文件或目录，返回实际写入的路径 (按 Skip 策略跳过时为 null)
+/// 进度通过 "fs_transfer_progress" 事件推送，op_id 用于前端区分并发的任务
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn copy_item(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    from: String,
+    to: String,
+    policy: Option<ConflictPolicy>,
+    op_id: Option<String>,
+    caller: IpcCaller
+) -> Result<Option<String>, FsError> {
+    let op_id = op_id.unwrap_or_else(|| to.clone());
+    let progress = |p: TransferProgress| { let _ = app_handle.emit("fs_transfer_progress", p); };
+    let target = ws.fs_for(caller.0).copy_item(&from, &to, policy.unwrap_or_default(), &op_id, &progress).await?;
+    if let Some(t) = &target {
+        bus.publish(ZymaEvent::FileCreated(t.clone()));
+    }
+    Ok(target)
+}
+
+/// 在同一目录下创建副本 ("a copy.txt")
+#[tauri::command]
+pub async fn duplicate_item(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    caller: IpcCaller
+) -> Result<Option<String>, FsError> {
+    copy_item(app_handle, ws, bus, path.clone(), path, Some(ConflictPolicy::Rename), None, caller).await
+}
+
+/// 移动文件或目录，支持跨磁盘 (退化为复制 + 删除)
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn move_item(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    from: String,
+    to: String,
+    policy: Option<ConflictPolicy>,
+    op_id: Option<String>,
+    caller: IpcCaller
+) -> Result<Option<String>, FsError> {
+    let op_id = op_id.unwrap_or_else(|| to.clone());
+    let progress = |p: TransferProgress| { let _ = app_handle.emit("fs_transfer_progress", p); };
+    let target = ws.fs_for(caller.0).move_item(&from, &to, policy.unwrap_or_default(), &op_id, &progress).await?;
+    if let Some(t) = &target {
+        bus.publish(ZymaEvent::FileMoved { from, to: t.clone() });
+    }
+    Ok(target)
+}
+
+/// 已注册的虚拟文件系统 scheme
+#[tauri::command]
+pub async fn fs_list_schemes(ws: State<'_, WorkspaceService>) -> Result<Vec<String>, String> {
+    Ok(ws.fs.schemes())
+}
+
+/// 读取符号链接的目标 (原样返回，不解析相对路径)
+#[tauri::command]
+pub async fn fs_read_link(ws: State<'_, WorkspaceService>, path: String, caller: IpcCaller) -> Result<String, FsError> {
+    ws.fs_for(caller.0).read_link(&path).await
+}
+
+/// 按 chmod 语法修改权限 ("+x"、"644"、"u+x,go-w")，返回新的权限位
+#[tauri::command]
+pub async fn fs_chmod(ws: State<'_, WorkspaceService>, path: String, mode: String, caller: IpcCaller) -> Result<u32, FsError> {
+    let fs = ws.fs_for(caller.0);
+    let current = fs.stat(&path).await?.attrs.mode.unwrap_or(0o644);
+    let new_mode = permissions::apply_mode_spec(current, &mode)?;
+    fs.set_mode(&path, new_mode).await?;
+    Ok(new_mode)
+}
+
+#[tauri::command]
+pub async fn fs_set_readonly(ws: State<'_, WorkspaceService>, path: String, readonly: bool, caller: IpcCaller) -> Result<(), FsError> {
+    ws.fs_for(caller.0).set_readonly(&path, readonly).await
+}
+
+/// 当前用户能否写入该路径 (打开文件时用于提示只读)
 #[tauri::command]
-pub async fn rename_item(ws: State<'_, WorkspaceService>, at: String, to: String) -> Result<(), String> {
-    ws.fs.rename_item(&at, &to).await
+pub async fn fs_is_writable(ws: State<'_, WorkspaceService>, path: String, caller: IpcCaller) -> Result<bool, FsError> {
+    ws.fs_for(caller.0).is_writable(&path).await
 }
 
 #[tauri::command]
-pub async fn fs_stat(ws: State<'_, WorkspaceService>, path: String) -> Result<FileStat, String> {
-    ws.fs.stat(&path).await
+pub async fn fs_stat(ws: State<'_, WorkspaceService>, path: String, caller: IpcCaller) -> Result<FileStat, FsError> {
+    ws.fs_for(caller.0).stat(&path).await
 }
\ No newline at end of file
@@ -0,0 +1,68 @@
+use serde::Serialize;
+use tauri::{State, Emitter};
+use crate::bus::{EventBus, ZymaEvent};
+use crate::commands::fs::WorkspaceService;
+use crate::models::FileReadResponse;
+use crate::services::diff::{self, DiffHunk};
+use crate::services::encoding;
+use crate::services::history::{HistoryEntry, HistorySource};
+use crate::commands::caller::IpcCaller;
+use crate::services::sandbox::Access;
+use crate::services::vfs::{FileSystem, FsError};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HistoryDiff {
+    pub hunks: Vec<DiffHunk>,
+    /// `diff -u` 风格的文本，方便直接展示或复制
+    pub unified: String,
+}
+
+/// 列出文件的本地历史快照，最新的在前
+#[tauri::command]
+pub async fn history_list(ws: State<'_, WorkspaceService>, path: String, caller: IpcCaller) -> Result<Vec<HistoryEntry>, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.check_access(&path, Access::Read, fs.sandbox_mode())?;
+    ws.history.list(&ws.history_key(&path)).await
+}
+
+/// 读取一份快照的内容 (按文件内容自动检测编码)
+#[tauri::command]
+pub async fn history_read(ws: State<'_, WorkspaceService>, path: String, id: String, caller: IpcCaller) -> Result<FileReadResponse, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.check_access(&path, Access::Read, fs.sandbox_mode())?;
+    let bytes = ws.history.read(&ws.history_key(&path), &id).await?;
+    Ok(encoding::decode(&bytes))
+}
+
+/// 比较快照与文件当前内容 (快照为旧、当前为新)；文件已被删除时视为空文件
+#[tauri::command]
+pub async fn history_diff(ws: State<'_, WorkspaceService>, path: String, id: String, caller: IpcCaller) -> Result<HistoryDiff, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.check_access(&path, Access::Read, fs.sandbox_mode())?;
+    let snapshot = encoding::decode(&ws.history.read(&ws.history_key(&path), &id).await?).content;
+    let current = match fs.read_bytes(&path).await {
+        Ok(bytes) => encoding::decode(&bytes).content,
+        Err(_) => String::new(),
+    };
+    let hunks = diff::diff_lines(&snapshot, &current);
+    let unified = diff::to_unified(&hunks, &format!("{} ({})", path, id), &path);
+    Ok(HistoryDiff { hunks, unified })
+}
+
+/// 用快照覆盖当前文件并返回新的版本标记；覆盖前的内容同样会留在历史中
+#[tauri::command]
+pub async fn history_restore(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    id: String,
+    caller: IpcCaller
+) -> Result<String, FsError> {
+    let fs = ws.fs_for(caller.0);
+    let bytes = ws.history.read(&ws.history_key(&path), &id).await?;
+    let version = ws.write_with_history(&fs, &path, &bytes, HistorySource::Restore).await?;
+    let _ = app_handle.emit("file_saved", &path);
+    bus.publish(ZymaEvent::FileSaved(path));
+    Ok(version)
+}
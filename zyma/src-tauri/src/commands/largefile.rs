@@ -0,0 +1,82 @@
+use tauri::{Manager, State, Emitter};
+use crate::commands::fs::WorkspaceService;
+use crate::services::vfs::{FileSystem, FsError};
+use crate::commands::caller::IpcCaller;
+use crate::services::largefile::{self, LineIndexService, LineIndexStatus, LinesResponse, RangeResponse};
+
+/// 读取字节范围 (用于十六进制查看或按偏移跳转)
+#[tauri::command]
+pub async fn fs_read_range(
+    ws: State<'_, WorkspaceService>,
+    path: String,
+    offset: u64,
+    length: usize,
+    encoding: Option<String>,
+    caller: IpcCaller
+) -> Result<RangeResponse, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.allow_path(&path);
+    largefile::read_range(&fs, &path, offset, length, encoding.as_deref()).await
+}
+
+/// 按行分页读取 (行号从 0 开始)，索引已建立时从最近的检查点开始扫描
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn fs_read_lines(
+    ws: State<'_, WorkspaceService>,
+    indexes: State<'_, LineIndexService>,
+    path: String,
+    start_line: u64,
+    count: usize,
+    encoding: Option<String>,
+    caller: IpcCaller
+) -> Result<LinesResponse, FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.allow_path(&path);
+    let stat = fs.stat(&path).await?;
+    // 文件已变化的旧索引不能再用于定位
+    let index = indexes.get(&path).filter(|i| i.matches(stat.size, stat.mtime));
+    largefile::read_lines(&fs, index.as_deref(), &path, start_line, count, encoding.as_deref()).await
+}
+
+/// 在后台建立行偏移索引，通过 "line_index_progress" 事件汇报进度，完成时带有总行数
+#[tauri::command]
+pub async fn fs_build_line_index(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    indexes: State<'_, LineIndexService>,
+    path: String,
+    caller: IpcCaller
+) -> Result<LineIndexStatus, FsError> {
+    let stat = ws.fs_for(caller.0).stat(&path).await?;
+    let (index, is_new) = indexes.get_or_create(&path, stat.size, stat.mtime);
+    let status = index.status(&path);
+    if is_new {
+        let app = app_handle.clone();
+        let task_path = path.clone();
+        tauri::async_runtime::spawn(async move {
+            let ws = app.state::<WorkspaceService>();
+            let fs = ws.fs_for(caller.0);
+            let progress = |s: LineIndexStatus| { let _ = app.emit("line_index_progress", s); };
+            if let Err(e) = index.build(&fs, &task_path, &progress).await {
+                log::error!("Failed to index {}: {}", task_path, e);
+                app.state::<LineIndexService>().remove(&task_path);
+            }
+        });
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn fs_line_index_status(ws: State<'_, WorkspaceService>, indexes: State<'_, LineIndexService>, path: String, caller: IpcCaller) -> Result<Option<LineIndexStatus>, FsError> {
+    ws.check_read(caller.0, &path)?;
+    Ok(indexes.get(&path).map(|i| i.status(&path)))
+}
+
+/// 文件关闭后释放索引
+#[tauri::command]
+pub async fn fs_drop_line_index(ws: State<'_, WorkspaceService>, indexes: State<'_, LineIndexService>, path: String, caller: IpcCaller) -> Result<(), FsError> {
+    ws.check_read(caller.0, &path)?;
+    indexes.remove(&path);
+    Ok(())
+}
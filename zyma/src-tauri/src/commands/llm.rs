@@ -1,6 +1,10 @@
 use tauri::{AppHandle, State, Runtime};
 use tauri::ipc::Channel;
 use futures::StreamExt;
+use std::sync::Arc;
+use crate::commands::fs::WorkspaceService;
+use crate::commands::secrets::resolve_secret;
+use crate::services::secrets::{ai_api_key_id, SecretService};
 use crate::llm::manager::LLMManager;
 use crate::llm::types::ChatCompletionRequest;
 
@@ -8,10 +12,15 @@ use crate::llm::types::ChatCompletionRequest;
 pub async fn llm_chat<R: Runtime>(
     _app: AppHandle<R>,
     llm: State<'_, LLMManager>,
+    ws: State<'_, WorkspaceService>,
+    secrets: State<'_, Arc<SecretService>>,
     request: ChatCompletionRequest,
     on_event: Channel<String>,
 ) -> Result<(), String> {
-    let settings = crate::commands::config::load_settings().unwrap_or_default();
+    // 模型可按工作区覆盖，服务商、凭据与地址只取自用户设置
+    let settings = crate::commands::config::effective_settings(&ws, None, None)
+        .map(|e| e.settings)
+        .unwrap_or_default();
     
     // 补齐模型字段
     let mut request = request;
@@ -20,7 +29,13 @@ pub async fn llm_chat<R: Runtime>(
     }
 
     let base_url = settings.ai_base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string());
-    let api_key = settings.ai_api_key.unwrap_or_default();
+    // 密钥按引用在后端取出，不经过前端；只使用为当前服务商保存的密钥
+    let api_key = match settings.ai_api_key_ref.clone() {
+        Some(_) => resolve_secret(secrets.inner().clone(), ai_api_key_id(settings.ai_provider.as_deref())).await?
+            .ok_or_else(|| format!("No AI API key is stored for provider \"{}\"", settings.ai_provider.as_deref().unwrap_or_default()))?,
+        // 尚未迁移的旧版配置
+        None => settings.ai_api_key.clone().unwrap_or_default(),
+    };
 
     let mut stream = llm.stream_chat(&base_url, &api_key, &request).await.map_err(|e| {
         eprintln!("[LLM Error] Request failed: {}", e);
@@ -1,3 +1,4 @@
+pub mod caller;
 pub mod config;
 pub mod fs;
 pub mod system;
@@ -8,24 +9,89 @@ pub mod search;
 pub mod watcher;
 pub mod llm;
 pub mod context;
+pub mod trash;
+pub mod workspace;
+pub mod largefile;
+pub mod binary;
+pub mod remote;
+pub mod history;
+pub mod backup;
+pub mod session;
+pub mod secrets;
+pub mod profiles;
 
 pub fn get_handlers() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send + Sync + 'static {
     tauri::generate_handler![
+        caller::caller_claim_token,
         config::load_settings, 
         config::save_settings,
+        config::settings_update,
+        config::settings_schema,
+        config::settings_diagnostics,
+        secrets::secrets_status,
+        secrets::secrets_unlock,
+        secrets::secrets_lock,
+        secrets::secrets_set,
+        secrets::secrets_delete,
+        profiles::profile_list,
+        profiles::profile_create,
+        profiles::profile_switch,
+        profiles::profile_delete,
+        profiles::profile_export,
+        profiles::profile_import,
         fs::read_dir, 
         fs::get_cwd,
         fs::fs_set_cwd,
+        workspace::workspace_open,
+        workspace::workspace_get_folders,
+        workspace::workspace_add_folder,
+        workspace::workspace_remove_folder,
+        workspace::workspace_save,
+        workspace::workspace_forget_recent,
         fs::read_file, 
         fs::write_file, 
+        fs::read_file_with_encoding,
+        fs::write_file_with_encoding,
+        fs::list_encodings,
         fs::create_file, 
         fs::create_dir, 
         fs::remove_item, 
         fs::rename_item,
+        fs::copy_item,
+        fs::duplicate_item,
+        fs::move_item,
         fs::fs_stat,
+        fs::fs_read_link,
+        fs::fs_chmod,
+        fs::fs_set_readonly,
+        fs::fs_is_writable,
+        fs::write_file_elevated,
+        fs::fs_list_schemes,
+        largefile::fs_read_range,
+        largefile::fs_read_lines,
+        largefile::fs_build_line_index,
+        largefile::fs_line_index_status,
+        largefile::fs_drop_line_index,
+        binary::fs_hex_dump,
+        binary::fs_patch_bytes,
+        session::session_load,
+        session::session_save,
+        backup::backup_buffer,
+        backup::backup_discard,
+        history::history_list,
+        history::history_read,
+        history::history_diff,
+        history::history_restore,
+        remote::ssh_connect,
+        remote::ssh_disconnect,
+        remote::ssh_list_connections,
+        trash::trash_list,
+        trash::trash_restore,
+        trash::trash_empty,
         search::search_in_dir,
         search::fs_find_files,
         watcher::fs_watch,
+        watcher::fs_watch_workspace,
         watcher::fs_unwatch,
         output::output_append,
         output::output_get_content,
@@ -36,6 +102,7 @@ pub fn get_handlers() -> impl Fn(tauri::ipc::Invoke<tauri::Wry>) -> bool + Send
         context::get_all_contexts,
         system::manage_context_menu, 
         system::get_cli_args, 
+        system::get_app_dirs,
         system::system_get_env,
         system::system_exec,
         system::emit_global_event,
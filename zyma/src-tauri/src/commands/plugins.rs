@@ -4,6 +4,7 @@ use std::path::{Path, PathBuf};
 use crate::{NativeChatParticipant, NativeAuthProvider, NativeSidebarItem, NativeSlotComponent, NativeFileMenuItem};
 use std::sync::RwLock;
 use tauri::Emitter;
+use crate::commands::caller::IpcCaller;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
 pub struct NativeCommand {
@@ -85,25 +86,24 @@ pub fn list_plugins(
     let mut plugins = Vec::new();
     let mut seen_names = std::collections::HashSet::new();
 
-    let system_paths = vec![
-        PathBuf::from("../plugins"),
-        PathBuf::from("plugins"),
-    ];
-    let user_path = get_user_plugins_dir();
-
-    for p_dir in system_paths {
-        scan_dir(&p_dir, true, &mut plugins, &mut seen_names);
-    }
-    scan_dir(&user_path, false, &mut plugins, &mut seen_names);
-
-    // 扫描通过命令行参数传入的动态路径
-    for p_dir in &plugin_service.external_plugins {
-        scan_dir(p_dir, false, &mut plugins, &mut seen_names);
+    for (p_dir, is_builtin) in plugin_dirs(&plugin_service) {
+        scan_dir(&p_dir, is_builtin, &mut plugins, &mut seen_names);
     }
 
     Ok(plugins)
 }
 
+/// 插件搜索目录：内置目录、用户插件目录，以及通过命令行参数传入的动态路径
+fn plugin_dirs(plugin_service: &PluginService) -> Vec<(PathBuf, bool)> {
+    let mut dirs = vec![
+        (PathBuf::from("../plugins"), true),
+        (PathBuf::from("plugins"), true),
+        (get_user_plugins_dir(), false),
+    ];
+    dirs.extend(plugin_service.external_plugins.iter().map(|p| (p.clone(), false)));
+    dirs
+}
+
 fn scan_dir(dir: &Path, is_builtin: bool, plugins: &mut Vec<(String, PluginManifest, bool)>, seen: &mut std::collections::HashSet<String>) {
     if !dir.exists() || !dir.is_dir() { return; }
     if let Ok(entries) = fs::read_dir(dir) {
@@ -130,46 +130,28 @@ fn scan_dir(dir: &Path, is_builtin: bool, plugins: &mut Vec<(String, PluginManif
 }
 
 fn get_user_plugins_dir() -> PathBuf {
-    let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE"))
-        .unwrap_or_else(|_| ".".to_string());
-    let p = Path::new(&home).join(".zyma").join("plugins");
+    let p = crate::services::paths::dirs().data_path("plugins");
     if !p.exists() { let _ = fs::create_dir_all(&p); }
     p
 }
 
+/// 读取插件入口等文件，仅限插件搜索目录之内；由界面的插件管理器加载插件时调用，插件自身不能使用
 #[tauri::command]
-pub fn read_plugin_file(path: String) -> Result<String, String> { 
-    let mut p = PathBuf::from(&path);
-    // 尝试规范化路径以处理分隔符问题
-    if let Ok(canon) = fs::canonicalize(&p) {
-        p = canon;
-    }
-
-    // 安全校验：向上查找直到找到 manifest.json (防止读取系统任意文件)
-    let mut current = p.parent();
-    let mut found = false;
-    // 最多向上找 5 层
-    for _ in 0..5 {
-        if let Some(dir) = current {
-            if dir.join("manifest.json").exists() {
-                found = true;
-                break;
-            }
-            current = dir.parent();
-        } else {
-            break;
-        }
-    }
-    
-    // 特殊情况：如果读取的就是 manifest.json 本身
-    if !found && p.file_name().and_then(|n| n.to_str()) == Some("manifest.json") {
-         found = true;
-    }
-
-    if !found {
+pub fn read_plugin_file(
+    plugin_service: tauri::State<'_, PluginService>,
+    path: String,
+    caller: IpcCaller,
+) -> Result<String, String> {
+    caller.require_user()?;
+    // 规范化后比较，避免 .. 或符号链接跳出插件目录
+    let p = fs::canonicalize(&path).map_err(|e| e.to_string())?;
+    let in_scope = plugin_dirs(&plugin_service).into_iter()
+        .filter_map(|(dir, _)| fs::canonicalize(dir).ok())
+        .any(|dir| p.starts_with(dir));
+    if !in_scope {
         return Err(format!("Unauthorized: Cannot read files outside plugin scope. Path: {}", path));
     }
-    fs::read_to_string(p).map_err(|e| e.to_string()) 
+    fs::read_to_string(p).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -0,0 +1,77 @@
+use serde_json::{Map, Value};
+use crate::commands::caller::IpcCaller;
+use crate::commands::config::store;
+use crate::services::config_file::strip_jsonc;
+use crate::services::profiles::{self, ProfilesExport, ProfilesInfo};
+
+fn update<F>(f: F) -> Result<ProfilesInfo, String>
+where
+    F: FnOnce(&mut Map<String, Value>) -> Result<(), String>,
+{
+    store().update_values(f)?;
+    Ok(profiles::info(&store().values()))
+}
+
+/// 全部档案及当前使用的档案
+#[tauri::command]
+pub fn profile_list() -> ProfilesInfo {
+    profiles::info(&store().values())
+}
+
+/// 新建档案，未给出 values 时保存当前的外观、AI 与插件设置
+#[tauri::command]
+pub fn profile_create(name: String, values: Option<Map<String, Value>>, caller: IpcCaller) -> Result<ProfilesInfo, String> {
+    caller.require_user()?;
+    update(|v| profiles::create(v, &name, values.as_ref()))
+}
+
+#[tauri::command]
+pub fn profile_switch(name: String, caller: IpcCaller) -> Result<ProfilesInfo, String> {
+    caller.require_user()?;
+    update(|v| profiles::switch(v, &name))
+}
+
+/// 删除档案；删除正在使用的档案时当前设置保持不变
+#[tauri::command]
+pub fn profile_delete(name: String, caller: IpcCaller) -> Result<ProfilesInfo, String> {
+    caller.require_user()?;
+    update(|v| profiles::delete(v, &name))
+}
+
+/// 导出到单个 JSON 文件，未指定 names 时导出全部档案
+#[tauri::command]
+pub fn profile_export(path: String, names: Option<Vec<String>>, caller: IpcCaller) -> Result<(), String> {
+    caller.require_user()?;
+    let file = profiles::export(&store().values(), names.as_deref())?;
+    let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 从导出文件导入，同名档案被覆盖，返回导入的档案名
+#[tauri::command]
+pub fn profile_import(path: String, caller: IpcCaller) -> Result<Vec<String>, String> {
+    caller.require_user()?;
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let file: ProfilesExport = serde_json::from_str(&strip_jsonc(&content))
+        .map_err(|e| format!("Invalid profiles file: {}", e))?;
+    let mut imported = Vec::new();
+    store().update_values(|v| {
+        imported = profiles::import(v, &file)?;
+        Ok(())
+    })?;
+    Ok(imported)
+}
+
+/// 启动时应用记录的档案，只有设置确实需要变化时才写回
+pub fn apply_active_profile() {
+    let mut values = store().values();
+    if !profiles::apply_active(&mut values) {
+        return;
+    }
+    if let Err(e) = store().update_values(|v| {
+        profiles::apply_active(v);
+        Ok(())
+    }) {
+        log::error!("Failed to apply the active profile: {}", e);
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use tauri::{State, Emitter};
+use crate::commands::caller::IpcCaller;
+use crate::commands::config::load_user_settings;
+use crate::services::sftp::{RemoteConnection, SftpFileSystem, SshCredentials, SshError};
+
+fn connections_changed(app_handle: &tauri::AppHandle, sftp: &SftpFileSystem) -> Vec<RemoteConnection> {
+    let list = sftp.connections();
+    let _ = app_handle.emit("ssh_connections_changed", &list);
+    list
+}
+
+/// 按 settings 中的 ssh_profiles 建立 SFTP 连接，成功后可通过 `sftp://<id>/...` 访问远程文件
+/// password / passphrase 仅用于本次连接 (由前端向配置的认证提供者索取，或询问用户)
+/// 遇到未知主机密钥时返回 unknown_host_key，用户确认后以 accept_host_key 重试
+#[tauri::command]
+pub async fn ssh_connect(
+    app_handle: tauri::AppHandle,
+    sftp: State<'_, Arc<SftpFileSystem>>,
+    profile_id: String,
+    password: Option<String>,
+    passphrase: Option<String>,
+    accept_host_key: Option<bool>,
+    caller: IpcCaller
+) -> Result<RemoteConnection, SshError> {
+    // 连接会写入 known_hosts 并挂载整台远程主机，仅对用户开放
+    caller.require_user()?;
+    let profile = load_user_settings()?.ssh_profiles.into_iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| format!("Unknown SSH profile: {}", profile_id))?;
+    let credentials = SshCredentials { password, passphrase };
+    let service = sftp.inner().clone();
+    let info = tokio::task::spawn_blocking(move || service.connect(&profile, &credentials, accept_host_key.unwrap_or(false)))
+        .await
+        .map_err(|e| e.to_string())??;
+    connections_changed(&app_handle, &sftp);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn ssh_disconnect(
+    app_handle: tauri::AppHandle,
+    sftp: State<'_, Arc<SftpFileSystem>>,
+    profile_id: String,
+    caller: IpcCaller
+) -> Result<bool, String> {
+    caller.require_user()?;
+    let service = sftp.inner().clone();
+    let was_connected = tokio::task::spawn_blocking(move || service.disconnect(&profile_id))
+        .await
+        .map_err(|e| e.to_string())?;
+    connections_changed(&app_handle, &sftp);
+    Ok(was_connected)
+}
+
+#[tauri::command]
+pub async fn ssh_list_connections(sftp: State<'_, Arc<SftpFileSystem>>) -> Result<Vec<RemoteConnection>, String> {
+    Ok(sftp.connections())
+}
@@ -1,4 +1,9 @@
+use std::path::PathBuf;
 use crate::models::SearchResult;
+use crate::commands::caller::IpcCaller;
+use crate::commands::fs::WorkspaceService;
+use crate::services::FileSystem;
+use crate::services::binary;
 use ignore::WalkBuilder;
 use std::sync::mpsc;
 use globset::{Glob, GlobSetBuilder};
@@ -6,22 +11,23 @@ use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
 use regex::RegexBuilder;
 
-// 简单的二进制文件检测：检查前 1024 字节是否包含 NULL 字符
+// 二进制文件检测：采样文件开头，规则与打开文件时一致
 fn is_binary(path: &std::path::Path) -> bool {
-    let mut file = match File::open(path) {
+    let file = match File::open(path) {
         Ok(f) => f,
         Err(_) => return false,
     };
-    let mut buffer = [0; 1024];
-    let n = match file.read(&mut buffer) {
-        Ok(n) => n,
-        Err(_) => return false,
-    };
-    buffer[..n].contains(&0)
+    let mut buffer = Vec::with_capacity(binary::SNIFF_LEN);
+    if file.take(binary::SNIFF_LEN as u64).read_to_end(&mut buffer).is_err() {
+        return false;
+    }
+    binary::is_binary(&buffer)
 }
 
+/// 插件与 Agent 只能在沙箱允许的目录中查找
 #[tauri::command]
-pub fn fs_find_files(base_dir: String, include: String, exclude: Option<String>) -> Result<Vec<String>, String> {
+pub fn fs_find_files(ws: tauri::State<'_, WorkspaceService>, base_dir: String, include: String, exclude: Option<String>, caller: IpcCaller) -> Result<Vec<String>, String> {
+    ws.check_read(caller.0, &base_dir)?;
     let include_glob = Glob::new(&include).map_err(|e| e.to_string())?.compile_matcher();
     let exclude_matcher = if let Some(ex) = exclude {
         let mut builder = GlobSetBuilder::new();
@@ -50,16 +56,20 @@ pub fn fs_find_files(base_dir: String, include: String, exclude: Option<String>)
     Ok(results)
 }
 
+/// 在目录中搜索；未指定 root 时搜索工作区的全部根目录 (root 同样受调用方的沙箱限制)
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn search_in_dir(
-    root: String, 
+    ws: tauri::State<'_, WorkspaceService>,
+    root: Option<String>, 
     pattern: String, 
     mode: Option<String>,
     case_sensitive: Option<bool>,
     whole_word: Option<bool>,
     use_regex: Option<bool>,
     include: Option<String>,
-    exclude: Option<String>
+    exclude: Option<String>,
+    caller: IpcCaller
 ) -> Result<Vec<SearchResult>, String> {
     if pattern.is_empty() { return Ok(Vec::new()); }
     let search_mode = mode.unwrap_or_else(|| "content".to_string());
@@ -97,8 +107,20 @@ pub fn search_in_dir(
         b.build().ok()
     });
 
+    let roots: Vec<PathBuf> = match root {
+        Some(r) => {
+            ws.check_read(caller.0, &r)?;
+            vec![PathBuf::from(r)]
+        }
+        None => ws.fs.roots().into_iter().map(|f| PathBuf::from(f.path)).collect(),
+    };
+    let mut builder = WalkBuilder::new(&roots[0]);
+    for r in &roots[1..] {
+        builder.add(r);
+    }
+
     let (tx, rx) = mpsc::channel();
-    let walker = WalkBuilder::new(&root)
+    let walker = builder
         .threads(std::cmp::min(8, num_cpus::get()))
         .build_parallel();
 
@@ -107,7 +129,7 @@ pub fn search_in_dir(
         let pattern = pattern.clone();
         let regex = regex.clone();
         let search_mode = search_mode.clone();
-        let root_path = std::path::Path::new(&root).to_path_buf();
+        let roots = roots.clone();
         let include_set = include_set.clone();
         let exclude_set = exclude_set.clone();
         
@@ -118,7 +140,10 @@ pub fn search_in_dir(
             }
 
             let path = entry.path();
-            let rel_path_raw = path.strip_prefix(&root_path).unwrap_or(path);
+            // 过滤规则相对于文件所在的根目录
+            let rel_path_raw = roots.iter()
+                .find_map(|r| path.strip_prefix(r).ok())
+                .unwrap_or(path);
             let rel_path_str = rel_path_raw.to_string_lossy().replace("\\", "/");
 
             // 文件过滤
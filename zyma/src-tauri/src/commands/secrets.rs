@@ -0,0 +1,91 @@
+use std::sync::Arc;
+use tauri::State;
+use crate::commands::caller::IpcCaller;
+use crate::services::secrets::{ai_api_key_id, SecretService, SecretsStatus, AI_API_KEY_SECRET};
+
+/// 当前使用的密钥存储后端及是否已解锁
+#[tauri::command]
+pub fn secrets_status(secrets: State<'_, Arc<SecretService>>) -> SecretsStatus {
+    secrets.status()
+}
+
+/// 用主口令解锁加密文件 (不存在时以该口令新建)，随后迁移配置中残留的明文密钥
+/// 口令派生密钥较慢，与钥匙串访问一样放到阻塞线程中
+#[tauri::command]
+pub async fn secrets_unlock(secrets: State<'_, Arc<SecretService>>, passphrase: String, caller: IpcCaller) -> Result<SecretsStatus, String> {
+    caller.require_user()?;
+    let secrets = secrets.inner().clone();
+    blocking(move || {
+        secrets.unlock(&passphrase)?;
+        migrate_plaintext_keys(&secrets);
+        Ok(secrets.status())
+    }).await
+}
+
+#[tauri::command]
+pub fn secrets_lock(secrets: State<'_, Arc<SecretService>>, caller: IpcCaller) -> Result<SecretsStatus, String> {
+    caller.require_user()?;
+    secrets.lock();
+    Ok(secrets.status())
+}
+
+/// 保存密钥；没有读取密钥的命令，取值只在后端按引用进行
+#[tauri::command]
+pub async fn secrets_set(secrets: State<'_, Arc<SecretService>>, id: String, value: String, caller: IpcCaller) -> Result<(), String> {
+    caller.require_user()?;
+    let secrets = secrets.inner().clone();
+    blocking(move || secrets.set(&id, &value)).await
+}
+
+#[tauri::command]
+pub async fn secrets_delete(secrets: State<'_, Arc<SecretService>>, id: String, caller: IpcCaller) -> Result<(), String> {
+    caller.require_user()?;
+    let secrets = secrets.inner().clone();
+    blocking(move || secrets.delete(&id)).await
+}
+
+/// 按引用取出密钥
+pub async fn resolve_secret(secrets: Arc<SecretService>, id: String) -> Result<Option<String>, String> {
+    blocking(move || secrets.get(&id)).await
+}
+
+/// 钥匙串访问与口令派生都可能阻塞，放到阻塞线程中执行，避免占住 IPC 所在的异步线程
+pub async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| e.to_string())?
+}
+
+/// 把旧版配置中的明文 ai_api_key 移入密钥存储，配置中只保留引用 (ai_api_key_ref)；
+/// 尚未绑定服务商的旧引用改存到当前服务商名下。加密文件尚未解锁时暂不迁移，解锁后再进行
+pub fn migrate_plaintext_keys(secrets: &SecretService) {
+    let settings = crate::commands::config::load_user_settings().unwrap_or_default();
+    if !secrets.status().unlocked {
+        return;
+    }
+    let id = ai_api_key_id(settings.ai_provider.as_deref());
+    // 旧版的明文密钥，或尚未绑定服务商的旧引用
+    let key = match settings.ai_api_key.filter(|k| !k.is_empty()) {
+        Some(key) => key,
+        None if id != AI_API_KEY_SECRET && settings.ai_api_key_ref.as_deref() == Some(AI_API_KEY_SECRET) => {
+            match secrets.get(AI_API_KEY_SECRET) {
+                Ok(Some(key)) => key,
+                _ => return,
+            }
+        }
+        None => return,
+    };
+    if let Err(e) = secrets.set(&id, &key) {
+        log::warn!("Failed to move AI API key into the secret store: {}", e);
+        return;
+    }
+    let result = crate::commands::config::update_user_settings(|s| {
+        s.ai_api_key = None;
+        s.ai_api_key_ref = Some(id.clone());
+    });
+    if let Err(e) = result {
+        log::error!("Failed to remove plaintext AI API key from settings: {}", e);
+        return;
+    }
+    if id != AI_API_KEY_SECRET {
+        let _ = secrets.delete(AI_API_KEY_SECRET);
+    }
+}
@@ -0,0 +1,40 @@
+use tauri::{Emitter, Manager, State};
+use crate::commands::caller::IpcCaller;
+use crate::commands::fs::WorkspaceService;
+use crate::models::SessionInfo;
+use crate::services::FileSystem;
+use crate::services::session::SessionStore;
+
+/// 读取当前工作区的会话 (打开的文件、视图状态、布局)
+/// 尚无独立会话文件时沿用旧版配置中的全局会话
+#[tauri::command]
+pub async fn session_load(ws: State<'_, WorkspaceService>, sessions: State<'_, SessionStore>, caller: IpcCaller) -> Result<SessionInfo, String> {
+    caller.require_user()?;
+    let root = ws.fs.get_cwd();
+    let mut session = sessions.current(&root);
+    if session.open_files.is_empty() && session.editors.is_empty() {
+        let legacy = crate::commands::config::load_user_settings().ok().and_then(|s| s.session);
+        if let Some(legacy) = legacy.filter(|l| l.root_path.as_deref().map(|r| SessionStore::normalize_root(r) == SessionStore::normalize_root(&root)).unwrap_or(false)) {
+            session.open_files = legacy.open_files;
+            session.active_file = legacy.active_file;
+        }
+    }
+    Ok(session)
+}
+
+/// 保存当前工作区的会话；返回 false 表示会话属于已切走的工作区而被忽略
+#[tauri::command]
+pub async fn session_save(ws: State<'_, WorkspaceService>, sessions: State<'_, SessionStore>, session: SessionInfo, caller: IpcCaller) -> Result<bool, String> {
+    caller.require_user()?;
+    sessions.update(&ws.fs.get_cwd(), session)
+}
+
+/// 工作区切换后调用：保存离开的会话并通过 "session_loaded" 事件推送新工作区的会话
+pub(crate) fn switch_session(app_handle: &tauri::AppHandle, root: &str) {
+    if let Some(sessions) = app_handle.try_state::<SessionStore>() {
+        match sessions.switch(root) {
+            Ok(session) => { let _ = app_handle.emit("session_loaded", &session); }
+            Err(e) => log::error!("Failed to switch session: {}", e),
+        }
+    }
+}
@@ -32,6 +32,10 @@ pub fn exit_app<R: tauri::Runtime>(app_handle: tauri::AppHandle<R>) { app_handle
 #[tauri::command]
 pub fn get_cli_args() -> Vec<String> { std::env::args().collect() }
 
+/// 当前使用的配置、数据与缓存目录
+#[tauri::command]
+pub fn get_app_dirs() -> crate::services::paths::AppDirs { crate::services::paths::dirs().clone() }
+
 #[tauri::command]
 pub fn open_url(url: String) -> Result<(), String> {
     #[cfg(windows)] {
@@ -84,8 +88,10 @@ pub async fn manage_context_menu<R: tauri::Runtime>(_app: AppHandle<R>, _enable:
 #[derive(serde::Serialize)]
 pub struct ExecResult { pub stdout: String, pub stderr: String, pub exit_code: i32 }
 
+/// 执行任意程序，仅对用户开放
 #[tauri::command]
-pub async fn system_exec(program: String, args: Vec<String>) -> Result<ExecResult, String> {
+pub async fn system_exec(program: String, args: Vec<String>, caller: crate::commands::caller::IpcCaller) -> Result<ExecResult, String> {
+    caller.require_user()?;
     use std::process::Command;
     #[cfg(windows)] use std::os::windows::process::CommandExt;
     let mut cmd = Command::new(&program);
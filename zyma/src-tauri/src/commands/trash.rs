@@ -0,0 +1,27 @@
+use tauri::State;
+use crate::bus::{EventBus, ZymaEvent};
+use crate::commands::caller::IpcCaller;
+use crate::services::trash::{self, TrashEntry};
+
+/// 列出由 Zyma 移入回收站、且尚未被清空的条目 (含工作区之外的原路径)，仅对用户开放
+#[tauri::command]
+pub async fn trash_list(caller: IpcCaller) -> Result<Vec<TrashEntry>, String> {
+    caller.require_user()?;
+    trash::list().await
+}
+
+/// 还原会写回原路径 (可能在工作区之外)，仅对用户开放
+#[tauri::command]
+pub async fn trash_restore(bus: State<'_, EventBus>, id: String, caller: IpcCaller) -> Result<String, String> {
+    caller.require_user()?;
+    let path = trash::restore(&id).await?;
+    bus.publish(ZymaEvent::FileCreated(path.clone()));
+    Ok(path)
+}
+
+/// 永久清除回收站条目，ids 为空时清除所有 Zyma 条目，返回清除数量
+#[tauri::command]
+pub async fn trash_empty(ids: Option<Vec<String>>, caller: IpcCaller) -> Result<usize, String> {
+    caller.require_user()?;
+    trash::empty(ids).await
+}
\ No newline at end of file
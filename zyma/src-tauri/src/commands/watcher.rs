@@ -1,8 +1,11 @@
 ﻿use std::sync::Mutex;
 use std::collections::HashMap;
 use notify::{Watcher, RecursiveMode, Config};
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 use serde::Serialize;
+use crate::commands::caller::IpcCaller;
+use crate::commands::fs::WorkspaceService;
+use crate::services::FileSystem;
 
 #[derive(Clone, Serialize)]
 pub enum FsEventKind {
@@ -26,9 +29,35 @@ pub struct WatcherState {
 pub fn fs_watch(
     app_handle: tauri::AppHandle,
     state: tauri::State<'_, WatcherState>,
-    path: String
+    ws: tauri::State<'_, WorkspaceService>,
+    path: String,
+    caller: IpcCaller
+) -> Result<(), String> {
+    // 变更事件会带出目录下的文件名，按调用方做读取检查
+    ws.check_read(caller.0, &path)?;
+    let mut watchers = state.watchers.lock().unwrap();
+    watch_path(&app_handle, &mut watchers, path)
+}
+
+/// 监听工作区的全部根目录 (多根工作区)
+#[tauri::command]
+pub fn fs_watch_workspace(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, WatcherState>,
+    ws: tauri::State<'_, WorkspaceService>
 ) -> Result<(), String> {
     let mut watchers = state.watchers.lock().unwrap();
+    for folder in ws.fs.roots() {
+        watch_path(&app_handle, &mut watchers, folder.path)?;
+    }
+    Ok(())
+}
+
+fn watch_path(
+    app_handle: &tauri::AppHandle,
+    watchers: &mut HashMap<String, notify::RecommendedWatcher>,
+    path: String
+) -> Result<(), String> {
     if watchers.contains_key(&path) { return Ok(()); }
 
     let app_handle_clone = app_handle.clone();
@@ -56,6 +85,10 @@ pub fn fs_watch(
                 paths: paths.clone() 
             });
             
+            if let Some(ws) = app_handle_clone.try_state::<WorkspaceService>() {
+                crate::commands::config::workspace_files_changed(&ws, &paths);
+            }
+
             for p in paths { 
                 let _ = app_handle_clone.emit(kind_str, p); 
             }
@@ -14,9 +14,6 @@ pub struct WindowState {
 pub fn save_window_state(window: tauri::WebviewWindow) -> Result<(), String> {
     let label = window.label().to_string();
     
-    // 使用统一的加载逻辑
-    let mut settings = crate::commands::config::load_settings()?;
-
     let is_maximized = window.is_maximized().unwrap_or(false);
     let factor = window.scale_factor().unwrap_or(1.0);
     let size = window.outer_size().unwrap_or_default().to_logical::<f64>(factor);
@@ -24,23 +21,23 @@ pub fn save_window_state(window: tauri::WebviewWindow) -> Result<(), String> {
 
     let state = WindowState { width: size.width, height: size.height, x: pos.x, y: pos.y, is_maximized };
 
-    if label == "main" {
-        settings.window_width = state.width;
-        settings.window_height = state.height;
-        settings.window_x = Some(state.x);
-        settings.window_y = Some(state.y);
-        settings.is_maximized = state.is_maximized;
-    } else {
-        let mut windows_map = match settings.windows {
-            Some(v) => v.as_object().cloned().unwrap_or_default(),
-            None => serde_json::Map::new(),
-        };
-        windows_map.insert(label, serde_json::to_value(state).unwrap());
-        settings.windows = Some(serde_json::Value::Object(windows_map));
-    }
-
-    // 使用统一的保存逻辑
-    crate::commands::config::save_settings(settings)
+    // 在设置存储的锁内读改写，不会覆盖同时发生的其他写入
+    crate::commands::config::update_user_settings(|settings| {
+        if label == "main" {
+            settings.window_width = state.width;
+            settings.window_height = state.height;
+            settings.window_x = Some(state.x);
+            settings.window_y = Some(state.y);
+            settings.is_maximized = state.is_maximized;
+        } else {
+            let mut windows_map = match settings.windows.take() {
+                Some(v) => v.as_object().cloned().unwrap_or_default(),
+                None => serde_json::Map::new(),
+            };
+            windows_map.insert(label, serde_json::to_value(state).unwrap());
+            settings.windows = Some(serde_json::Value::Object(windows_map));
+        }
+    })
 }
 
 #[tauri::command]
@@ -49,7 +46,7 @@ pub async fn open_detached_output(app_handle: tauri::AppHandle, channel: String)
     if let Some(win) = app_handle.get_webview_window(&label) { let _ = win.set_focus(); return Ok(()); }
     
     // 使用统一的加载逻辑
-    let settings = crate::commands::config::load_settings()?;
+    let settings = crate::commands::config::load_user_settings()?;
     let mut saved_state: Option<WindowState> = None;
     
     if let Some(windows) = settings.windows.and_then(|v| v.as_object().cloned()) {
@@ -0,0 +1,170 @@
+use std::path::Path;
+use tauri::{State, Emitter};
+use crate::models::{SessionInfo, WorkspaceFolder};
+use crate::bus::{EventBus, ZymaEvent};
+use crate::commands::caller::IpcCaller;
+use crate::commands::fs::WorkspaceService;
+use crate::services::FileSystem;
+use crate::services::sandbox::{Access, Caller};
+use crate::services::vfs::FsError;
+use crate::services::workspace;
+
+/// 打开 .zyma-workspace 多根工作区，返回全部根目录 (修改工作区根目录只对用户开放)
+#[tauri::command]
+pub async fn workspace_open(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    caller: IpcCaller
+) -> Result<Vec<WorkspaceFolder>, FsError> {
+    open_workspace_file(&app_handle, &ws, &bus, &path, caller.0)
+}
+
+pub(crate) fn open_workspace_file(app_handle: &tauri::AppHandle, ws: &WorkspaceService, bus: &EventBus, path: &str, caller: Caller) -> Result<Vec<WorkspaceFolder>, FsError> {
+    let fs = ws.fs_for(caller);
+    fs.check_access(path, Access::Read, fs.sandbox_mode())?;
+    let folders = workspace::load(Path::new(path))?;
+    fs.set_roots(folders)?;
+    let roots = ws.fs.roots();
+    let file = path.replace("\\", "/");
+    record_workspace(&file, Some(&file), &roots);
+
+    let primary = ws.fs.get_cwd().replace("\\", "/");
+    let _ = app_handle.emit("workspace_changed", &primary);
+    crate::commands::session::switch_session(app_handle, &primary);
+    bus.publish(ZymaEvent::WorkspaceChanged(primary));
+    Ok(roots)
+}
+
+#[tauri::command]
+pub async fn workspace_get_folders(ws: State<'_, WorkspaceService>) -> Result<Vec<WorkspaceFolder>, String> {
+    Ok(ws.fs.roots())
+}
+
+/// 向当前工作区追加一个根目录 (已保存的工作区文件会同步更新)
+#[tauri::command]
+pub async fn workspace_add_folder(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    name: Option<String>,
+    caller: IpcCaller
+) -> Result<Vec<WorkspaceFolder>, FsError> {
+    if !Path::new(&path).is_dir() {
+        return Err(format!("Not a directory: {}", path).into());
+    }
+    let mut roots = ws.fs.roots();
+    let folder = workspace::new_folder(&path, name);
+    if roots.iter().any(|f| f.path == folder.path) {
+        return Ok(roots);
+    }
+    roots.push(folder);
+    ws.fs_for(caller.0).set_roots(roots)?;
+    folders_changed(&app_handle, &ws, &bus)
+}
+
+/// 从工作区移除根目录 (至少保留一个)
+#[tauri::command]
+pub async fn workspace_remove_folder(
+    app_handle: tauri::AppHandle,
+    ws: State<'_, WorkspaceService>,
+    bus: State<'_, EventBus>,
+    path: String,
+    caller: IpcCaller
+) -> Result<Vec<WorkspaceFolder>, FsError> {
+    let target = path.replace("\\", "/");
+    let old_primary = ws.fs.get_cwd();
+    let roots: Vec<WorkspaceFolder> = ws.fs.roots().into_iter().filter(|f| f.path != target).collect();
+    if roots.is_empty() {
+        return Err("Cannot remove the last workspace folder".to_string().into());
+    }
+    ws.fs_for(caller.0).set_roots(roots)?;
+
+    let primary = ws.fs.get_cwd();
+    if primary != old_primary {
+        let primary = primary.replace("\\", "/");
+        let _ = app_handle.emit("workspace_changed", &primary);
+        crate::commands::session::switch_session(&app_handle, &primary);
+        bus.publish(ZymaEvent::WorkspaceChanged(primary));
+    }
+    folders_changed(&app_handle, &ws, &bus)
+}
+
+/// 将当前工作区保存为 .zyma-workspace 文件 (保存位置受调用方的沙箱限制)
+#[tauri::command]
+pub async fn workspace_save(ws: State<'_, WorkspaceService>, path: String, caller: IpcCaller) -> Result<(), FsError> {
+    let fs = ws.fs_for(caller.0);
+    fs.check_access(&path, Access::Write, fs.sandbox_mode())?;
+    let roots = ws.fs.roots();
+    workspace::save(Path::new(&path), &roots)?;
+    let file = path.replace("\\", "/");
+    record_workspace(&file, Some(&file), &roots);
+    Ok(())
+}
+
+fn folders_changed(app_handle: &tauri::AppHandle, ws: &WorkspaceService, bus: &EventBus) -> Result<Vec<WorkspaceFolder>, FsError> {
+    let roots = ws.fs.roots();
+    let file = current_workspace_file();
+    if let Some(file) = &file {
+        workspace::save(Path::new(file), &roots)?;
+    }
+    record_session(file.as_deref(), &roots);
+    let _ = app_handle.emit("workspace_folders_changed", &roots);
+    bus.publish(ZymaEvent::WorkspaceFoldersChanged(roots.clone()));
+    Ok(roots)
+}
+
+/// 从最近工作区列表中移除一项，返回移除后的列表
+#[tauri::command]
+pub fn workspace_forget_recent(path: String, caller: IpcCaller) -> Result<Vec<String>, String> {
+    caller.require_user()?;
+    let lower_path = path.replace("\\", "/").to_lowercase();
+    let mut recent = Vec::new();
+    crate::commands::config::update_user_settings(|settings| {
+        settings.recent_workspaces.retain(|p| p.replace("\\", "/").to_lowercase() != lower_path);
+        recent = settings.recent_workspaces.clone();
+    })?;
+    Ok(recent)
+}
+
+pub(crate) fn current_workspace_file() -> Option<String> {
+    crate::commands::config::load_user_settings().ok()
+        .and_then(|s| s.session)
+        .and_then(|s| s.workspace_file)
+}
+
+/// 记录到最近工作区，并把工作区信息写入会话以便下次启动恢复
+pub(crate) fn record_workspace(recent_path: &str, workspace_file: Option<&str>, roots: &[WorkspaceFolder]) {
+    let lower_path = recent_path.to_lowercase();
+    let result = crate::commands::config::update_user_settings(|settings| {
+        // 去重并插入首位 (大小写不敏感，防止 Windows 下盘符大小写导致的重复)
+        settings.recent_workspaces.retain(|p| {
+            p.replace("\\", "/").to_lowercase() != lower_path
+        });
+        settings.recent_workspaces.insert(0, recent_path.to_string());
+        // 只保留最近 10 个
+        if settings.recent_workspaces.len() > 10 {
+            settings.recent_workspaces.truncate(10);
+        }
+        apply_session(&mut settings.session, workspace_file, roots);
+    });
+    if let Err(e) = result {
+        log::error!("Failed to record workspace: {}", e);
+    }
+}
+
+fn record_session(workspace_file: Option<&str>, roots: &[WorkspaceFolder]) {
+    if let Err(e) = crate::commands::config::update_user_settings(|settings| apply_session(&mut settings.session, workspace_file, roots)) {
+        log::error!("Failed to record workspace folders: {}", e);
+    }
+}
+
+fn apply_session(session: &mut Option<SessionInfo>, workspace_file: Option<&str>, roots: &[WorkspaceFolder]) {
+    let session = session.get_or_insert_with(SessionInfo::default);
+    session.root_path = roots.first().map(|f| f.path.clone());
+    session.workspace_file = workspace_file.map(|f| f.to_string());
+    // 单根工作区不需要额外记录
+    session.folders = if roots.len() > 1 { roots.to_vec() } else { Vec::new() };
+}
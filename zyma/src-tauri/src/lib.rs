@@ -12,6 +12,7 @@ pub mod bus;
 pub mod services;
 
 use crate::commands::config::get_config_path;
+use crate::services::FileSystem;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NativeChatParticipant {
@@ -30,6 +31,9 @@ pub struct NativeAuthProvider {
     pub login_command: String,
     pub logout_command: String,
     pub auth_event: Option<String>,
+    /// 返回连接凭据的命令 (如 SSH 的密码或私钥口令)，连接配置的 auth_provider 指向该提供者时由前端调用
+    #[serde(default)]
+    pub credentials_command: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -65,9 +69,16 @@ pub struct ZymaBuilder {
     sidebar_items: Vec<NativeSidebarItem>,
     file_menu_items: Vec<NativeFileMenuItem>,
     slot_components: Vec<NativeSlotComponent>,
+    fs_providers: Vec<(String, services::mount::SharedFs)>,
     setup_hook: Option<Box<dyn FnOnce(&mut tauri::App<Wry>) -> Result<(), Box<dyn std::error::Error>> + Send + 'static>>,
 }
 
+impl Default for ZymaBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ZymaBuilder {
     pub fn new() -> Self {
         Self {
@@ -77,6 +88,7 @@ impl ZymaBuilder {
             sidebar_items: Vec::new(),
             file_menu_items: Vec::new(),
             slot_components: Vec::new(),
+            fs_providers: Vec::new(),
             setup_hook: None,
         }
     }
@@ -89,6 +101,7 @@ impl ZymaBuilder {
             sidebar_items: Vec::new(),
             file_menu_items: Vec::new(),
             slot_components: Vec::new(),
+            fs_providers: Vec::new(),
             setup_hook: None,
         }
     }
@@ -118,6 +131,13 @@ impl ZymaBuilder {
         self
     }
 
+    /// 为自定义 URI scheme (如 "gen"、"s3") 注册文件系统实现，
+    /// 之后 `gen:/a/b` 形式的路径会经由现有的 fs 命令分发给该实现
+    pub fn register_fs_provider(mut self, scheme: &str, fs: impl FileSystem + Send + Sync + 'static) -> Self {
+        self.fs_providers.push((scheme.to_string(), std::sync::Arc::new(fs)));
+        self
+    }
+
     pub fn setup<F>(mut self, callback: F) -> Self
     where
         F: FnOnce(&mut tauri::App<Wry>) -> Result<(), Box<dyn std::error::Error>> + Send + 'static,
@@ -132,27 +152,73 @@ impl ZymaBuilder {
         let items = self.sidebar_items;
         let file_menus = self.file_menu_items;
         let slots = self.slot_components;
+        let fs_providers = self.fs_providers;
         let custom_setup = self.setup_hook;
+        let dirs = services::paths::dirs();
 
-        self.builder
+        let mut builder = self.builder
             .plugin(tauri_plugin_dialog::init())
             .plugin(tauri_plugin_shell::init())
             .plugin(tauri_plugin_updater::Builder::new().build())
-            .plugin(tauri_plugin_log::Builder::new().build())
-            .plugin(tauri_plugin_single_instance::init(|_app, _args, _cwd| {}))
+            .plugin(tauri_plugin_log::Builder::new().build());
+        // 以 --user-data-dir 启动的实例相互独立，不转发给已运行的实例
+        if !dirs.is_isolated() {
+            builder = builder.plugin(tauri_plugin_single_instance::init(|_app, _args, _cwd| {}));
+        }
+        builder
             .plugin(tauri_plugin_cli::init())
+            // 页面 (重新) 加载时作废该窗口的用户令牌，由新页面重新领取
+            .on_page_load(|webview, payload| {
+                if matches!(payload.event(), tauri::webview::PageLoadEvent::Started) {
+                    if let Some(callers) = webview.try_state::<services::sandbox::CallerRegistry>() {
+                        callers.reset(webview.label());
+                    }
+                }
+            })
             .setup(move |app| {
+                // 旧版的 ~/.zyma_config.json 与 ~/.zyma/ 一次性移到配置与数据目录 (便携模式与 --user-data-dir 不迁移)
+                if dirs.mode == services::paths::DirsMode::Standard {
+                    if let Some(home) = services::paths::legacy_home() {
+                        for path in services::paths::migrate_legacy(&home, dirs) {
+                            log::info!("Migrated legacy data to {}", path.display());
+                        }
+                    }
+                }
+                // 先应用记录的配置档案，之后读取的设置均为档案生效后的值
+                commands::profiles::apply_active_profile();
                 // 1. 初始化并注册 WorkspaceService (增加恢复逻辑)
-                let initial_path = if let Ok(settings) = commands::config::load_settings() {
-                    settings.session.and_then(|s| s.root_path).and_then(|p| {
-                        let path = PathBuf::from(p);
-                        if path.exists() && path.is_dir() { Some(path) } else { None }
-                    }).unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
-                } else {
-                    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
-                };
-
-                app.manage(commands::fs::WorkspaceService::new(initial_path));
+                let settings = commands::config::load_user_settings().unwrap_or_default();
+                // 热退出：把上次未保存的内容放回会话，由前端以脏状态重新打开
+                let backups = std::sync::Arc::new(services::backup::BackupService::new(services::backup::BackupService::default_root()));
+                let sessions = services::session::SessionStore::new(services::session::SessionStore::default_dir());
+                sessions.set_restored_buffers(backups.load_all());
+                app.manage(backups);
+                app.manage(sessions);
+                let initial_path = settings.session.as_ref().and_then(|s| s.root_path.clone()).and_then(|p| {
+                    let path = PathBuf::from(p);
+                    if path.exists() && path.is_dir() { Some(path) } else { None }
+                }).unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+
+                let local_fs = services::LocalFileSystem::new(initial_path);
+                local_fs.apply_settings(&settings);
+                // 恢复多根工作区
+                if let Some(roots) = settings.session.as_ref().and_then(services::workspace::restore_from_session) {
+                    let _ = local_fs.set_roots(roots);
+                }
+                let ws = commands::fs::WorkspaceService::with_fs(Box::new(local_fs));
+                ws.history.set_root(services::history::HistoryService::default_root());
+                ws.history.set_policy(services::history::HistoryPolicy::from_settings(&settings));
+                for (scheme, provider) in fs_providers {
+                    if let Err(e) = ws.mount(&scheme, provider) {
+                        log::error!("Failed to register file system provider: {}", e);
+                    }
+                }
+                // 远程 SFTP 连接统一挂载在 sftp: 下
+                let sftp = std::sync::Arc::new(services::sftp::SftpFileSystem::new());
+                ws.mount(services::sftp::SFTP_SCHEME, sftp.clone()).map_err(String::from)?;
+                app.manage(sftp);
+                app.manage(ws);
+                app.manage(services::largefile::LineIndexService::new());
                 // 2. 初始化并注册 WatcherState
                 app.manage(commands::watcher::WatcherState { 
                     watchers: Mutex::new(HashMap::new()) 
@@ -167,6 +233,11 @@ impl ZymaBuilder {
                 // 5. 初始化并注册 ContextService
                 app.manage(services::ContextService::new());
 
+                // 调用方认定：原生 Chat Participant 的命令以 Agent 身份访问文件
+                let callers = services::sandbox::CallerRegistry::default();
+                callers.set_agent_commands(participants.iter().map(|p| p.command.clone()));
+                app.manage(callers);
+
                 // 6. 初始化并注册 PluginService (包含侧边栏项、命令、插槽组件)
                 app.manage(commands::plugins::PluginService {
                     external_plugins: Vec::new(),
@@ -182,6 +253,27 @@ impl ZymaBuilder {
                 let bus = bus::EventBus::new();
                 app.manage(bus.clone());
 
+                // 密钥存储：优先系统钥匙串，并把旧版配置中的明文密钥迁移进去
+                let secrets = std::sync::Arc::new(services::secrets::SecretService::detect(services::secrets::SecretService::default_vault_path()));
+                commands::secrets::migrate_plaintext_keys(&secrets);
+                app.manage(secrets);
+
+                // 设置变化推送给所有窗口与事件总线
+                let settings_handle = app.handle().clone();
+                let settings_bus = bus.clone();
+                commands::config::store().set_listener(Box::new(move |change| {
+                    // 文件系统相关的设置 (沙箱模式、备份策略等) 立即生效，无需重启
+                    if let Some(ws) = settings_handle.try_state::<commands::fs::WorkspaceService>() {
+                        ws.fs.apply_settings(&commands::config::store().settings());
+                    }
+                    let _ = settings_handle.emit("settings-changed", change);
+                    settings_bus.publish(bus::ZymaEvent::SettingsChanged(change.keys.clone()));
+                }));
+                match commands::config::watch_user_settings() {
+                    Ok(watcher) => { app.manage(watcher); }
+                    Err(e) => log::warn!("Failed to watch settings file: {}", e),
+                }
+
                 setup_zyma(app, bus)?;
 
                 // 7. 最后执行业务层注入的自定义 setup 钩子
@@ -196,6 +288,9 @@ impl ZymaBuilder {
     }
 }
 
+/// 关闭窗口时等待前端备份未保存内容的最长时间
+const HOT_EXIT_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
 pub fn setup_zyma(app: &mut tauri::App<Wry>, bus: bus::EventBus) -> Result<(), Box<dyn std::error::Error>> {
     let handle = app.handle();
     restore_window_state(handle)?;
@@ -204,6 +299,8 @@ pub fn setup_zyma(app: &mut tauri::App<Wry>, bus: bus::EventBus) -> Result<(), B
     // 捕获 bus 的副本用于闭包
     let bus_clone = bus.clone();
 
+    let closing = std::sync::atomic::AtomicBool::new(false);
+
     if let Some(main_window) = app.get_webview_window("main") {
         main_window.on_window_event(move |event| {
             match event {
@@ -211,7 +308,20 @@ pub fn setup_zyma(app: &mut tauri::App<Wry>, bus: bus::EventBus) -> Result<(), B
                     let _ = h.emit("window-state-changed", *focused); 
                     bus_clone.publish(bus::ZymaEvent::WindowFocused(*focused));
                 }
-                tauri::WindowEvent::CloseRequested { api, .. } => { api.prevent_close(); h.exit(0); }
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    api.prevent_close();
+                    // 热退出：先让前端把防抖中的脏缓冲区写入备份，由前端备份完成后调用 exit_app
+                    // 前端无响应或再次点击关闭时直接退出
+                    if closing.swap(true, std::sync::atomic::Ordering::SeqCst) || h.emit("hot_exit_flush", ()).is_err() {
+                        h.exit(0);
+                        return;
+                    }
+                    let h_exit = h.clone();
+                    tauri::async_runtime::spawn(async move {
+                        tokio::time::sleep(HOT_EXIT_FLUSH_TIMEOUT).await;
+                        h_exit.exit(0);
+                    });
+                }
                 _ => {}
             }
         });
@@ -222,6 +332,13 @@ pub fn setup_zyma(app: &mut tauri::App<Wry>, bus: bus::EventBus) -> Result<(), B
     let mut bus_rx = bus.subscribe();
     tauri::async_runtime::spawn(async move {
         while let Ok(event) = bus_rx.recv().await {
+            if let bus::ZymaEvent::SettingsChanged(keys) = &event {
+                // 本地历史策略可即时生效
+                if keys.iter().any(|k| k.starts_with("local_history")) {
+                    let settings = commands::config::load_user_settings().unwrap_or_default();
+                    h_bus.state::<commands::fs::WorkspaceService>().history.set_policy(services::history::HistoryPolicy::from_settings(&settings));
+                }
+            }
             if let bus::ZymaEvent::WorkspaceChanged(new_path) = event {
                 // 1. 清理所有旧监听
                 let watcher_state = h_bus.state::<commands::watcher::WatcherState>();
@@ -246,16 +363,11 @@ pub fn run() {
 
 pub fn restore_window_state(app: &AppHandle<Wry>) -> tauri::Result<()> {
     if let Some(main_window) = app.get_webview_window("main") {
-        let config_path = get_config_path();
-        if config_path.exists() {
-            if let Ok(content) = fs::read_to_string(config_path) {
-                if let Ok(val) = serde_json::from_str::<serde_json::Value>(&content) {
-                    let w = val.get("window_width").and_then(|v| v.as_f64()).unwrap_or(1000.0) as u32;
-                    let h = val.get("window_height").and_then(|v| v.as_f64()).unwrap_or(700.0) as u32;
-                    let _ = main_window.set_size(tauri::PhysicalSize::new(w, h));
-                    if val.get("is_maximized").and_then(|v| v.as_bool()).unwrap_or(false) { let _ = main_window.maximize(); }
-                }
-            }
+        // 经由设置存储读取，配置文件中的注释、尾逗号与个别无效的键不影响恢复
+        if get_config_path().exists() {
+            let settings = commands::config::store().settings();
+            let _ = main_window.set_size(tauri::PhysicalSize::new(settings.window_width as u32, settings.window_height as u32));
+            if settings.is_maximized { let _ = main_window.maximize(); }
         }
         let _ = main_window.show();
         let _ = main_window.set_focus();
@@ -1,10 +1,45 @@
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileItem {
     pub name: String,
     pub path: String,
+    /// 指向目录的符号链接同样为 true (可展开)，失效的链接为 false
     pub is_dir: bool,
+    #[serde(flatten)]
+    pub attrs: FileAttributes,
+}
+
+/// 文件的链接与权限属性 (read_dir 与 fs_stat 共用)，实现不支持的项保持默认值
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FileAttributes {
+    #[serde(default)]
+    pub is_symlink: bool,
+    /// 链接目标 (readlink 的原始结果，可能是相对路径)
+    #[serde(default)]
+    pub target: Option<String>,
+    /// 链接目标不存在 (或链接成环)
+    #[serde(default)]
+    pub broken: bool,
+    /// Unix 权限位 (如 0o644)，其它平台为空
+    #[serde(default)]
+    pub mode: Option<u32>,
+    #[serde(default)]
+    pub readonly: bool,
+    /// 以 "." 开头，或带有 Windows 隐藏属性
+    #[serde(default)]
+    pub hidden: bool,
+    /// 创建时间 (秒)，文件系统不记录时为空
+    #[serde(default)]
+    pub created: Option<u64>,
+}
+
+impl FileAttributes {
+    /// 仅能从名称推断的属性 (用于内存、压缩包等没有权限概念的实现)
+    pub fn from_name(name: &str) -> Self {
+        Self { hidden: name.starts_with('.'), ..Default::default() }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -14,46 +49,266 @@ pub struct SearchResult {
     pub content: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
 pub struct SessionInfo {
     pub root_path: Option<String>,
     pub open_files: Vec<String>,
     pub active_file: Option<String>,
+    // 多根工作区：已保存的 .zyma-workspace 文件，或尚未保存时的文件夹列表
+    #[serde(default)]
+    pub workspace_file: Option<String>,
+    #[serde(default)]
+    pub folders: Vec<WorkspaceFolder>,
+    // 各标签页的光标、选区、滚动位置与固定状态
+    #[serde(default)]
+    pub editors: Vec<EditorViewState>,
+    // 分栏编辑器布局，为空表示单栏
+    #[serde(default)]
+    pub layout: Option<EditorLayout>,
+    // 侧边栏与底部面板
+    #[serde(default)]
+    pub panels: Option<PanelLayout>,
+    // 热退出：启动时从备份目录恢复的未保存内容 (仅随 session_load 返回，不落盘)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dirty_buffers: Vec<DirtyBuffer>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// 单个标签页的视图状态
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct EditorViewState {
+    pub path: String,
+    /// 光标与选区 (文档中的字符偏移)，第一个为主选区
+    #[serde(default)]
+    pub selections: Vec<SelectionRange>,
+    #[serde(default)]
+    pub scroll_top: f64,
+    #[serde(default)]
+    pub scroll_left: f64,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+/// anchor == head 时即为光标
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+pub struct SelectionRange {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+/// 分栏编辑器布局：按 orientation 排列的若干编辑器组
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct EditorLayout {
+    /// "horizontal" (左右) / "vertical" (上下)
+    #[serde(default)]
+    pub orientation: String,
+    #[serde(default)]
+    pub groups: Vec<EditorGroupState>,
+    /// 各组所占比例，与 groups 一一对应
+    #[serde(default)]
+    pub sizes: Vec<f64>,
+    #[serde(default)]
+    pub active_group: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct EditorGroupState {
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub active_file: Option<String>,
+}
+
+/// 侧边栏与底部面板的显示状态
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PanelLayout {
+    /// 侧边栏当前视图 (explorer / search / 插件视图 id)
+    #[serde(default)]
+    pub sidebar_view: Option<String>,
+    #[serde(default = "default_true")]
+    pub sidebar_visible: bool,
+    #[serde(default)]
+    pub sidebar_width: Option<f64>,
+    #[serde(default)]
+    pub bottom_panel_visible: bool,
+    #[serde(default)]
+    pub bottom_panel_height: Option<f64>,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self { sidebar_view: None, sidebar_visible: true, sidebar_width: None, bottom_panel_visible: false, bottom_panel_height: None }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 未保存的编辑内容 (热退出备份)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct DirtyBuffer {
+    /// 文件路径；未命名文件为前端生成的临时 id
+    pub id: String,
+    #[serde(default)]
+    pub path: Option<String>,
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub encoding: Option<String>,
+    #[serde(default)]
+    pub has_bom: bool,
+    #[serde(default)]
+    pub line_ending: Option<String>,
+    /// 开始编辑时磁盘文件的版本标记，恢复后保存仍能检测外部修改
+    #[serde(default)]
+    pub version: Option<String>,
+    /// 备份时间 (毫秒)
+    #[serde(default)]
+    pub timestamp: u64,
+}
+
+/// 工作区中的一个根目录
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct WorkspaceFolder {
+    pub name: String,
+    pub path: String,
+}
+
+/// SSH 认证方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SshAuthMethod {
+    /// 使用 ssh-agent 中的密钥
+    #[default]
+    Agent,
+    Password,
+    /// 私钥文件 (key_path)，可带口令
+    Key,
+}
+
+/// 远程连接配置，连接后以 `sftp://<id>/path` 访问
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct SshProfile {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    pub username: String,
+    #[serde(default)]
+    pub auth: SshAuthMethod,
+    #[serde(default)]
+    pub key_path: Option<String>,
+    /// 远程的初始目录，为空时使用登录用户的主目录
+    #[serde(default)]
+    pub remote_root: Option<String>,
+    /// 提供凭据的认证提供者 id (AuthRegistry 中的提供者，原生提供者通过 ZymaBuilder::register_auth_provider 注册)
+    #[serde(default)]
+    pub auth_provider: Option<String>,
+}
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+/// 用户设置 (settings.json)；各层设置文件只需写出要覆盖的键
+/// 字段的文档注释即为 JSON Schema 中的说明 (见 config_file::schema)
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+#[schemars(title = "Zyma settings")]
 pub struct AppSettings {
+    /// 配置格式版本，旧版配置没有该字段 (视为 0)，读取时按 config_file 中的迁移升级
+    #[serde(default)]
+    pub version: u32,
+    /// 配色主题
+    #[schemars(extend("enum" = ["dark", "light", "abyss"]))]
     pub theme: String,
+    /// 编辑器字号
     pub font_size: u32,
+    /// 界面字号
     pub ui_font_size: u32,
+    /// 每级缩进的空格数
     pub tab_size: u32,
+    /// 界面语言，如 zh-CN
     pub language: String,
+    /// 在系统右键菜单中显示 "用 Zyma 编辑"
     pub context_menu: bool,
+    /// 在已运行的实例中打开文件
     pub single_instance: bool,
+    /// 自动检查更新
     pub auto_update: bool,
+    /// 主窗口宽度
     pub window_width: f64,
+    /// 主窗口高度
     pub window_height: f64,
+    /// 主窗口横坐标
     pub window_x: Option<i32>,
+    /// 主窗口纵坐标
     pub window_y: Option<i32>,
+    /// 主窗口是否最大化
     pub is_maximized: bool,
+    /// 各窗口的状态，按窗口 label 索引
     pub windows: Option<serde_json::Value>,
-    
-    // 会话信息
+
+    /// 会话信息 (上次打开的工作区)
     pub session: Option<SessionInfo>,
 
-    // 最近打开的工作区
+    /// 最近打开的工作区
     #[serde(default)]
     pub recent_workspaces: Vec<String>,
 
-    // AI Settings
+    /// AI 服务商
     #[serde(default)]
     pub ai_provider: Option<String>,
+    /// 已废弃：旧版的明文密钥，启动或解锁密钥存储后迁移到 SecretService，不再写入
     #[serde(default)]
     pub ai_api_key: Option<String>,
+    /// 密钥在 SecretService 中的引用 (id)
+    #[serde(default)]
+    pub ai_api_key_ref: Option<String>,
+    /// AI 接口地址
     #[serde(default)]
     pub ai_base_url: Option<String>,
+    /// AI 模型
     #[serde(default)]
     pub ai_model: Option<String>,
+
+    /// 保存时的备份策略: "bak" (单个 .bak) / "numbered" (编号备份)，为空则不备份
+    #[serde(default)]
+    #[schemars(extend("enum" = ["bak", "numbered", null]))]
+    pub save_backup: Option<String>,
+    /// 保留的编号备份数量
+    #[serde(default)]
+    pub save_backup_limit: Option<u32>,
+
+    /// 本地历史 (每次保存留一份快照)，默认开启；保留份数与天数为空时使用默认值 (50 份 / 30 天)
+    #[serde(default)]
+    pub local_history: Option<bool>,
+    /// 每个文件保留的快照数
+    #[serde(default)]
+    pub local_history_max_entries: Option<u32>,
+    /// 快照保留的天数
+    #[serde(default)]
+    pub local_history_max_days: Option<u32>,
+
+    /// 文件访问沙箱: "workspace" / "read_only_outside"，为空则不限制 (插件与 Agent 始终受限)
+    #[serde(default)]
+    #[schemars(extend("enum" = ["workspace", "read_only_outside", null]))]
+    pub fs_sandbox: Option<String>,
+
+    /// SSH/SFTP 远程连接配置 (不保存密码)
+    #[serde(default)]
+    pub ssh_profiles: Vec<SshProfile>,
+
+    /// 已禁用的插件 (按插件名)
+    #[serde(default)]
+    pub disabled_plugins: Vec<String>,
+
+    /// 配置档案: 名称 -> 设置子集 (见 services::profiles::PROFILE_KEYS)
+    #[serde(default)]
+    pub profiles: std::collections::BTreeMap<String, serde_json::Value>,
+    /// 当前使用的配置档案，启动时应用
+    #[serde(default)]
+    pub active_profile: Option<String>,
     
     // 扩展字段
     #[serde(flatten)]
@@ -63,6 +318,7 @@ pub struct AppSettings {
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            version: crate::services::config_file::CURRENT_VERSION,
             theme: "dark".to_string(),
             font_size: 14,
             ui_font_size: 13,
@@ -81,8 +337,19 @@ impl Default for AppSettings {
             recent_workspaces: Vec::new(),
             ai_provider: None,
             ai_api_key: None,
+            ai_api_key_ref: None,
             ai_base_url: None,
             ai_model: None,
+            save_backup: None,
+            save_backup_limit: None,
+            local_history: None,
+            local_history_max_entries: None,
+            local_history_max_days: None,
+            fs_sandbox: None,
+            ssh_profiles: Vec::new(),
+            disabled_plugins: Vec::new(),
+            profiles: std::collections::BTreeMap::new(),
+            active_profile: None,
             extra: std::collections::HashMap::new(),
         }
     }
@@ -92,6 +359,25 @@ impl Default for AppSettings {
 pub struct FileReadResponse {
     pub content: String,
     pub encoding: String,
+    #[serde(default)]
+    pub has_bom: bool,
+    // "LF" / "CRLF" / "Mixed"
+    #[serde(default)]
+    pub line_ending: String,
+    // 读取时的版本标记，保存时回传以检测外部修改
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// 写入文件时的编码选项 (缺省为 UTF-8、无 BOM、保持原有换行)
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct WriteOptions {
+    #[serde(default)]
+    pub encoding: Option<String>,
+    #[serde(default)]
+    pub has_bom: bool,
+    #[serde(default)]
+    pub line_ending: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
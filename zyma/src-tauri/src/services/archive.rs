@@ -0,0 +1,534 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Cursor, Read};
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use crate::models::{FileAttributes, FileItem, WorkspaceFolder};
+use crate::services::largefile;
+use crate::services::sandbox::{Access, SandboxMode};
+use crate::services::vfs::{FileStat, FileSystem, FsError};
+
+/// 压缩包内路径的前缀：`zip:/path/to/a.zip!/inner/file` (tar / tar.gz 同样使用该前缀)
+pub const ARCHIVE_SCHEME: &str = "zip:";
+
+/// 最多缓存的已解析压缩包数量
+const MAX_CACHED: usize = 4;
+
+/// 用户强制打开时，压缩包 (tar.gz 为解压后) 与单个条目解压后大小的硬上限
+const MAX_FORCED_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// 载入内存或解压的字节上限：默认为大文件阈值，强制打开后放宽到硬上限
+fn size_limit(forced: bool) -> u64 {
+    if forced { MAX_FORCED_BYTES } else { largefile::LARGE_FILE_THRESHOLD }
+}
+
+/// 最多读取 limit 个字节，超出时返回 None (防止压缩炸弹耗尽内存)
+fn read_capped(reader: impl Read, limit: u64) -> std::io::Result<Option<Vec<u8>>> {
+    let mut buf = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut buf)?;
+    Ok(if buf.len() as u64 > limit { None } else { Some(buf) })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    fn from_path(path: &str) -> Option<Self> {
+        let lower = path.to_lowercase();
+        if [".zip", ".jar", ".war", ".ear", ".apk", ".vsix", ".nupkg", ".whl"].iter().any(|e| lower.ends_with(e)) {
+            Some(ArchiveKind::Zip)
+        } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else if lower.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// 是否为可浏览的压缩包文件 (按扩展名判断)
+pub fn is_archive_file(path: &str) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+pub fn is_archive_path(path: &str) -> bool {
+    path.starts_with(ARCHIVE_SCHEME)
+}
+
+/// 拆分为 (压缩包路径, 包内路径)，包内路径不带首尾的 '/'，根目录为空串
+pub fn split(path: &str) -> Option<(&str, String)> {
+    let rest = path.strip_prefix(ARCHIVE_SCHEME)?;
+    let (archive, inner) = match rest.find("!/") {
+        Some(i) => (&rest[..i], &rest[i + 2..]),
+        None => (rest.strip_suffix('!').unwrap_or(rest), ""),
+    };
+    if archive.is_empty() {
+        return None;
+    }
+    Some((archive, normalize_entry(inner)))
+}
+
+/// 组合出压缩包内条目的完整路径
+pub fn join(archive: &str, inner: &str) -> String {
+    format!("{}{}!/{}", ARCHIVE_SCHEME, archive, inner)
+}
+
+fn normalize_entry(name: &str) -> String {
+    name.replace("\\", "/")
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn parent_of(inner: &str) -> &str {
+    inner.rfind('/').map(|i| &inner[..i]).unwrap_or("")
+}
+
+struct ArchiveEntry {
+    is_dir: bool,
+    size: u64,
+    /// zip 为条目序号，tar 为文件数据在包内的偏移 (稀疏文件等无法直接定位时为 None)
+    pos: Option<u64>,
+}
+
+/// 已解析的压缩包 (内容常驻内存，条目按路径索引；tar.gz 只在解析时解压一次)
+struct Archive {
+    kind: ArchiveKind,
+    data: Vec<u8>,
+    entries: BTreeMap<String, ArchiveEntry>,
+    mtime: u64,
+}
+
+impl Archive {
+    fn parse(kind: ArchiveKind, data: Vec<u8>, mtime: u64, limit: u64) -> Result<Self, FsError> {
+        let (kind, data) = match kind {
+            ArchiveKind::TarGz => {
+                let tar = read_capped(flate2::read::GzDecoder::new(&data[..]), limit)?
+                    .ok_or_else(|| FsError::from(format!("Archive exceeds the decompressed size limit ({} bytes)", limit)))?;
+                (ArchiveKind::Tar, tar)
+            }
+            _ => (kind, data),
+        };
+        let mut entries = BTreeMap::new();
+        match kind {
+            ArchiveKind::Zip => {
+                let mut zip = zip::ZipArchive::new(Cursor::new(&data[..])).map_err(|e| e.to_string())?;
+                for i in 0..zip.len() {
+                    let file = zip.by_index(i).map_err(|e| e.to_string())?;
+                    let name = normalize_entry(file.name());
+                    if !name.is_empty() {
+                        entries.insert(name, ArchiveEntry { is_dir: file.is_dir(), size: file.size(), pos: Some(i as u64) });
+                    }
+                }
+            }
+            ArchiveKind::Tar | ArchiveKind::TarGz => {
+                let mut tar = tar::Archive::new(&data[..]);
+                for entry in tar.entries()? {
+                    let entry = entry?;
+                    let name = normalize_entry(&entry.path()?.to_string_lossy());
+                    if !name.is_empty() {
+                        let entry_type = entry.header().entry_type();
+                        let pos = (!entry_type.is_gnu_sparse()).then(|| entry.raw_file_position());
+                        entries.insert(name, ArchiveEntry { is_dir: entry_type.is_dir(), size: entry.size(), pos });
+                    }
+                }
+            }
+        }
+
+        // 部分压缩包不包含目录条目，按文件路径补齐
+        let files: Vec<String> = entries.keys().cloned().collect();
+        for name in files {
+            let mut dir = parent_of(&name);
+            while !dir.is_empty() && !entries.contains_key(dir) {
+                entries.insert(dir.to_string(), ArchiveEntry { is_dir: true, size: 0, pos: None });
+                dir = parent_of(dir);
+            }
+        }
+        Ok(Self { kind, data, entries, mtime })
+    }
+
+    /// 解压单个条目，解压后超过 limit 时返回 FileTooLarge
+    fn read_entry(&self, path: &str, inner: &str, limit: u64) -> Result<Vec<u8>, FsError> {
+        let entry = self.entries.get(inner)
+            .ok_or_else(|| FsError::from(format!("No such file in archive: {}", inner)))?;
+        let too_large = || FsError::FileTooLarge { path: path.to_string(), size: entry.size.max(limit + 1) };
+        match (self.kind, entry.pos) {
+            (ArchiveKind::Zip, Some(index)) => {
+                let mut zip = zip::ZipArchive::new(Cursor::new(&self.data[..])).map_err(|e| e.to_string())?;
+                let file = zip.by_index(index as usize).map_err(|e| e.to_string())?;
+                read_capped(file, limit)?.ok_or_else(too_large)
+            }
+            (ArchiveKind::Tar, Some(offset)) => {
+                // 数据已在内存中，直接按偏移切片
+                let start = (offset as usize).min(self.data.len());
+                let end = start.saturating_add(entry.size as usize).min(self.data.len());
+                if (end - start) as u64 > limit {
+                    return Err(too_large());
+                }
+                Ok(self.data[start..end].to_vec())
+            }
+            _ => {
+                let mut tar = tar::Archive::new(&self.data[..]);
+                for item in tar.entries()? {
+                    let item = item?;
+                    if normalize_entry(&item.path()?.to_string_lossy()) == inner {
+                        return read_capped(item, limit)?.ok_or_else(too_large);
+                    }
+                }
+                Err(format!("No such file in archive: {}", inner).into())
+            }
+        }
+    }
+
+    fn stat(&self, inner: &str) -> Option<FileStat> {
+        if inner.is_empty() {
+            return Some(FileStat { file_type: "dir".to_string(), size: 0, mtime: self.mtime, is_large: false, attrs: entry_attributes(inner) });
+        }
+        self.entries.get(inner).map(|e| FileStat {
+            file_type: if e.is_dir { "dir" } else { "file" }.to_string(),
+            size: e.size,
+            mtime: self.mtime,
+            is_large: largefile::is_large(e.size),
+            attrs: entry_attributes(inner),
+        })
+    }
+}
+
+/// 包内条目一律只读
+fn entry_attributes(inner: &str) -> FileAttributes {
+    let name = inner.rsplit('/').next().unwrap_or(inner);
+    FileAttributes { readonly: true, ..FileAttributes::from_name(name) }
+}
+
+struct CachedArchive {
+    path: String,
+    size: u64,
+    mtime: u64,
+    archive: Arc<Archive>,
+}
+
+/// 已解析压缩包的缓存 (按路径 + 大小 + 修改时间失效)，由 WorkspaceService 持有
+#[derive(Default)]
+pub struct ArchiveCache {
+    entries: Mutex<Vec<CachedArchive>>,
+    /// 用户确认后允许超过大文件阈值的压缩包或包内条目 -> 首次使用时压缩包的 (大小, 修改时间)
+    /// 压缩包变化后确认随之失效，需要重新确认
+    forced: Mutex<HashMap<String, Option<(u64, u64)>>>,
+}
+
+impl ArchiveCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 允许打开超过大文件阈值的压缩包 (传入压缩包路径) 或读取超大的包内条目 (传入包内路径)
+    pub fn force(&self, path: &str) {
+        self.forced.lock().unwrap().insert(path.to_string(), None);
+    }
+
+    // stamp 为压缩包当前的 (大小, 修改时间)
+    fn is_forced(&self, path: &str, stamp: (u64, u64)) -> bool {
+        let mut forced = self.forced.lock().unwrap();
+        let confirmed = match forced.get_mut(path) {
+            None => return false,
+            Some(confirmed) => confirmed,
+        };
+        match confirmed {
+            Some(at) if *at != stamp => {
+                forced.remove(path);
+                false
+            }
+            Some(_) => true,
+            None => {
+                *confirmed = Some(stamp);
+                true
+            }
+        }
+    }
+
+    fn get(&self, path: &str, size: u64, mtime: u64) -> Option<Arc<Archive>> {
+        let mut entries = self.entries.lock().unwrap();
+        let pos = entries.iter().position(|e| e.path == path && e.size == size && e.mtime == mtime)?;
+        // 移到末尾，最久未使用的最先淘汰
+        let entry = entries.remove(pos);
+        let archive = entry.archive.clone();
+        entries.push(entry);
+        Some(archive)
+    }
+
+    fn insert(&self, path: &str, size: u64, mtime: u64, archive: Arc<Archive>) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|e| e.path != path);
+        entries.push(CachedArchive { path: path.to_string(), size, mtime, archive });
+        if entries.len() > MAX_CACHED {
+            entries.remove(0);
+        }
+    }
+}
+
+/// 只读的压缩包文件系统，叠加在 host 之上：包内路径在此解析，其余路径原样交给 host
+/// 压缩包本身也通过 host 读取，因此同样受沙箱约束；从包内复制到磁盘即为解压
+pub struct ArchiveFs<'a, H: FileSystem> {
+    host: H,
+    cache: &'a ArchiveCache,
+}
+
+impl<'a, H: FileSystem> ArchiveFs<'a, H> {
+    pub fn new(host: H, cache: &'a ArchiveCache) -> Self {
+        Self { host, cache }
+    }
+
+    /// 返回压缩包、压缩包路径、包内路径与压缩包的 (大小, 修改时间)
+    async fn open(&self, path: &str) -> Result<(Arc<Archive>, String, String, (u64, u64)), FsError> {
+        let (archive_path, inner) = split(path).ok_or_else(|| FsError::from(format!("Invalid archive path: {}", path)))?;
+        let kind = ArchiveKind::from_path(archive_path)
+            .ok_or_else(|| FsError::from(format!("Unsupported archive type: {}", archive_path)))?;
+        let stat = self.host.stat(archive_path).await?;
+        let stamp = (stat.size, stat.mtime);
+        if let Some(archive) = self.cache.get(archive_path, stat.size, stat.mtime) {
+            return Ok((archive, archive_path.to_string(), inner, stamp));
+        }
+        // 压缩包整体载入内存，超过阈值时需用户确认
+        let forced = self.cache.is_forced(archive_path, stamp);
+        if stat.is_large && !forced {
+            return Err(FsError::FileTooLarge { path: archive_path.to_string(), size: stat.size });
+        }
+        let data = self.host.read_bytes(archive_path).await?;
+        let mtime = stat.mtime;
+        let limit = size_limit(forced);
+        let archive = tokio::task::spawn_blocking(move || Archive::parse(kind, data, mtime, limit))
+            .await
+            .map_err(|e| e.to_string())??;
+        let archive = Arc::new(archive);
+        self.cache.insert(archive_path, stat.size, stat.mtime, archive.clone());
+        Ok((archive, archive_path.to_string(), inner, stamp))
+    }
+
+    fn read_only(path: &str) -> FsError {
+        format!("Archive is read-only: {}", path).into()
+    }
+}
+
+#[async_trait]
+impl<H: FileSystem> FileSystem for ArchiveFs<'_, H> {
+    fn get_cwd(&self) -> String {
+        self.host.get_cwd()
+    }
+
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.set_cwd(path)
+    }
+
+    fn roots(&self) -> Vec<WorkspaceFolder> {
+        self.host.roots()
+    }
+
+    fn set_roots(&self, roots: Vec<WorkspaceFolder>) -> Result<(), FsError> {
+        self.host.set_roots(roots)
+    }
+
+    fn sandbox_mode(&self) -> SandboxMode {
+        self.host.sandbox_mode()
+    }
+
+    /// 包内路径的访问权限等同于压缩包文件本身
+    fn check_access(&self, path: &str, access: Access, mode: SandboxMode) -> Result<(), FsError> {
+        match split(path) {
+            Some((archive, _)) => self.host.check_access(archive, access, mode),
+            None => self.host.check_access(path, access, mode),
+        }
+    }
+
+    fn allows_restricted_callers(&self, path: &str) -> bool {
+        match split(path) {
+            Some((archive, _)) => self.host.allows_restricted_callers(archive),
+            None => self.host.allows_restricted_callers(path),
+        }
+    }
+
+    fn absolute_path(&self, path: &str) -> String {
+        if is_archive_path(path) {
+            return path.to_string();
+        }
+        self.host.absolute_path(path)
+    }
+
+    fn allow_path(&self, path: &str) {
+        match split(path) {
+            Some((archive, _)) => self.host.allow_path(archive),
+            None => self.host.allow_path(path),
+        }
+    }
+
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> {
+        if !is_archive_path(path) {
+            return self.host.read_dir(path).await;
+        }
+        let (archive, archive_path, inner, _) = self.open(path).await?;
+        match archive.stat(&inner) {
+            Some(stat) if stat.file_type == "dir" => {}
+            Some(_) => return Err(format!("Not a directory: {}", path).into()),
+            None => return Err(format!("No such file or directory: {}", path).into()),
+        }
+        let mut items: Vec<FileItem> = archive.entries.iter()
+            .filter(|(name, _)| parent_of(name) == inner)
+            .map(|(name, entry)| FileItem {
+                name: name.rsplit('/').next().unwrap_or(name).to_string(),
+                path: join(&archive_path, name),
+                is_dir: entry.is_dir,
+                attrs: entry_attributes(name),
+            })
+            .collect();
+        items.sort_by(|a, b| {
+            if a.is_dir != b.is_dir { b.is_dir.cmp(&a.is_dir) }
+            else { a.name.cmp(&b.name) }
+        });
+        Ok(items)
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        if !is_archive_path(path) {
+            return self.host.read_bytes(path).await;
+        }
+        let (archive, _, inner, stamp) = self.open(path).await?;
+        let forced = self.cache.is_forced(path, stamp);
+        match archive.entries.get(&inner) {
+            Some(e) if e.is_dir => return Err(format!("Is a directory: {}", path).into()),
+            Some(e) if largefile::is_large(e.size) && !forced => {
+                return Err(FsError::FileTooLarge { path: path.to_string(), size: e.size });
+            }
+            Some(_) => {}
+            None => return Err(format!("No such file in archive: {}", path).into()),
+        }
+        let path = path.to_string();
+        tokio::task::spawn_blocking(move || archive.read_entry(&path, &inner, size_limit(forced)))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+
+    fn supports_range_reads(&self, path: &str) -> bool {
+        !is_archive_path(path) && self.host.supports_range_reads(path)
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        if !is_archive_path(path) {
+            return self.host.read_range(path, offset, length).await;
+        }
+        let bytes = self.read_bytes(path).await?;
+        let start = (offset as usize).min(bytes.len());
+        let end = start.saturating_add(length).min(bytes.len());
+        Ok(bytes[start..end].to_vec())
+    }
+
+    async fn read_link(&self, path: &str) -> Result<String, FsError> {
+        if !is_archive_path(path) {
+            return self.host.read_link(path).await;
+        }
+        Err(format!("Not a symbolic link: {}", path).into())
+    }
+
+    /// 包内没有符号链接，真实路径即自身
+    async fn real_path(&self, path: &str) -> Result<String, FsError> {
+        if !is_archive_path(path) {
+            return self.host.real_path(path).await;
+        }
+        Ok(path.to_string())
+    }
+
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> {
+        if !is_archive_path(path) {
+            return self.host.stat(path).await;
+        }
+        let (archive, _, inner, _) = self.open(path).await?;
+        archive.stat(&inner).ok_or_else(|| format!("No such file or directory: {}", path).into())
+    }
+
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.write_bytes(path, data).await
+    }
+
+    async fn write_range(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.write_range(path, offset, data).await
+    }
+
+    async fn create_file(&self, path: &str) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.create_file(path).await
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.create_dir(path).await
+    }
+
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.remove_item(path).await
+    }
+
+    async fn trash_item(&self, path: &str) -> Result<bool, FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.trash_item(path).await
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), FsError> {
+        if is_archive_path(to) {
+            return Err(Self::read_only(to));
+        }
+        if is_archive_path(from) {
+            let bytes = self.read_bytes(from).await?;
+            return self.host.write_bytes(to, &bytes).await;
+        }
+        self.host.copy_file(from, to).await
+    }
+
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        if is_archive_path(from) || is_archive_path(to) {
+            return Err(Self::read_only(from));
+        }
+        self.host.rename_item(from, to).await
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.set_mode(path, mode).await
+    }
+
+    async fn set_readonly(&self, path: &str, readonly: bool) -> Result<(), FsError> {
+        if is_archive_path(path) {
+            return Err(Self::read_only(path));
+        }
+        self.host.set_readonly(path, readonly).await
+    }
+
+    async fn is_writable(&self, path: &str) -> Result<bool, FsError> {
+        if is_archive_path(path) {
+            return Ok(false);
+        }
+        self.host.is_writable(path).await
+    }
+}
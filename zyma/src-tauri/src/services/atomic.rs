@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use crate::models::AppSettings;
+
+/// 保存前对原文件的备份策略
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BackupPolicy {
+    #[default]
+    None,
+    /// 覆盖式保留一个 `<file>.bak`
+    Single,
+    /// GNU 风格的编号备份 `<file>.~N~`，最多保留 N 份
+    Numbered(u32),
+}
+
+impl BackupPolicy {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        match settings.save_backup.as_deref() {
+            Some("bak") => BackupPolicy::Single,
+            Some("numbered") => BackupPolicy::Numbered(settings.save_backup_limit.unwrap_or(5).max(1)),
+            _ => BackupPolicy::None,
+        }
+    }
+}
+
+/// 崩溃安全的写入：同目录临时文件 -> fsync -> rename 覆盖目标
+/// 目标若为符号链接则替换其指向的真实文件，并尽量保留原文件的权限与属主
+pub async fn atomic_write(path: &Path, data: &[u8], backup: BackupPolicy) -> std::io::Result<()> {
+    let target = match fs::canonicalize(path).await {
+        Ok(real) => real,
+        Err(_) => path.to_path_buf(),
+    };
+    let original = fs::metadata(&target).await.ok().filter(|m| m.is_file());
+
+    if original.is_some() {
+        write_backup(&target, backup).await?;
+    }
+
+    let tmp = temp_path_for(&target);
+    let result = write_and_replace(&tmp, &target, data, original.as_ref()).await;
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp).await;
+    }
+    result
+}
+
+async fn write_and_replace(tmp: &Path, target: &Path, data: &[u8], original: Option<&std::fs::Metadata>) -> std::io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).create_new(true).open(tmp).await?;
+    file.write_all(data).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    if let Some(meta) = original {
+        fs::set_permissions(tmp, meta.permissions()).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // 非 root 用户通常无法修改属主，失败时忽略
+            let _ = std::os::unix::fs::chown(tmp, Some(meta.uid()), Some(meta.gid()));
+        }
+    }
+
+    fs::rename(tmp, target).await?;
+    sync_parent_dir(target).await;
+    Ok(())
+}
+
+async fn write_backup(target: &Path, policy: BackupPolicy) -> std::io::Result<()> {
+    let (backup, stale) = match plan_backup(target, policy).await {
+        Some(plan) => plan,
+        None => return Ok(()),
+    };
+    fs::copy(target, backup).await?;
+    for p in stale {
+        let _ = fs::remove_file(p).await;
+    }
+    Ok(())
+}
+
+/// 按策略计算本次的备份路径，以及复制完成后需要清理的旧备份
+/// 提权保存时由辅助进程按此执行复制与清理
+pub async fn plan_backup(target: &Path, policy: BackupPolicy) -> Option<(PathBuf, Vec<PathBuf>)> {
+    let file_name = target.file_name()?.to_string_lossy().to_string();
+    match policy {
+        BackupPolicy::None => None,
+        BackupPolicy::Single => Some((target.with_file_name(format!("{}.bak", file_name)), Vec::new())),
+        BackupPolicy::Numbered(limit) => {
+            let existing = numbered_backups(target, &file_name).await;
+            // 编号按 u64 处理，手工放置的超大编号 (如 .~4294967295~) 不会导致溢出
+            let next = existing.iter().map(|(n, _)| *n).max().unwrap_or(0).saturating_add(1);
+            // 超出数量上限的旧备份
+            let stale = existing.into_iter()
+                .filter(|(n, _)| n.saturating_add(u64::from(limit)) <= next)
+                .map(|(_, p)| p)
+                .collect();
+            Some((target.with_file_name(format!("{}.~{}~", file_name, next)), stale))
+        }
+    }
+}
+
+async fn numbered_backups(target: &Path, file_name: &str) -> Vec<(u64, PathBuf)> {
+    let mut found = Vec::new();
+    let dir = match target.parent() {
+        Some(d) => d,
+        None => return found,
+    };
+    let prefix = format!("{}.~", file_name);
+    if let Ok(mut entries) = fs::read_dir(dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(num) = name.strip_prefix(&prefix).and_then(|s| s.strip_suffix('~')) {
+                if let Ok(n) = num.parse::<u64>() {
+                    found.push((n, entry.path()));
+                }
+            }
+        }
+    }
+    found
+}
+
+fn temp_path_for(target: &Path) -> PathBuf {
+    let name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    target.with_file_name(format!(".{}.zyma-tmp-{}-{}", name, std::process::id(), nanos))
+}
+
+/// 确保 rename 本身也已落盘 (仅 Unix 支持对目录 fsync)
+async fn sync_parent_dir(target: &Path) {
+    #[cfg(unix)]
+    if let Some(dir) = target.parent() {
+        if let Ok(d) = fs::File::open(dir).await {
+            let _ = d.sync_all().await;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = target;
+}
@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use tokio::sync::Mutex;
+use crate::models::DirtyBuffer;
+
+/// 热退出：把未保存的编辑内容 (含未命名文件) 备份到数据目录的 `backups/` 下，重启后恢复
+/// 前端按防抖间隔推送脏缓冲区，保存、撤销修改或关闭标签页时丢弃对应备份
+pub struct BackupService {
+    root: PathBuf,
+    // 串行化写入与删除，避免迟到的写入复活已丢弃的备份
+    lock: Mutex<()>,
+}
+
+impl BackupService {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, lock: Mutex::new(()) }
+    }
+
+    pub fn default_root() -> PathBuf {
+        crate::services::paths::dirs().data_path("backups")
+    }
+
+    fn file_for(&self, id: &str) -> PathBuf {
+        let digest = Sha256::digest(id.as_bytes());
+        let name: String = digest.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        self.root.join(format!("{}.json", name))
+    }
+
+    /// 写入 (覆盖) 一个缓冲区的备份
+    pub async fn backup(&self, buffer: &DirtyBuffer) -> Result<(), String> {
+        let content = serde_json::to_string(buffer).map_err(|e| e.to_string())?;
+        let target = self.file_for(&buffer.id);
+        let tmp = target.with_extension("json.tmp");
+        let _guard = self.lock.lock().await;
+        fs::create_dir_all(&self.root).await.map_err(|e| e.to_string())?;
+        fs::write(&tmp, content).await.map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &target).await.map_err(|e| e.to_string())
+    }
+
+    /// 丢弃备份 (已保存或放弃修改)，不存在时视为成功
+    pub async fn discard(&self, id: &str) -> Result<(), String> {
+        let _guard = self.lock.lock().await;
+        match fs::remove_file(self.file_for(id)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// 读取全部备份，按备份时间排序；损坏的文件会被跳过
+    pub fn load_all(&self) -> Vec<DirtyBuffer> {
+        let mut buffers: Vec<DirtyBuffer> = match std::fs::read_dir(&self.root) {
+            Ok(entries) => entries.flatten()
+                .filter(|e| e.path().extension().map(|ext| ext == "json").unwrap_or(false))
+                .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+                .filter_map(|content| serde_json::from_str(&content).ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        buffers.sort_by_key(|b| b.timestamp);
+        buffers
+    }
+}
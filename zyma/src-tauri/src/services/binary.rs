@@ -0,0 +1,92 @@
+use serde::Serialize;
+use encoding_rs::Encoding;
+use crate::services::vfs::{FileSystem, FsError};
+
+/// 检测时采样的字节数
+pub const SNIFF_LEN: usize = 8 * 1024;
+
+/// 十六进制视图每行的字节数
+pub const HEX_ROW_WIDTH: usize = 16;
+
+/// 单次分页最多返回的行数
+pub const MAX_HEX_ROWS: usize = 4096;
+
+/// 判断内容是否为二进制：带 BOM 的视为文本 (UTF-16 本身包含大量 0 字节)，
+/// 否则出现 NUL，或不可打印的控制字符超过采样的 10% 即视为二进制
+pub fn is_binary(bytes: &[u8]) -> bool {
+    if Encoding::for_bom(bytes).is_some() {
+        return false;
+    }
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+    // \t \n \f \r 与 ESC (终端日志中的颜色控制) 不算
+    let control = sample.iter()
+        .filter(|b| **b < 0x20 && !matches!(**b, b'\t' | b'\n' | 0x0c | b'\r' | 0x1b))
+        .count();
+    control * 10 > sample.len()
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HexRow {
+    pub offset: u64,
+    /// 原始字节 (前端可按需自行格式化)
+    pub bytes: Vec<u8>,
+    /// "4d 5a 90 00 ..."
+    pub hex: String,
+    /// 可打印 ASCII 原样显示，其余为 '.'
+    pub ascii: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HexDump {
+    pub offset: u64,
+    pub size: u64,
+    pub rows: Vec<HexRow>,
+    pub eof: bool,
+}
+
+/// 从 offset 开始读取 rows 行 (offset 会向下对齐到行宽)
+pub async fn hex_dump(fs: &dyn FileSystem, path: &str, offset: u64, rows: usize) -> Result<HexDump, FsError> {
+    let size = fs.stat(path).await?.size;
+    let offset = offset - offset % HEX_ROW_WIDTH as u64;
+    let rows = rows.clamp(1, MAX_HEX_ROWS);
+    let bytes = fs.read_range(path, offset, rows * HEX_ROW_WIDTH).await?;
+    let rows = bytes.chunks(HEX_ROW_WIDTH).enumerate().map(|(i, chunk)| HexRow {
+        offset: offset + (i * HEX_ROW_WIDTH) as u64,
+        bytes: chunk.to_vec(),
+        hex: chunk.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "),
+        ascii: chunk.iter().map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' }).collect(),
+    }).collect();
+    Ok(HexDump { offset, size, rows, eof: offset + bytes.len() as u64 >= size })
+}
+
+/// 解析 "4d5a 90 00" 形式的十六进制字符串 (忽略空白)
+pub fn parse_hex(input: &str) -> Result<Vec<u8>, FsError> {
+    let digits: Vec<u8> = input.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if digits.len() % 2 != 0 {
+        return Err("Hex string must have an even number of digits".to_string().into());
+    }
+    digits.chunks(2).map(|pair| {
+        let s = std::str::from_utf8(pair).unwrap_or("");
+        u8::from_str_radix(s, 16).map_err(|_| FsError::from(format!("Invalid hex byte: {}", s)))
+    }).collect()
+}
+
+/// 在内存中从 offset 开始覆盖写入 data (offset 等于长度时为追加)
+pub fn apply_patch(bytes: &mut Vec<u8>, offset: u64, data: &[u8]) -> Result<(), FsError> {
+    let start = offset as usize;
+    if offset > bytes.len() as u64 {
+        return Err(format!("Offset {} is beyond the end of file ({} bytes)", offset, bytes.len()).into());
+    }
+    let end = start + data.len();
+    if end > bytes.len() {
+        bytes.resize(end, 0);
+    }
+    bytes[start..end].copy_from_slice(data);
+    Ok(())
+}
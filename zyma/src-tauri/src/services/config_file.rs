@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use crate::models::AppSettings;
+use crate::services::settings::check_value;
+
+/// 当前配置版本，调整配置结构时加一并在 MIGRATIONS 末尾追加一步迁移
+pub const CURRENT_VERSION: u32 = 1;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// MIGRATIONS[n] 把版本 n 的配置升级到 n + 1
+const MIGRATIONS: &[Migration] = &[migrate_v0];
+
+/// 版本 0 (没有 version 字段) -> 1：
+/// 旧版设置面板会把数字存成字符串；热退出的缓冲区曾写在 session.dirty_buffers 中，现已移到数据目录的 `backups/`
+fn migrate_v0(values: &mut Map<String, Value>) {
+    for key in ["font_size", "ui_font_size", "tab_size", "save_backup_limit", "local_history_max_entries", "local_history_max_days"] {
+        if let Some(n) = values.get(key).and_then(Value::as_str).and_then(|s| s.trim().parse::<u32>().ok()) {
+            values.insert(key.to_string(), json!(n));
+        }
+    }
+    for key in ["window_width", "window_height"] {
+        if let Some(n) = values.get(key).and_then(Value::as_str).and_then(|s| s.trim().parse::<f64>().ok()) {
+            values.insert(key.to_string(), json!(n));
+        }
+    }
+    if let Some(Value::Object(session)) = values.get_mut("session") {
+        session.remove("dirty_buffers");
+    }
+}
+
+/// 配置文件中的问题，行列号从 1 开始 (无法定位时为 0)
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SettingsIssue {
+    /// 出错的键，整个文件无法解析时为空
+    pub key: Option<String>,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// 读取配置文件的结果
+#[derive(Debug, Clone, Default)]
+pub struct LoadedConfig {
+    /// 迁移并去掉无效值后的键值，写回时以此为基础
+    pub values: Map<String, Value>,
+    pub settings: AppSettings,
+    pub issues: Vec<SettingsIssue>,
+    /// 迁移前的版本，无需迁移时为空
+    pub migrated_from: Option<u32>,
+}
+
+impl LoadedConfig {
+    /// 妨碍写回的问题：文件无法解析，或由更新的版本写入 (写回会丢失新版的设置)
+    pub fn blocking_issue(&self) -> Option<&SettingsIssue> {
+        self.issues.iter().find(|i| i.key.is_none())
+    }
+}
+
+/// 补齐缺少的键 (取默认值)
+pub fn with_defaults(values: &Map<String, Value>) -> Map<String, Value> {
+    let mut merged = defaults();
+    merged.extend(values.clone());
+    merged
+}
+
+/// 在默认值之上应用键值
+pub fn to_settings(values: &Map<String, Value>) -> Result<AppSettings, String> {
+    serde_json::from_value(Value::Object(with_defaults(values))).map_err(|e| e.to_string())
+}
+
+fn defaults() -> Map<String, Value> {
+    match serde_json::to_value(AppSettings::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// 解析配置内容：容忍注释与末尾逗号，按版本迁移，逐键校验；
+/// 无效的键只丢弃该键 (数组只丢弃无效的元素)，其余设置照常生效
+pub fn parse(content: &str) -> LoadedConfig {
+    let cleaned = strip_jsonc(content);
+    let mut values = match serde_json::from_str::<Value>(&cleaned) {
+        Ok(Value::Object(map)) => map,
+        Ok(_) => return failed(SettingsIssue { key: None, message: "Settings must be a JSON object".to_string(), line: 1, column: 1 }),
+        Err(e) => return failed(SettingsIssue { key: None, message: e.to_string(), line: e.line(), column: e.column() }),
+    };
+
+    let positions = key_positions(&cleaned);
+    let from = values.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if from > CURRENT_VERSION as u64 {
+        // 尽量按已知的键读取，但不迁移也不允许写回
+        let (line, column) = positions.get("version").copied().unwrap_or((0, 0));
+        let issue = SettingsIssue {
+            key: None,
+            message: format!("Settings were written by a newer version of Zyma (version {}), changes will not be saved", from),
+            line,
+            column,
+        };
+        let mut loaded = parse_values(values, &positions);
+        loaded.issues.insert(0, issue);
+        return loaded;
+    }
+    let from = from as u32;
+    for migration in MIGRATIONS.iter().skip(from as usize) {
+        migration(&mut values);
+    }
+    let migrated_from = if from < CURRENT_VERSION {
+        values.insert("version".to_string(), json!(CURRENT_VERSION));
+        Some(from)
+    } else {
+        None
+    };
+
+    let mut loaded = parse_values(values, &positions);
+    loaded.migrated_from = migrated_from;
+    loaded
+}
+
+// 逐键校验已迁移的键值
+fn parse_values(values: Map<String, Value>, positions: &HashMap<String, (usize, usize)>) -> LoadedConfig {
+    let mut merged = defaults();
+    let mut kept = Map::new();
+    let mut issues = Vec::new();
+    for (key, value) in values {
+        let accepted = match check_value(&merged, &key, &value) {
+            Ok(()) => Some(value),
+            Err(message) => {
+                let (line, column) = positions.get(&key).copied().unwrap_or((0, 0));
+                issues.push(SettingsIssue { key: Some(key.clone()), message, line, column });
+                // 例如一条损坏的 ssh_profiles 不应连累其他连接配置
+                match value {
+                    Value::Array(items) => {
+                        let valid: Vec<Value> = items.into_iter()
+                            .filter(|item| check_value(&merged, &key, &Value::Array(vec![item.clone()])).is_ok())
+                            .collect();
+                        if valid.is_empty() { None } else { Some(Value::Array(valid)) }
+                    }
+                    _ => None,
+                }
+            }
+        };
+        if let Some(value) = accepted {
+            merged.insert(key.clone(), value.clone());
+            kept.insert(key, value);
+        }
+    }
+
+    let settings = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+    LoadedConfig { values: kept, settings, issues, migrated_from: None }
+}
+
+fn failed(issue: SettingsIssue) -> LoadedConfig {
+    LoadedConfig { issues: vec![issue], ..Default::default() }
+}
+
+/// 读取配置文件 (不修改任何文件)
+pub fn load(path: &Path) -> LoadedConfig {
+    match std::fs::read_to_string(path) {
+        Ok(content) => parse(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => LoadedConfig::default(),
+        Err(e) => failed(SettingsIssue { key: None, message: e.to_string(), line: 0, column: 0 }),
+    }
+}
+
+pub fn corrupt_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(".corrupt");
+    path.with_file_name(name)
+}
+
+/// 把有问题的配置文件另存为 `.corrupt`，无效的键在下次保存时会被丢弃，原内容仍可找回
+pub fn keep_corrupt_copy(path: &Path) {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(_) => return,
+    };
+    let target = corrupt_path(path);
+    if std::fs::read(&target).map(|old| old == content).unwrap_or(false) {
+        return;
+    }
+    match std::fs::write(&target, content) {
+        Ok(()) => log::warn!("Settings file has errors, original kept at {}", target.display()),
+        Err(e) => log::error!("Failed to keep a copy of the broken settings file: {}", e),
+    }
+}
+
+// 把注释与末尾逗号替换为空格，保持行列号不变
+pub(crate) fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut out = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(&next) = chars.get(i + 1) {
+                    out.push(next);
+                    i += 1;
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.push(' ');
+                    i += 1;
+                }
+                continue;
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                out.push_str("  ");
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    out.push(if chars[i] == '\n' { '\n' } else { ' ' });
+                    i += 1;
+                }
+                if i < chars.len() {
+                    out.push_str("  ");
+                    i += 2;
+                }
+                continue;
+            }
+            ',' if matches!(chars[i + 1..].iter().find(|c| !c.is_whitespace()), Some('}') | Some(']')) => out.push(' '),
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+// 顶层各键在文件中的位置 (行, 列)
+fn key_positions(content: &str) -> HashMap<String, (usize, usize)> {
+    let chars: Vec<char> = content.chars().collect();
+    let mut positions = HashMap::new();
+    let (mut depth, mut line, mut column, mut i) = (0usize, 1usize, 1usize, 0usize);
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' {
+            let start = (line, column);
+            let mut text = String::new();
+            i += 1;
+            column += 1;
+            while i < chars.len() && chars[i] != '"' {
+                if chars[i] == '\\' && i + 1 < chars.len() {
+                    i += 1;
+                    column += 1;
+                }
+                text.push(chars[i]);
+                i += 1;
+                column += 1;
+            }
+            i += 1;
+            column += 1;
+            if depth == 1 && chars.get(i..).and_then(|rest| rest.iter().find(|c| !c.is_whitespace())) == Some(&':') {
+                positions.entry(text).or_insert(start);
+            }
+            continue;
+        }
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+        i += 1;
+    }
+    positions
+}
+
+/// 配置文件的 JSON Schema (draft-07)，由 AppSettings 派生，可供编辑器校验与补全
+pub fn schema() -> Value {
+    let generator = schemars::generate::SchemaSettings::draft07().into_generator();
+    let mut schema = generator.into_root_schema_for::<AppSettings>().to_value();
+    if let Some(root) = schema.as_object_mut() {
+        // 各层设置文件只写需要覆盖的键，不要求任何字段
+        root.remove("required");
+        // 语言专属覆盖块，如 "[rust]": { "tab_size": 2 }
+        root.insert("patternProperties".into(), json!({ "^\\[.+\\]$": { "type": "object" } }));
+        root.insert("additionalProperties".into(), json!(true));
+    }
+    schema
+}
@@ -4,15 +4,14 @@ use serde_json::Value;
 
 /// 全局上下文服务
 /// 允许业务层 (如 Pro 版) 存储全局状态，底座其他组件 (如 AI) 自动感知
+#[derive(Default)]
 pub struct ContextService {
     store: RwLock<HashMap<String, Value>>,
 }
 
 impl ContextService {
     pub fn new() -> Self {
-        Self {
-            store: RwLock::new(HashMap::new()),
-        }
+        Self::default()
     }
 
     pub fn set(&self, key: String, value: Value) {
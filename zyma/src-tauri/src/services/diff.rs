@@ -0,0 +1,191 @@
+use serde::Serialize;
+
+/// 每个差异块前后保留的上下文行数
+pub const CONTEXT_LINES: usize = 3;
+
+/// 编辑距离超过该值时放弃逐行比对，整体视为替换 (避免超大改动占用过多内存)
+const MAX_EDIT_DISTANCE: usize = 4000;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+/// 统一格式 (unified diff) 的一个差异块，行号从 1 开始
+#[derive(Serialize, Debug, Clone)]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// 逐行比较两段文本，返回带上下文的差异块；内容相同时为空
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffHunk> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&a, &b);
+    build_hunks(&ops, &a, &b, CONTEXT_LINES)
+}
+
+/// 渲染为 `diff -u` 风格的文本
+pub fn to_unified(hunks: &[DiffHunk], old_label: &str, new_label: &str) -> String {
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for hunk in hunks {
+        out.push_str(&format!("@@ -{},{} +{},{} @@\n", hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines));
+        for line in &hunk.lines {
+            let prefix = match line.kind {
+                DiffLineKind::Context => ' ',
+                DiffLineKind::Added => '+',
+                DiffLineKind::Removed => '-',
+            };
+            out.push(prefix);
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// 去掉公共前后缀后用 Myers 算法求最短编辑序列
+fn diff_ops(a: &[&str], b: &[&str]) -> Vec<Op> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<Op> = (0..prefix).map(|i| Op::Equal(i, i)).collect();
+    let middle = myers(a_mid, b_mid).unwrap_or_else(|| {
+        (0..a_mid.len()).map(Op::Delete).chain((0..b_mid.len()).map(Op::Insert)).collect()
+    });
+    ops.extend(middle.into_iter().map(|op| match op {
+        Op::Equal(x, y) => Op::Equal(x + prefix, y + prefix),
+        Op::Delete(x) => Op::Delete(x + prefix),
+        Op::Insert(y) => Op::Insert(y + prefix),
+    }));
+    ops.extend((0..suffix).map(|i| Op::Equal(a.len() - suffix + i, b.len() - suffix + i)));
+    ops
+}
+
+/// 编辑距离超过 MAX_EDIT_DISTANCE 时返回 None
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<Op>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = a.len() + b.len();
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // trace[d] 保存第 d 轮结束后 k ∈ [-d, d] 的最远 x
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut found = false;
+
+    'search: for d in 0..=max.min(MAX_EDIT_DISTANCE) as isize {
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) { v[idx + 1] } else { v[idx - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+                found = true;
+                break 'search;
+            }
+            k += 2;
+        }
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+    }
+
+    if !found {
+        return None;
+    }
+    let d_final = trace.len() as isize - 1;
+    let (mut x, mut y) = (n, m);
+
+    let get = |d: isize, k: isize| trace[d as usize][(k + d) as usize];
+    let mut ops = Vec::new();
+    for d in (1..=d_final).rev() {
+        let k = x - y;
+        let down = k == -d || (k != d && get(d - 1, k - 1) < get(d - 1, k + 1));
+        let prev_k = if down { k + 1 } else { k - 1 };
+        let prev_x = get(d - 1, prev_k);
+        let prev_y = prev_x - prev_k;
+        let (mid_x, mid_y) = if down { (prev_x, prev_x - k) } else { (prev_x + 1, prev_x + 1 - k) };
+        while x > mid_x && y > mid_y {
+            x -= 1;
+            y -= 1;
+            ops.push(Op::Equal(x as usize, y as usize));
+        }
+        ops.push(if down { Op::Insert(prev_y as usize) } else { Op::Delete(prev_x as usize) });
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        ops.push(Op::Equal(x as usize, y as usize));
+    }
+    ops.reverse();
+    Some(ops)
+}
+
+fn build_hunks(ops: &[Op], a: &[&str], b: &[&str], context: usize) -> Vec<DiffHunk> {
+    let changes: Vec<usize> = ops.iter().enumerate()
+        .filter(|(_, op)| !matches!(op, Op::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut hunks = Vec::new();
+    let mut i = 0;
+    while i < changes.len() {
+        // 相邻改动之间的相同行不超过 2 * context 时合并为同一块
+        let mut j = i;
+        while j + 1 < changes.len() && changes[j + 1] - changes[j] <= 2 * context + 1 {
+            j += 1;
+        }
+        let start = changes[i].saturating_sub(context);
+        let end = (changes[j] + context + 1).min(ops.len());
+
+        // 块起点对应的新旧行号
+        let (mut old_pos, mut new_pos) = (0, 0);
+        for op in &ops[..start] {
+            match op {
+                Op::Equal(..) => { old_pos += 1; new_pos += 1; }
+                Op::Delete(_) => old_pos += 1,
+                Op::Insert(_) => new_pos += 1,
+            }
+        }
+        let mut hunk = DiffHunk { old_start: old_pos + 1, old_lines: 0, new_start: new_pos + 1, new_lines: 0, lines: Vec::new() };
+        for op in &ops[start..end] {
+            let (kind, text) = match *op {
+                Op::Equal(x, _) => { hunk.old_lines += 1; hunk.new_lines += 1; (DiffLineKind::Context, a[x]) }
+                Op::Delete(x) => { hunk.old_lines += 1; (DiffLineKind::Removed, a[x]) }
+                Op::Insert(y) => { hunk.new_lines += 1; (DiffLineKind::Added, b[y]) }
+            };
+            hunk.lines.push(DiffLine { kind, text: text.to_string() });
+        }
+        // 与 diff -u 一致：空的一侧起始行号取前一行
+        if hunk.old_lines == 0 { hunk.old_start -= 1; }
+        if hunk.new_lines == 0 { hunk.new_start -= 1; }
+        hunks.push(hunk);
+        i = j + 1;
+    }
+    hunks
+}
@@ -0,0 +1,197 @@
+use std::borrow::Cow;
+use encoding_rs::{EncoderResult, Encoding};
+use crate::models::{FileReadResponse, WriteOptions};
+use crate::services::vfs::{FsError, UnmappableChar};
+
+pub const EOL_LF: &str = "LF";
+pub const EOL_CRLF: &str = "CRLF";
+pub const EOL_MIXED: &str = "Mixed";
+
+/// 单次保存最多报告的不可编码字符数量
+const MAX_UNMAPPABLE_REPORTS: usize = 100;
+
+/// 供 "以指定编码重新打开 / 保存" 选择的常用编码
+pub const COMMON_ENCODINGS: &[&str] = &[
+    "UTF-8", "UTF-16LE", "UTF-16BE", "GBK", "gb18030", "Big5",
+    "Shift_JIS", "EUC-JP", "ISO-2022-JP", "EUC-KR",
+    "windows-1250", "windows-1251", "windows-1252", "windows-1253", "windows-1254",
+    "windows-1255", "windows-1256", "windows-1257", "windows-1258", "windows-874",
+    "ISO-8859-2", "ISO-8859-5", "ISO-8859-7", "ISO-8859-15", "KOI8-R", "KOI8-U",
+];
+
+/// 自动检测字节流编码并解码为文本 (供各 FileSystem 实现复用)
+/// 同时记录 BOM 与换行风格，保证写回时可以原样还原
+pub fn decode(bytes: &[u8]) -> FileReadResponse {
+    // 优先信任 BOM
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (res, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return build_response(res.into_owned(), encoding.name().to_string(), true);
+    }
+
+    // 自动检测编码
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    let encoding = detector.guess(None, true);
+
+    let (res, has_errors) = encoding.decode_without_bom_handling(bytes);
+    let mut encoding_name = encoding.name().to_string();
+
+    if has_errors && encoding == encoding_rs::UTF_8 {
+        // 如果 UTF-8 解码失败，尝试 GBK (Windows 常见 ANSI)
+        let (res_gbk, errors_gbk) = encoding_rs::GBK.decode_without_bom_handling(bytes);
+        if !errors_gbk {
+            return build_response(res_gbk.into_owned(), "GBK".to_string(), false);
+        } else {
+            encoding_name = "Unknown".to_string();
+        }
+    }
+
+    // 规范化显示名称
+    encoding_name = match encoding_name.as_str() {
+        "UTF-8" => "UTF-8".to_string(),
+        "windows-1252" | "ISO-8859-1" => "ANSI".to_string(),
+        "Unknown" => "Unknown".to_string(),
+        _ => encoding_name
+    };
+
+    build_response(res.into_owned(), encoding_name, false)
+}
+
+/// 使用用户指定的编码解码 (用于纠正自动检测的误判)
+pub fn decode_with(bytes: &[u8], label: &str) -> Result<FileReadResponse, FsError> {
+    let encoding = resolve_encoding(label)
+        .ok_or_else(|| FsError::UnsupportedEncoding { encoding: label.to_string() })?;
+    let bom = bom_for(encoding);
+    let has_bom = !bom.is_empty() && bytes.starts_with(bom);
+    let body = if has_bom { &bytes[bom.len()..] } else { bytes };
+    let (res, _) = encoding.decode_without_bom_handling(body);
+    Ok(build_response(res.into_owned(), encoding.name().to_string(), has_bom))
+}
+
+/// 按写入选项把文本编码为字节 (换行转换 -> 编码 -> BOM)
+/// 遇到目标编码无法表示的字符时返回其行列位置，而不是静默替换
+pub fn encode(content: &str, options: &WriteOptions) -> Result<Vec<u8>, FsError> {
+    let encoding = match options.encoding.as_deref() {
+        Some(label) => resolve_encoding(label)
+            .ok_or_else(|| FsError::UnsupportedEncoding { encoding: label.to_string() })?,
+        None => encoding_rs::UTF_8,
+    };
+    let text = apply_line_ending(content, options.line_ending.as_deref());
+
+    let mut out = Vec::with_capacity(text.len() + 3);
+    if options.has_bom {
+        out.extend_from_slice(bom_for(encoding));
+    }
+
+    if encoding == encoding_rs::UTF_16LE {
+        text.encode_utf16().for_each(|u| out.extend_from_slice(&u.to_le_bytes()));
+    } else if encoding == encoding_rs::UTF_16BE {
+        text.encode_utf16().for_each(|u| out.extend_from_slice(&u.to_be_bytes()));
+    } else {
+        encode_strict(&text, encoding, &mut out)?;
+    }
+    Ok(out)
+}
+
+fn encode_strict(text: &str, encoding: &'static Encoding, out: &mut Vec<u8>) -> Result<(), FsError> {
+    let mut encoder = encoding.new_encoder();
+    let mut positions = Vec::new();
+    let mut unmappable_count = 0;
+    let mut locator = LineLocator::default();
+    let mut offset = 0;
+
+    loop {
+        let remaining = text.len() - offset;
+        let needed = encoder.max_buffer_length_from_utf8_without_replacement(remaining).unwrap_or(remaining * 4);
+        out.reserve(needed.max(16));
+        let (result, read) = encoder.encode_from_utf8_to_vec_without_replacement(&text[offset..], out, true);
+        offset += read;
+        match result {
+            EncoderResult::InputEmpty => break,
+            EncoderResult::OutputFull => continue,
+            EncoderResult::Unmappable(c) => {
+                unmappable_count += 1;
+                if positions.len() < MAX_UNMAPPABLE_REPORTS {
+                    let (line, column) = locator.locate(text, offset - c.len_utf8());
+                    positions.push(UnmappableChar { line, column, character: c.to_string() });
+                }
+            }
+        }
+    }
+
+    if unmappable_count > 0 {
+        return Err(FsError::Unmappable { encoding: encoding.name().to_string(), count: unmappable_count, positions });
+    }
+    Ok(())
+}
+
+/// 增量计算字节偏移对应的行列号 (偏移需单调递增)
+#[derive(Default)]
+struct LineLocator {
+    scanned: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl LineLocator {
+    fn locate(&mut self, text: &str, byte_offset: usize) -> (usize, usize) {
+        for (i, b) in text.as_bytes()[self.scanned..byte_offset].iter().enumerate() {
+            if *b == b'\n' {
+                self.line += 1;
+                self.line_start = self.scanned + i + 1;
+            }
+        }
+        self.scanned = byte_offset;
+        let column = text[self.line_start..byte_offset].chars().count();
+        (self.line + 1, column + 1)
+    }
+}
+
+/// 将显示名称 (如 "ANSI") 或标准标签解析为 encoding_rs 编码
+pub fn resolve_encoding(label: &str) -> Option<&'static Encoding> {
+    match label.trim() {
+        "ANSI" => Some(encoding_rs::WINDOWS_1252),
+        "Unknown" | "" => Some(encoding_rs::UTF_8),
+        other => Encoding::for_label(other.as_bytes()),
+    }
+}
+
+/// 统计换行风格：LF / CRLF / Mixed (无换行时视为 LF)
+pub fn detect_line_ending(content: &str) -> &'static str {
+    let crlf = content.matches("\r\n").count();
+    let lf = content.matches('\n').count() - crlf;
+    match (crlf, lf) {
+        (0, _) => EOL_LF,
+        (_, 0) => EOL_CRLF,
+        _ => EOL_MIXED,
+    }
+}
+
+/// 按目标换行风格转换文本，Mixed 或未指定时保持原样
+pub fn apply_line_ending<'a>(content: &'a str, line_ending: Option<&str>) -> Cow<'a, str> {
+    match line_ending {
+        Some(EOL_LF) if content.contains("\r\n") => Cow::Owned(content.replace("\r\n", "\n")),
+        Some(EOL_CRLF) if content.contains('\n') => {
+            Cow::Owned(content.replace("\r\n", "\n").replace('\n', "\r\n"))
+        }
+        _ => Cow::Borrowed(content),
+    }
+}
+
+fn bom_for(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == encoding_rs::UTF_8 {
+        b"\xEF\xBB\xBF"
+    } else if encoding == encoding_rs::UTF_16LE {
+        b"\xFF\xFE"
+    } else if encoding == encoding_rs::UTF_16BE {
+        b"\xFE\xFF"
+    } else {
+        // 其余编码没有 BOM 概念
+        b""
+    }
+}
+
+fn build_response(content: String, encoding: String, has_bom: bool) -> FileReadResponse {
+    let line_ending = detect_line_ending(&content).to_string();
+    FileReadResponse { content, encoding, has_bom, line_ending, version: None }
+}
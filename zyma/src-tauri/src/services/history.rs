@@ -0,0 +1,237 @@
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use tokio::sync::Mutex;
+use crate::models::AppSettings;
+use crate::services::sandbox::Caller;
+use crate::services::largefile::LARGE_FILE_THRESHOLD;
+use crate::services::version::content_hash;
+use crate::services::vfs::FsError;
+
+const INDEX_FILE: &str = "entries.json";
+
+/// 本地历史的保留策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryPolicy {
+    pub enabled: bool,
+    /// 每个文件最多保留的快照数
+    pub max_entries: usize,
+    /// 超过该天数的快照会被清理 (0 表示不按时间清理)
+    pub max_age_days: u64,
+}
+
+impl Default for HistoryPolicy {
+    fn default() -> Self {
+        Self { enabled: true, max_entries: 50, max_age_days: 30 }
+    }
+}
+
+impl HistoryPolicy {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let default = Self::default();
+        Self {
+            enabled: settings.local_history.unwrap_or(default.enabled),
+            max_entries: settings.local_history_max_entries.map(|n| n.max(1) as usize).unwrap_or(default.max_entries),
+            max_age_days: settings.local_history_max_days.map(u64::from).unwrap_or(default.max_age_days),
+        }
+    }
+}
+
+/// 快照的来源
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HistorySource {
+    /// 用户保存
+    Save,
+    /// 插件或 Agent 写入
+    Agent,
+    /// 保存前发现的外部修改
+    External,
+    /// 从历史恢复
+    Restore,
+}
+
+impl HistorySource {
+    pub fn for_caller(caller: Caller) -> Self {
+        match caller {
+            Caller::User => HistorySource::Save,
+            Caller::Plugin | Caller::Agent => HistorySource::Agent,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: String,
+    /// 毫秒时间戳
+    pub timestamp: u64,
+    pub size: u64,
+    pub hash: String,
+    pub source: HistorySource,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct HistoryIndex {
+    path: String,
+    entries: Vec<HistoryEntry>,
+}
+
+/// 本地文件历史：每次保存在数据目录的 `history/<路径哈希>/` 下留一份 gzip 快照
+pub struct HistoryService {
+    root: RwLock<Option<PathBuf>>,
+    policy: RwLock<HistoryPolicy>,
+    // 串行化对索引文件的读-改-写
+    lock: Mutex<()>,
+}
+
+impl Default for HistoryService {
+    /// 未指定存储目录，记录操作为空操作
+    fn default() -> Self {
+        Self { root: RwLock::new(None), policy: RwLock::new(HistoryPolicy::default()), lock: Mutex::new(()) }
+    }
+}
+
+impl HistoryService {
+    pub fn new(root: PathBuf, policy: HistoryPolicy) -> Self {
+        Self { root: RwLock::new(Some(root)), policy: RwLock::new(policy), lock: Mutex::new(()) }
+    }
+
+    pub fn default_root() -> PathBuf {
+        crate::services::paths::dirs().data_path("history")
+    }
+
+    pub fn set_root(&self, root: PathBuf) {
+        *self.root.write().unwrap() = Some(root);
+    }
+
+    pub fn set_policy(&self, policy: HistoryPolicy) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.policy.read().unwrap().enabled && self.root.read().unwrap().is_some()
+    }
+
+    /// path 应为规范化的绝对路径 (见 WorkspaceService::history_key)，否则同一文件会分成多条时间线
+    fn dir_for(&self, path: &str) -> Result<PathBuf, FsError> {
+        let root = self.root.read().unwrap().clone()
+            .ok_or_else(|| FsError::from("Local history is not available".to_string()))?;
+        let digest = Sha256::digest(path.as_bytes());
+        let name: String = digest.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        Ok(root.join(name))
+    }
+
+    /// 记录一份快照；与最近一份内容相同、历史已关闭或文件过大时不记录并返回 None
+    pub async fn record(&self, path: &str, content: &[u8], source: HistorySource) -> Result<Option<HistoryEntry>, FsError> {
+        if !self.enabled() || content.len() as u64 >= LARGE_FILE_THRESHOLD {
+            return Ok(None);
+        }
+        let dir = self.dir_for(path)?;
+        let policy = *self.policy.read().unwrap();
+        let hash = content_hash(content);
+
+        let _guard = self.lock.lock().await;
+        let mut index = load_index(&dir).await?;
+        if index.entries.last().map(|e| e.hash == hash).unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let timestamp = now_millis();
+        // 同一毫秒内的多次保存以序号区分
+        let mut id = timestamp.to_string();
+        let mut n = 1;
+        while index.entries.iter().any(|e| e.id == id) {
+            id = format!("{}-{}", timestamp, n);
+            n += 1;
+        }
+        fs::create_dir_all(&dir).await?;
+        fs::write(dir.join(format!("{}.gz", id)), compress(content)?).await?;
+
+        let entry = HistoryEntry { id, timestamp, size: content.len() as u64, hash, source };
+        index.path = path.to_string();
+        index.entries.push(entry.clone());
+        prune(&dir, &mut index, policy, timestamp).await;
+        save_index(&dir, &index).await?;
+        Ok(Some(entry))
+    }
+
+    /// 列出文件的快照，最新的在前
+    pub async fn list(&self, path: &str) -> Result<Vec<HistoryEntry>, FsError> {
+        let dir = self.dir_for(path)?;
+        let _guard = self.lock.lock().await;
+        let mut entries = load_index(&dir).await?.entries;
+        entries.reverse();
+        Ok(entries)
+    }
+
+    /// 读取快照的原始字节
+    pub async fn read(&self, path: &str, id: &str) -> Result<Vec<u8>, FsError> {
+        let dir = self.dir_for(path)?;
+        let _guard = self.lock.lock().await;
+        if !load_index(&dir).await?.entries.iter().any(|e| e.id == id) {
+            return Err(format!("History entry not found: {}", id).into());
+        }
+        let data = fs::read(dir.join(format!("{}.gz", id))).await?;
+        decompress(&data)
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, FsError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, FsError> {
+    let mut out = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+/// 索引不存在时视为空；无法解析时报错，避免下次保存用空索引覆盖掉已有的快照记录
+async fn load_index(dir: &Path) -> Result<HistoryIndex, FsError> {
+    let path = dir.join(INDEX_FILE);
+    match fs::read_to_string(&path).await {
+        Ok(content) => serde_json::from_str(&content)
+            .map_err(|e| format!("Local history index is corrupted ({}): {}", path.display(), e).into()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HistoryIndex::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn save_index(dir: &Path, index: &HistoryIndex) -> Result<(), FsError> {
+    let content = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    let tmp = dir.join(format!("{}.tmp", INDEX_FILE));
+    fs::write(&tmp, content).await?;
+    fs::rename(&tmp, dir.join(INDEX_FILE)).await?;
+    Ok(())
+}
+
+/// 按数量与时间清理旧快照，最新的一份始终保留
+async fn prune(dir: &Path, index: &mut HistoryIndex, policy: HistoryPolicy, now: u64) {
+    let max_age = policy.max_age_days.saturating_mul(24 * 60 * 60 * 1000);
+    let len = index.entries.len();
+    let mut keep = Vec::with_capacity(len);
+    for (i, entry) in index.entries.drain(..).enumerate() {
+        let is_latest = i + 1 == len;
+        let too_many = len - i > policy.max_entries;
+        let too_old = max_age > 0 && now.saturating_sub(entry.timestamp) > max_age;
+        if is_latest || !(too_many || too_old) {
+            keep.push(entry);
+        } else {
+            let _ = fs::remove_file(dir.join(format!("{}.gz", entry.id))).await;
+        }
+    }
+    index.entries = keep;
+}
@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use encoding_rs::Encoding;
+use serde::Serialize;
+use crate::services::encoding;
+use crate::services::vfs::{FileSystem, FsError};
+
+/// 超过该大小的文件不再整体读入，改为分段读取
+pub const LARGE_FILE_THRESHOLD: u64 = 50 * 1024 * 1024;
+
+/// 每隔多少行记录一次字节偏移 (稀疏索引，数 GB 的日志也只占用很少内存)
+const INDEX_STRIDE: u64 = 1000;
+
+/// 单次读取的块大小
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// 单次分页最多返回的行数
+pub const MAX_PAGE_LINES: usize = 10_000;
+
+/// 单次按字节范围读取的上限
+pub const MAX_RANGE_BYTES: usize = 4 * 1024 * 1024;
+
+pub fn is_large(size: u64) -> bool {
+    size >= LARGE_FILE_THRESHOLD
+}
+
+/// 某个文件的行偏移索引 (后台逐步构建)
+pub struct LineIndex {
+    size: u64,
+    mtime: u64,
+    // checkpoints[k] 为第 k * INDEX_STRIDE 行 (从 0 开始) 的起始字节偏移
+    checkpoints: RwLock<Vec<u64>>,
+    lines: AtomicU64,
+    bytes: AtomicU64,
+    done: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LineIndexStatus {
+    pub path: String,
+    /// 已扫描到的换行数 (构建中仅供显示进度)
+    pub lines_indexed: u64,
+    pub bytes_indexed: u64,
+    pub size: u64,
+    /// 构建完成后才有总行数
+    pub total_lines: Option<u64>,
+    pub done: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LinesResponse {
+    pub start_line: u64,
+    pub lines: Vec<String>,
+    pub encoding: String,
+    pub total_lines: Option<u64>,
+    /// 超过 MAX_RANGE_BYTES 而被截断的行 (行号)
+    pub truncated_lines: Vec<u64>,
+    /// 已读到文件末尾
+    pub eof: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RangeResponse {
+    pub offset: u64,
+    pub length: u64,
+    pub size: u64,
+    pub content: String,
+    pub eof: bool,
+}
+
+impl LineIndex {
+    fn new(size: u64, mtime: u64) -> Self {
+        Self {
+            size,
+            mtime,
+            checkpoints: RwLock::new(vec![0]),
+            lines: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            done: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn status(&self, path: &str) -> LineIndexStatus {
+        let done = self.done.load(Ordering::Acquire);
+        let lines = self.lines.load(Ordering::Acquire);
+        LineIndexStatus {
+            path: path.to_string(),
+            lines_indexed: lines,
+            bytes_indexed: self.bytes.load(Ordering::Acquire),
+            size: self.size,
+            // 行数 = 换行数 + 1 (与编辑器行号一致)
+            total_lines: if done { Some(lines + 1) } else { None },
+            done,
+        }
+    }
+
+    /// 索引是否仍对应当前磁盘上的文件
+    pub fn matches(&self, size: u64, mtime: u64) -> bool {
+        self.size == size && self.mtime == mtime
+    }
+
+    /// 不超过 line 的最近检查点：(行号, 字节偏移)
+    fn checkpoint_for(&self, line: u64) -> (u64, u64) {
+        let checkpoints = self.checkpoints.read().unwrap();
+        let k = ((line / INDEX_STRIDE) as usize).min(checkpoints.len() - 1);
+        (k as u64 * INDEX_STRIDE, checkpoints[k])
+    }
+
+    /// 扫描整个文件，逐块记录检查点
+    pub async fn build(&self, fs: &dyn FileSystem, path: &str, on_progress: &(dyn Fn(LineIndexStatus) + Send + Sync)) -> Result<(), FsError> {
+        let mut reader = ChunkReader::new(fs, path);
+        let mut offset = 0u64;
+        let mut line = 0u64;
+        loop {
+            if self.cancelled.load(Ordering::Acquire) {
+                return Ok(());
+            }
+            let chunk = reader.read(offset, CHUNK_SIZE).await?;
+            let mut new_points = Vec::new();
+            for (i, b) in chunk.iter().enumerate() {
+                if *b == b'\n' {
+                    line += 1;
+                    if line % INDEX_STRIDE == 0 {
+                        new_points.push(offset + i as u64 + 1);
+                    }
+                }
+            }
+            offset += chunk.len() as u64;
+            self.checkpoints.write().unwrap().extend(new_points);
+            self.lines.store(line, Ordering::Release);
+            self.bytes.store(offset, Ordering::Release);
+
+            if chunk.len() < CHUNK_SIZE {
+                break;
+            }
+            // 约每 64MB 推送一次进度
+            if (offset / CHUNK_SIZE as u64) % 64 == 0 {
+                on_progress(self.status(path));
+            }
+        }
+        self.done.store(true, Ordering::Release);
+        on_progress(self.status(path));
+        Ok(())
+    }
+}
+
+/// 管理各文件的行索引，文件大小或修改时间变化后自动失效
+#[derive(Default)]
+pub struct LineIndexService {
+    indexes: Mutex<HashMap<String, Arc<LineIndex>>>,
+}
+
+impl LineIndexService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 获取现有索引，返回 (索引, 是否为新建)，新建的索引需要调用方启动构建
+    pub fn get_or_create(&self, path: &str, size: u64, mtime: u64) -> (Arc<LineIndex>, bool) {
+        let mut indexes = self.indexes.lock().unwrap();
+        if let Some(index) = indexes.get(path) {
+            if index.matches(size, mtime) {
+                return (index.clone(), false);
+            }
+            index.cancelled.store(true, Ordering::Release);
+        }
+        let index = Arc::new(LineIndex::new(size, mtime));
+        indexes.insert(path.to_string(), index.clone());
+        (index, true)
+    }
+
+    pub fn get(&self, path: &str) -> Option<Arc<LineIndex>> {
+        self.indexes.lock().unwrap().get(path).cloned()
+    }
+
+    pub fn remove(&self, path: &str) {
+        if let Some(index) = self.indexes.lock().unwrap().remove(path) {
+            index.cancelled.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// 逐块读取文件；不支持范围读取的实现只整体读入一次，避免每块都重新读取整个文件
+struct ChunkReader<'a> {
+    fs: &'a dyn FileSystem,
+    path: &'a str,
+    whole: Option<Vec<u8>>,
+}
+
+impl<'a> ChunkReader<'a> {
+    fn new(fs: &'a dyn FileSystem, path: &'a str) -> Self {
+        Self { fs, path, whole: None }
+    }
+
+    async fn read(&mut self, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        if self.fs.supports_range_reads(self.path) {
+            return self.fs.read_range(self.path, offset, length).await;
+        }
+        if self.whole.is_none() {
+            self.whole = Some(self.fs.read_bytes(self.path).await?);
+        }
+        let bytes = self.whole.as_deref().unwrap_or_default();
+        let start = (offset as usize).min(bytes.len());
+        let end = start.saturating_add(length).min(bytes.len());
+        Ok(bytes[start..end].to_vec())
+    }
+}
+
+/// 读取指定字节范围并解码 (范围边界可能截断多字节字符，按替换字符处理)
+/// length 超过 MAX_RANGE_BYTES 时按上限截断，返回的 length 为实际读取的字节数
+pub async fn read_range(fs: &dyn FileSystem, path: &str, offset: u64, length: usize, label: Option<&str>) -> Result<RangeResponse, FsError> {
+    let size = fs.stat(path).await?.size;
+    let mut reader = ChunkReader::new(fs, path);
+    let bytes = reader.read(offset, length.min(MAX_RANGE_BYTES)).await?;
+    let enc = resolve(&mut reader, label).await?;
+    let (content, _) = enc.decode_without_bom_handling(&bytes);
+    Ok(RangeResponse {
+        offset,
+        length: bytes.len() as u64,
+        size,
+        content: content.into_owned(),
+        eof: offset + bytes.len() as u64 >= size,
+    })
+}
+
+/// 读取 [start_line, start_line + count) 行 (行号从 0 开始)，借助索引从最近的检查点开始扫描
+/// 单行与整页都最多保留 MAX_RANGE_BYTES：超长的行截断 (见 truncated_lines)，整页超出时提前结束，返回的行数可能少于 count
+pub async fn read_lines(fs: &dyn FileSystem, index: Option<&LineIndex>, path: &str, start_line: u64, count: usize, label: Option<&str>) -> Result<LinesResponse, FsError> {
+    let mut reader = ChunkReader::new(fs, path);
+    let enc = resolve(&mut reader, label).await?;
+    if enc == encoding_rs::UTF_16LE || enc == encoding_rs::UTF_16BE {
+        return Err(format!("Line paging is not supported for {}", enc.name()).into());
+    }
+    let count = count.min(MAX_PAGE_LINES);
+    let (mut line, mut offset) = index.map(|i| i.checkpoint_for(start_line)).unwrap_or((0, 0));
+
+    let mut raw_lines: Vec<Vec<u8>> = Vec::with_capacity(count);
+    let mut truncated_lines = Vec::new();
+    let mut pending = LineBuffer::default();
+    let mut page_bytes = 0;
+    let mut eof = false;
+    let full = |lines: &Vec<Vec<u8>>, bytes: usize| lines.len() >= count || bytes >= MAX_RANGE_BYTES;
+    while !full(&raw_lines, page_bytes) {
+        let chunk = reader.read(offset, CHUNK_SIZE).await?;
+        offset += chunk.len() as u64;
+        let mut line_begin = 0;
+        for (i, b) in chunk.iter().enumerate() {
+            if *b != b'\n' {
+                continue;
+            }
+            if line >= start_line {
+                pending.extend(&chunk[line_begin..i]);
+                let (raw, truncated) = pending.take();
+                if truncated {
+                    truncated_lines.push(line);
+                }
+                page_bytes += raw.len();
+                raw_lines.push(raw);
+                if full(&raw_lines, page_bytes) {
+                    break;
+                }
+            }
+            line += 1;
+            line_begin = i + 1;
+        }
+        if full(&raw_lines, page_bytes) {
+            break;
+        }
+        if line >= start_line {
+            pending.extend(&chunk[line_begin..]);
+        }
+        if chunk.len() < CHUNK_SIZE {
+            // 最后一行没有换行符
+            if line >= start_line {
+                let (raw, truncated) = pending.take();
+                if truncated {
+                    truncated_lines.push(line);
+                }
+                raw_lines.push(raw);
+            }
+            eof = true;
+            break;
+        }
+    }
+
+    let lines = raw_lines.iter().enumerate().map(|(i, raw)| {
+        let mut bytes = raw.as_slice();
+        if bytes.last() == Some(&b'\r') {
+            bytes = &bytes[..bytes.len() - 1];
+        }
+        // 跳过首行的 BOM
+        if start_line == 0 && i == 0 && bytes.starts_with(b"\xEF\xBB\xBF") {
+            bytes = &bytes[3..];
+        }
+        enc.decode_without_bom_handling(bytes).0.into_owned()
+    }).collect();
+
+    Ok(LinesResponse {
+        start_line,
+        lines,
+        encoding: enc.name().to_string(),
+        total_lines: index.and_then(|i| i.status(path).total_lines),
+        truncated_lines,
+        eof,
+    })
+}
+
+// 正在读取的一行，超过 MAX_RANGE_BYTES 的部分直接丢弃 (单行数 GB 的压缩 JSON、日志等不会整行载入内存)
+#[derive(Default)]
+struct LineBuffer {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl LineBuffer {
+    fn extend(&mut self, data: &[u8]) {
+        let room = MAX_RANGE_BYTES - self.bytes.len();
+        if data.len() > room {
+            self.truncated = true;
+        }
+        self.bytes.extend_from_slice(&data[..data.len().min(room)]);
+    }
+
+    fn take(&mut self) -> (Vec<u8>, bool) {
+        let truncated = std::mem::take(&mut self.truncated);
+        (std::mem::take(&mut self.bytes), truncated)
+    }
+}
+
+/// 未指定编码时按文件开头的内容检测
+async fn resolve(reader: &mut ChunkReader<'_>, label: Option<&str>) -> Result<&'static Encoding, FsError> {
+    let label = match label {
+        Some(l) => l.to_string(),
+        None => {
+            let head = reader.read(0, 64 * 1024).await?;
+            encoding::decode(&head).encoding
+        }
+    };
+    encoding::resolve_encoding(&label).ok_or(FsError::UnsupportedEncoding { encoding: label })
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use async_trait::async_trait;
+use crate::models::{FileAttributes, FileItem};
+use crate::services::largefile;
+use crate::services::vfs::{normalize_path, FileStat, FileSystem, FsError};
+
+/// 内存中的节点
+#[derive(Clone)]
+enum MemNode {
+    Dir { mtime: u64 },
+    File { data: Vec<u8>, mtime: u64 },
+}
+
+/// 纯内存文件系统实现
+/// 用于测试以及永不落盘的 `mem:` 草稿文档 (见 services::mount)
+pub struct MemoryFileSystem {
+    root: Mutex<PathBuf>,
+    nodes: RwLock<BTreeMap<PathBuf, MemNode>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new(root: PathBuf) -> Self {
+        let fs = Self {
+            root: Mutex::new(PathBuf::new()),
+            nodes: RwLock::new(BTreeMap::new()),
+        };
+        let root = normalize_path(&root);
+        fs.ensure_dir_all(&root);
+        *fs.root.lock().unwrap() = root;
+        fs
+    }
+
+    /// 直接写入一个文件 (自动创建父目录)，便于测试预置数据
+    pub fn insert_file(&self, path: &str, data: impl Into<Vec<u8>>) {
+        let p = self.resolve(path);
+        if let Some(parent) = p.parent() {
+            self.ensure_dir_all(parent);
+        }
+        self.nodes.write().unwrap().insert(p, MemNode::File { data: data.into(), mtime: now_secs() });
+    }
+
+    fn resolve(&self, target: &str) -> PathBuf {
+        let root = self.root.lock().unwrap();
+        let target_path = if Path::new(target).is_absolute() || target.starts_with('/') {
+            PathBuf::from(target)
+        } else {
+            root.join(target)
+        };
+        normalize_path(&target_path)
+    }
+
+    fn ensure_dir_all(&self, path: &Path) {
+        let mut nodes = self.nodes.write().unwrap();
+        for ancestor in path.ancestors() {
+            if ancestor.as_os_str().is_empty() { continue; }
+            nodes.entry(ancestor.to_path_buf()).or_insert(MemNode::Dir { mtime: now_secs() });
+        }
+    }
+
+    fn check_parent(nodes: &BTreeMap<PathBuf, MemNode>, path: &Path) -> Result<(), FsError> {
+        match path.parent() {
+            None => Ok(()),
+            Some(parent) if parent.as_os_str().is_empty() => Ok(()),
+            Some(parent) => match nodes.get(parent) {
+                Some(MemNode::Dir { .. }) => Ok(()),
+                Some(MemNode::File { .. }) => Err(format!("Not a directory: {}", display(parent)).into()),
+                None => Err(format!("No such file or directory: {}", display(parent)).into()),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl FileSystem for MemoryFileSystem {
+    fn get_cwd(&self) -> String {
+        display(&self.root.lock().unwrap())
+    }
+
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> {
+        let p = normalize_path(Path::new(path));
+        self.ensure_dir_all(&p);
+        *self.root.lock().unwrap() = p;
+        Ok(())
+    }
+
+    /// 内存中的临时文档不涉及磁盘与远程主机，插件可以读写
+    fn allows_restricted_callers(&self, _path: &str) -> bool {
+        true
+    }
+
+    fn absolute_path(&self, path: &str) -> String {
+        display(&self.resolve(path))
+    }
+
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> {
+        let dir = self.resolve(path);
+        let nodes = self.nodes.read().unwrap();
+        match nodes.get(&dir) {
+            Some(MemNode::Dir { .. }) => {}
+            Some(MemNode::File { .. }) => return Err(format!("Not a directory: {}", display(&dir)).into()),
+            None => return Err(format!("No such file or directory: {}", display(&dir)).into()),
+        }
+
+        let mut items: Vec<FileItem> = nodes.iter()
+            .filter(|(p, _)| p.parent() == Some(dir.as_path()))
+            .map(|(p, node)| {
+                let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                FileItem {
+                    attrs: FileAttributes::from_name(&name),
+                    name,
+                    path: display(p),
+                    is_dir: matches!(node, MemNode::Dir { .. }),
+                }
+            })
+            .collect();
+
+        items.sort_by(|a, b| {
+            if a.is_dir != b.is_dir { b.is_dir.cmp(&a.is_dir) }
+            else { a.name.cmp(&b.name) }
+        });
+        Ok(items)
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        let p = self.resolve(path);
+        match self.nodes.read().unwrap().get(&p) {
+            Some(MemNode::File { data, .. }) => Ok(data.clone()),
+            Some(MemNode::Dir { .. }) => Err(format!("Is a directory: {}", display(&p)).into()),
+            None => Err(format!("No such file or directory: {}", display(&p)).into()),
+        }
+    }
+
+    fn supports_range_reads(&self, _path: &str) -> bool {
+        true
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        let p = self.resolve(path);
+        match self.nodes.read().unwrap().get(&p) {
+            Some(MemNode::File { data, .. }) => {
+                let start = (offset as usize).min(data.len());
+                let end = start.saturating_add(length).min(data.len());
+                Ok(data[start..end].to_vec())
+            }
+            Some(MemNode::Dir { .. }) => Err(format!("Is a directory: {}", display(&p)).into()),
+            None => Err(format!("No such file or directory: {}", display(&p)).into()),
+        }
+    }
+
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        let p = self.resolve(path);
+        let mut nodes = self.nodes.write().unwrap();
+        Self::check_parent(&nodes, &p)?;
+        if let Some(MemNode::Dir { .. }) = nodes.get(&p) {
+            return Err(format!("Is a directory: {}", display(&p)).into());
+        }
+        nodes.insert(p, MemNode::File { data: data.to_vec(), mtime: now_secs() });
+        Ok(())
+    }
+
+    async fn create_file(&self, path: &str) -> Result<(), FsError> {
+        self.write_bytes(path, &[]).await
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        let p = self.resolve(path);
+        if let Some(MemNode::File { .. }) = self.nodes.read().unwrap().get(&p) {
+            return Err(format!("File exists: {}", display(&p)).into());
+        }
+        self.ensure_dir_all(&p);
+        Ok(())
+    }
+
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> {
+        let p = self.resolve(path);
+        let mut nodes = self.nodes.write().unwrap();
+        if !nodes.contains_key(&p) {
+            return Err(format!("No such file or directory: {}", display(&p)).into());
+        }
+        nodes.retain(|k, _| !k.starts_with(&p));
+        Ok(())
+    }
+
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        let src = self.resolve(from);
+        let dst = self.resolve(to);
+        if src == dst { return Ok(()); }
+
+        let mut nodes = self.nodes.write().unwrap();
+        if !nodes.contains_key(&src) {
+            return Err(format!("No such file or directory: {}", display(&src)).into());
+        }
+        if dst.starts_with(&src) {
+            return Err(format!("Cannot move a directory into itself: {}", display(&dst)).into());
+        }
+        Self::check_parent(&nodes, &dst)?;
+
+        // 与本地 (POSIX) 重命名语义一致：文件覆盖文件、目录覆盖空目录，其余情况报错
+        match (nodes.get(&src), nodes.get(&dst)) {
+            (Some(MemNode::File { .. }), Some(MemNode::Dir { .. })) => {
+                return Err(format!("Is a directory: {}", display(&dst)).into());
+            }
+            (Some(MemNode::Dir { .. }), Some(MemNode::File { .. })) => {
+                return Err(format!("Not a directory: {}", display(&dst)).into());
+            }
+            (Some(MemNode::Dir { .. }), Some(MemNode::Dir { .. })) if nodes.keys().any(|k| k.parent() == Some(dst.as_path())) => {
+                return Err(format!("Directory not empty: {}", display(&dst)).into());
+            }
+            _ => {}
+        }
+        nodes.remove(&dst);
+        let moved: Vec<PathBuf> = nodes.keys().filter(|k| k.starts_with(&src)).cloned().collect();
+        for old in moved {
+            if let Some(node) = nodes.remove(&old) {
+                let suffix = old.strip_prefix(&src).unwrap_or(Path::new(""));
+                let new_path = if suffix.as_os_str().is_empty() { dst.clone() } else { dst.join(suffix) };
+                nodes.insert(new_path, node);
+            }
+        }
+        Ok(())
+    }
+
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> {
+        let p = self.resolve(path);
+        let attrs = FileAttributes::from_name(&p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+        match self.nodes.read().unwrap().get(&p) {
+            Some(MemNode::Dir { mtime }) => Ok(FileStat { file_type: "dir".to_string(), size: 0, mtime: *mtime, is_large: false, attrs }),
+            Some(MemNode::File { data, mtime }) => Ok(FileStat { file_type: "file".to_string(), size: data.len() as u64, mtime: *mtime, is_large: largefile::is_large(data.len() as u64), attrs }),
+            None => Err(format!("No such file or directory: {}", display(&p)).into()),
+        }
+    }
+}
+
+fn display(p: &Path) -> String {
+    p.to_string_lossy().to_string().replace("\\", "/")
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
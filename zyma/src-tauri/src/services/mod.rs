@@ -1,5 +1,30 @@
 pub mod vfs;
+pub mod encoding;
+pub mod atomic;
+pub mod version;
+pub mod trash;
+pub mod transfer;
+pub mod sandbox;
+pub mod workspace;
+pub mod largefile;
+pub mod binary;
+pub mod permissions;
+pub mod history;
+pub mod backup;
+pub mod session;
+pub mod settings;
+pub mod config_file;
+pub mod settings_store;
+pub mod secrets;
+pub mod profiles;
+pub mod paths;
+pub mod diff;
+pub mod archive;
+pub mod mount;
+pub mod sftp;
+pub mod memfs;
 pub mod context;
 
 pub use vfs::{FileSystem, LocalFileSystem};
+pub use memfs::MemoryFileSystem;
 pub use context::ContextService;
\ No newline at end of file
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use async_trait::async_trait;
+use crate::models::{AppSettings, FileItem, WorkspaceFolder};
+use crate::services::sandbox::{Access, SandboxMode};
+use crate::services::vfs::{FileStat, FileSystem, FsError};
+use crate::services::MemoryFileSystem;
+
+/// 默认文件系统 (本地磁盘) 的 scheme，`file:/a/b` 与 `/a/b` 等价
+pub const FILE_SCHEME: &str = "file";
+
+/// 内置的内存文件系统 scheme，用于生成的视图等临时文档
+pub const MEM_SCHEME: &str = "mem";
+
+/// 不能被注册的 scheme (zip: 由挂载表外层的 ArchiveFs 处理)
+const RESERVED_SCHEMES: &[&str] = &[FILE_SCHEME, "zip"];
+
+pub type SharedFs = Arc<dyn FileSystem + Send + Sync>;
+
+/// 提取路径形式上的 scheme (`mem:/a` -> `mem`)
+/// 单个字母视为 Windows 盘符 (`C:/a`)，不当作 scheme；是否真正分发由挂载表按已注册的 scheme 决定
+pub fn scheme_of(path: &str) -> Option<&str> {
+    let end = path.find(':')?;
+    let scheme = &path[..end];
+    let valid = scheme.len() > 1
+        && scheme.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if valid { Some(scheme) } else { None }
+}
+
+/// 按 URI scheme 把路径分发给对应的文件系统实现
+/// 不带 scheme (或 `file:`) 的路径交给默认文件系统；其它 scheme 的实现收到的是去掉前缀后的路径，
+/// 返回的路径 (如 read_dir 的结果) 会重新加上前缀
+pub struct MountTable {
+    default: SharedFs,
+    mounts: RwLock<HashMap<String, SharedFs>>,
+}
+
+impl MountTable {
+    pub fn new(default: Box<dyn FileSystem + Send + Sync>) -> Self {
+        let table = Self { default: Arc::from(default), mounts: RwLock::new(HashMap::new()) };
+        let _ = table.mount(MEM_SCHEME, Arc::new(MemoryFileSystem::new(PathBuf::from("/"))));
+        table
+    }
+
+    /// 注册 (或替换) 某个 scheme 的实现
+    pub fn mount(&self, scheme: &str, fs: SharedFs) -> Result<(), FsError> {
+        let scheme = scheme.trim_end_matches(':').to_lowercase();
+        if RESERVED_SCHEMES.contains(&scheme.as_str()) || scheme_of(&format!("{}:", scheme)).is_none() {
+            return Err(format!("Invalid or reserved scheme: {}", scheme).into());
+        }
+        self.mounts.write().unwrap().insert(scheme, fs);
+        Ok(())
+    }
+
+    pub fn unmount(&self, scheme: &str) -> bool {
+        let scheme = scheme.trim_end_matches(':').to_lowercase();
+        self.mounts.write().unwrap().remove(&scheme).is_some()
+    }
+
+    /// 已注册的 scheme (不含默认的 file)
+    pub fn schemes(&self) -> Vec<String> {
+        let mut schemes: Vec<String> = self.mounts.read().unwrap().keys().cloned().collect();
+        schemes.sort();
+        schemes
+    }
+
+    /// 返回 (实现, 实现内部的路径, 需要加回的前缀)
+    /// 未注册的 "scheme" 视为普通的相对路径 (如文件名 `notes:v2.md`)
+    fn route<'p>(&self, path: &'p str) -> (SharedFs, &'p str, Option<String>) {
+        let scheme = match scheme_of(path) {
+            Some(s) => s,
+            None => return (self.default.clone(), path, None),
+        };
+        let rest = &path[scheme.len() + 1..];
+        let lower = scheme.to_lowercase();
+        if lower == FILE_SCHEME {
+            // file:///a/b 与 file:/a/b 均可
+            let rest = rest.strip_prefix("//").unwrap_or(rest);
+            return (self.default.clone(), rest, None);
+        }
+        match self.mounts.read().unwrap().get(&lower) {
+            Some(fs) => (fs.clone(), rest, Some(format!("{}:", scheme))),
+            None => (self.default.clone(), path, None),
+        }
+    }
+
+    /// 路径所属的已注册 scheme，默认文件系统为空串
+    fn provider_key(&self, path: &str) -> String {
+        match scheme_of(path).map(|s| s.to_lowercase()) {
+            Some(s) if s != FILE_SCHEME && self.mounts.read().unwrap().contains_key(&s) => s,
+            _ => String::new(),
+        }
+    }
+
+    fn same_provider(&self, a: &str, b: &str) -> bool {
+        self.provider_key(a) == self.provider_key(b)
+    }
+}
+
+fn prefixed(prefix: &Option<String>, path: String) -> String {
+    match prefix {
+        Some(p) => format!("{}{}", p, path),
+        None => path,
+    }
+}
+
+#[async_trait]
+impl FileSystem for MountTable {
+    fn get_cwd(&self) -> String {
+        self.default.get_cwd()
+    }
+
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> {
+        let (fs, p, prefix) = self.route(path);
+        if prefix.is_some() {
+            return Err(format!("Cannot open a virtual path as workspace: {}", path).into());
+        }
+        fs.set_cwd(p)
+    }
+
+    fn roots(&self) -> Vec<WorkspaceFolder> {
+        self.default.roots()
+    }
+
+    fn set_roots(&self, roots: Vec<WorkspaceFolder>) -> Result<(), FsError> {
+        self.default.set_roots(roots)
+    }
+
+    fn sandbox_mode(&self) -> SandboxMode {
+        self.default.sandbox_mode()
+    }
+
+    /// 虚拟文档的访问控制由各实现自行决定 (默认不限制)
+    fn check_access(&self, path: &str, access: Access, mode: SandboxMode) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.check_access(p, access, mode)
+    }
+
+    /// 挂载在其它 scheme 下的实现 (远程主机等) 默认只对用户开放，需由实现显式开放
+    fn allows_restricted_callers(&self, path: &str) -> bool {
+        let (fs, p, _) = self.route(path);
+        fs.allows_restricted_callers(p)
+    }
+
+    /// `file:/a/b` 与 `/a/b` 得到相同结果
+    fn absolute_path(&self, path: &str) -> String {
+        let (fs, p, prefix) = self.route(path);
+        prefixed(&prefix, fs.absolute_path(p))
+    }
+
+    fn allow_path(&self, path: &str) {
+        let (fs, p, _) = self.route(path);
+        fs.allow_path(p);
+    }
+
+    fn apply_settings(&self, settings: &AppSettings) {
+        self.default.apply_settings(settings);
+        for fs in self.mounts.read().unwrap().values() {
+            fs.apply_settings(settings);
+        }
+    }
+
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> {
+        let (fs, p, prefix) = self.route(path);
+        let mut items = fs.read_dir(p).await?;
+        for item in items.iter_mut() {
+            item.path = prefixed(&prefix, std::mem::take(&mut item.path));
+        }
+        Ok(items)
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.read_bytes(p).await
+    }
+
+    fn supports_range_reads(&self, path: &str) -> bool {
+        let (fs, p, _) = self.route(path);
+        fs.supports_range_reads(p)
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.read_range(p, offset, length).await
+    }
+
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.write_bytes(p, data).await
+    }
+
+    async fn write_range(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.write_range(p, offset, data).await
+    }
+
+    async fn create_file(&self, path: &str) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.create_file(p).await
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.create_dir(p).await
+    }
+
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.remove_item(p).await
+    }
+
+    async fn trash_item(&self, path: &str) -> Result<bool, FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.trash_item(p).await
+    }
+
+    /// 跨实现的重命名不可能原子完成，返回 CrossDevice (move_item 会退化为复制 + 删除)
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        if !self.same_provider(from, to) {
+            return Err(FsError::CrossDevice { from: from.to_string(), to: to.to_string() });
+        }
+        let (fs, f, _) = self.route(from);
+        let (_, t, _) = self.route(to);
+        fs.rename_item(f, t).await
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), FsError> {
+        if self.same_provider(from, to) {
+            let (fs, f, _) = self.route(from);
+            let (_, t, _) = self.route(to);
+            return fs.copy_file(f, t).await;
+        }
+        let bytes = self.read_bytes(from).await?;
+        self.write_bytes(to, &bytes).await
+    }
+
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.stat(p).await
+    }
+
+    async fn read_link(&self, path: &str) -> Result<String, FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.read_link(p).await
+    }
+
+    async fn real_path(&self, path: &str) -> Result<String, FsError> {
+        let (fs, p, prefix) = self.route(path);
+        Ok(prefixed(&prefix, fs.real_path(p).await?))
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.set_mode(p, mode).await
+    }
+
+    async fn set_readonly(&self, path: &str, readonly: bool) -> Result<(), FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.set_readonly(p, readonly).await
+    }
+
+    async fn is_writable(&self, path: &str) -> Result<bool, FsError> {
+        let (fs, p, _) = self.route(path);
+        fs.is_writable(p).await
+    }
+}
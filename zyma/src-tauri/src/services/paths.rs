@@ -0,0 +1,224 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use serde::Serialize;
+
+/// 便携模式：可执行文件旁存在该目录时，所有数据都放在其中
+pub const PORTABLE_DIR: &str = "zyma-data";
+/// 命令行参数，为独立实例指定数据目录 (`--user-data-dir <dir>` 或 `--user-data-dir=<dir>`)
+pub const USER_DATA_DIR_ARG: &str = "--user-data-dir";
+
+const APP_DIR: &str = "zyma";
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DirsMode {
+    /// 平台约定的目录 (Linux 为 XDG 目录)
+    Standard,
+    Portable,
+    /// 由 --user-data-dir 指定
+    Custom,
+}
+
+/// 用户配置、数据与缓存目录
+/// Linux: `$XDG_CONFIG_HOME/zyma`、`$XDG_DATA_HOME/zyma`、`$XDG_CACHE_HOME/zyma`
+/// macOS: `~/Library/Application Support/zyma`、`~/Library/Caches/zyma`
+/// Windows: `%APPDATA%\zyma`、`%LOCALAPPDATA%\zyma\cache`
+/// 便携模式与 --user-data-dir 下为同一目录中的 config / data / cache 子目录
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AppDirs {
+    pub mode: DirsMode,
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub cache: PathBuf,
+}
+
+impl AppDirs {
+    fn under(root: &Path, mode: DirsMode) -> Self {
+        Self { mode, config: root.join("config"), data: root.join("data"), cache: root.join("cache") }
+    }
+
+    /// 用户设置文件
+    pub fn settings_file(&self) -> PathBuf {
+        self.config.join(SETTINGS_FILE)
+    }
+
+    /// 数据目录下的文件或子目录 (plugins、history、backups、sessions、trash.json、secrets.vault)
+    pub fn data_path(&self, name: &str) -> PathBuf {
+        self.data.join(name)
+    }
+
+    /// 是否与默认实例隔离 (不参与单实例转发)
+    pub fn is_isolated(&self) -> bool {
+        self.mode == DirsMode::Custom
+    }
+
+    /// 按命令行参数、便携目录与平台约定依次确定目录
+    /// env 读取环境变量，便于在测试中替换；找不到主目录时报错，而不是把配置与密钥放进共享的临时目录
+    pub fn resolve<F>(args: &[String], exe_dir: Option<&Path>, env: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(dir) = user_data_dir_arg(args) {
+            return Ok(Self::under(&dir, DirsMode::Custom));
+        }
+        if let Some(portable) = exe_dir.map(|d| d.join(PORTABLE_DIR)).filter(|d| d.is_dir()) {
+            return Ok(Self::under(&portable, DirsMode::Portable));
+        }
+        standard_dirs(&env)
+    }
+}
+
+/// 解析 --user-data-dir，相对路径以当前目录为基准
+pub fn user_data_dir_arg(args: &[String]) -> Option<PathBuf> {
+    let prefix = format!("{}=", USER_DATA_DIR_ARG);
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == USER_DATA_DIR_ARG {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(&prefix).map(|v| v.to_string())
+        }
+    })?;
+    if value.is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(value);
+    Some(if path.is_absolute() { path } else { std::env::current_dir().unwrap_or_default().join(path) })
+}
+
+// XDG 规范要求忽略相对路径
+fn absolute_var<F: Fn(&str) -> Option<String>>(env: &F, name: &str) -> Option<PathBuf> {
+    env(name).filter(|v| !v.is_empty()).map(PathBuf::from).filter(|p| p.is_absolute())
+}
+
+fn home<F: Fn(&str) -> Option<String>>(env: &F) -> Option<PathBuf> {
+    absolute_var(env, "HOME").or_else(|| absolute_var(env, "USERPROFILE"))
+}
+
+fn standard_dirs<F: Fn(&str) -> Option<String>>(env: &F) -> Result<AppDirs, String> {
+    let home = home(env);
+    let under_home = |parts: &[&str]| -> Result<PathBuf, String> {
+        match &home {
+            Some(home) => Ok(parts.iter().fold(home.clone(), |p, c| p.join(c))),
+            None => Err(format!("Cannot determine the home directory: set HOME or start with {} <dir>", USER_DATA_DIR_ARG)),
+        }
+    };
+    let base = |var: &str, parts: &[&str]| match absolute_var(env, var) {
+        Some(dir) => Ok(dir),
+        None => under_home(parts),
+    };
+    Ok(if cfg!(windows) {
+        let roaming = base("APPDATA", &["AppData", "Roaming"])?.join(APP_DIR);
+        let local = base("LOCALAPPDATA", &["AppData", "Local"])?.join(APP_DIR);
+        AppDirs { mode: DirsMode::Standard, config: roaming.clone(), data: roaming, cache: local.join("cache") }
+    } else if cfg!(target_os = "macos") {
+        let support = under_home(&["Library", "Application Support"])?.join(APP_DIR);
+        AppDirs { mode: DirsMode::Standard, config: support.clone(), data: support, cache: under_home(&["Library", "Caches"])?.join(APP_DIR) }
+    } else {
+        AppDirs {
+            mode: DirsMode::Standard,
+            config: base("XDG_CONFIG_HOME", &[".config"])?.join(APP_DIR),
+            data: base("XDG_DATA_HOME", &[".local", "share"])?.join(APP_DIR),
+            cache: base("XDG_CACHE_HOME", &[".cache"])?.join(APP_DIR),
+        }
+    })
+}
+
+/// 进程环境变量；Unix 上没有 HOME 时 (如以服务账号运行) 取 passwd 中登记的主目录
+fn process_env(name: &str) -> Option<String> {
+    let value = std::env::var(name).ok();
+    #[cfg(unix)]
+    if value.is_none() && name == "HOME" {
+        return passwd_home();
+    }
+    value
+}
+
+#[cfg(unix)]
+fn passwd_home() -> Option<String> {
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 16 * 1024];
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let rc = unsafe { libc::getpwuid_r(libc::getuid(), &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() || pwd.pw_dir.is_null() {
+        return None;
+    }
+    let dir = unsafe { std::ffi::CStr::from_ptr(pwd.pw_dir) };
+    Some(dir.to_string_lossy().to_string())
+}
+
+static DIRS: OnceLock<AppDirs> = OnceLock::new();
+
+/// 当前进程使用的目录，首次调用时按命令行参数与环境确定
+/// 无法确定时直接终止：配置与密钥不能落到其他用户也可写入的位置
+pub fn dirs() -> &'static AppDirs {
+    DIRS.get_or_init(|| {
+        let args: Vec<String> = std::env::args().collect();
+        let exe_dir = std::env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf()));
+        match AppDirs::resolve(&args, exe_dir.as_deref(), process_env) {
+            Ok(dirs) => dirs,
+            Err(e) => panic!("{}", e),
+        }
+    })
+}
+
+/// 旧版布局：`~/.zyma_config.json` 与 `~/.zyma/` 下的数据
+pub fn legacy_home() -> Option<PathBuf> {
+    home(&process_env)
+}
+
+/// 当前用户的主目录 (回收站、~/.ssh 等)；无法确定时返回错误，不退回当前目录或临时目录
+pub fn home_dir() -> Result<PathBuf, String> {
+    home(&process_env).ok_or_else(|| "Cannot determine the home directory: set HOME".to_string())
+}
+
+const LEGACY_DATA: &[&str] = &["plugins", "history", "backups", "sessions", "trash.json", "secrets.vault"];
+
+/// 把旧版布局中的文件一次性移到新目录，目标已存在的条目保留原处不动。返回已迁移的新路径
+pub fn migrate_legacy(home: &Path, dirs: &AppDirs) -> Vec<PathBuf> {
+    let mut moves = vec![
+        (home.join(".zyma_config.json"), dirs.settings_file()),
+        (home.join(".zyma_config.json.corrupt"), dirs.config.join(format!("{}.corrupt", SETTINGS_FILE))),
+    ];
+    let legacy_dir = home.join(".zyma");
+    moves.extend(LEGACY_DATA.iter().map(|name| (legacy_dir.join(name), dirs.data_path(name))));
+
+    let mut migrated = Vec::new();
+    for (from, to) in moves {
+        if !from.exists() || to.exists() {
+            continue;
+        }
+        match move_path(&from, &to) {
+            Ok(()) => migrated.push(to),
+            Err(e) => log::warn!("Failed to migrate {} to {}: {}", from.display(), to.display(), e),
+        }
+    }
+    // 全部移走后删除空的旧目录
+    let _ = std::fs::remove_dir(&legacy_dir);
+    migrated
+}
+
+fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(dir) = to.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    // 跨文件系统时复制后删除
+    copy_recursive(from, to)?;
+    if from.is_dir() { std::fs::remove_dir_all(from) } else { std::fs::remove_file(from) }
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        std::fs::copy(from, to).map(|_| ())
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use crate::services::vfs::FsError;
+
+/// 解析 chmod 风格的权限描述，返回新的权限位
+/// 支持八进制 ("755"、"0644") 与符号形式 ("+x"、"u+x,go-w"、"a=r")；符号形式基于 current 修改
+pub fn apply_mode_spec(current: u32, spec: &str) -> Result<u32, FsError> {
+    let spec = spec.trim();
+    let invalid = || FsError::from(format!("Invalid mode: {}", spec));
+    if !spec.is_empty() && spec.chars().all(|c| c.is_digit(8)) {
+        let mode = u32::from_str_radix(spec, 8).map_err(|_| invalid())?;
+        return if mode <= 0o7777 { Ok(mode) } else { Err(invalid()) };
+    }
+
+    let mut mode = current & 0o7777;
+    for clause in spec.split(',') {
+        let op_at = clause.find(['+', '-', '=']).ok_or_else(invalid)?;
+        let (who, rest) = clause.split_at(op_at);
+        // 未指定对象时作用于全部 (chmod 还会考虑 umask，这里从简)
+        let mut mask = 0;
+        for c in who.chars() {
+            mask |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => return Err(invalid()),
+            };
+        }
+        if mask == 0 {
+            mask = 0o7777;
+        }
+        let op = rest.chars().next().ok_or_else(invalid)?;
+        let mut bits = 0;
+        for c in rest[1..].chars() {
+            bits |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                's' => 0o6000,
+                't' => 0o1000,
+                _ => return Err(invalid()),
+            };
+        }
+        let bits = bits & mask;
+        mode = match op {
+            '+' => mode | bits,
+            '-' => mode & !bits,
+            // "=" 同时清除对象的特殊位 (setuid/setgid/sticky)，与 chmod 一致
+            _ => (mode & !mask) | bits,
+        };
+    }
+    Ok(mode)
+}
+
+/// 当前进程能否写入该路径；路径不存在时检查能否在父目录中创建
+#[cfg(unix)]
+pub fn is_writable(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    let (target, flags) = if path.exists() {
+        (path, libc::W_OK)
+    } else {
+        (path.parent().unwrap_or(Path::new("/")), libc::W_OK | libc::X_OK)
+    };
+    match CString::new(target.as_os_str().as_bytes()) {
+        // access 按真实 uid 检查，同时考虑 ACL 与只读挂载
+        Ok(c) => unsafe { libc::access(c.as_ptr(), flags) == 0 },
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_writable(path: &Path) -> bool {
+    match std::fs::metadata(path) {
+        Ok(metadata) => !metadata.permissions().readonly(),
+        Err(_) => path.parent().and_then(|p| std::fs::metadata(p).ok()).map(|m| !m.permissions().readonly()).unwrap_or(false),
+    }
+}
+
+/// 提权写入前的备份：(备份路径, 复制完成后需要清理的旧备份)，见 atomic::plan_backup
+pub type ElevatedBackup = (PathBuf, Vec<PathBuf>);
+
+/// 复制备份并清理旧备份后再由 tee 写入；目标尚不存在时跳过备份
+#[cfg(unix)]
+const BACKUP_AND_TEE: &str = r#"target=$1; backup=$2; shift 2
+if [ -e "$target" ]; then cp -p -- "$target" "$backup" || exit 1; rm -f -- "$@"; fi
+exec tee -- "$target""#;
+
+/// 通过提权的辅助进程写入文件 ("以管理员身份保存")
+/// 依次尝试 pkexec 与 `sudo -n`，由 `tee` 原地写入，因此保留原文件的属主与权限
+/// 需要备份时在同一次提权中先复制原文件，只需认证一次
+#[cfg(unix)]
+pub async fn write_elevated(path: &Path, data: &[u8], backup: Option<ElevatedBackup>) -> Result<(), FsError> {
+    use std::ffi::OsString;
+    use std::process::Stdio;
+    use tokio::io::AsyncWriteExt;
+    use tokio::process::Command;
+    let args: Vec<OsString> = match backup {
+        Some((backup, stale)) => {
+            let mut args: Vec<OsString> = vec!["sh".into(), "-c".into(), BACKUP_AND_TEE.into(), "zyma-save".into(), path.into(), backup.into()];
+            args.extend(stale.into_iter().map(OsString::from));
+            args
+        }
+        None => vec!["tee".into(), "--".into(), path.into()],
+    };
+    let helpers: &[&[&str]] = &[&["pkexec"], &["sudo", "-n"]];
+    let mut last_error = String::from("No privilege escalation helper (pkexec or sudo) found");
+    for helper in helpers {
+        let mut child = match Command::new(helper[0])
+            .args(&helper[1..])
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            // 认证被取消时子进程提前退出，写入会失败，以退出码为准
+            let _ = stdin.write_all(data).await;
+        }
+        let output = child.wait_with_output().await?;
+        if output.status.success() {
+            return Ok(());
+        }
+        last_error = match (helper[0], output.status.code()) {
+            ("pkexec", Some(126)) => return Err("Authentication was cancelled".to_string().into()),
+            ("pkexec", Some(127)) => return Err("Not authorized to write as administrator".to_string().into()),
+            _ => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        };
+    }
+    Err(last_error.into())
+}
+
+#[cfg(not(unix))]
+pub async fn write_elevated(_path: &Path, _data: &[u8], _backup: Option<ElevatedBackup>) -> Result<(), FsError> {
+    Err("Saving as administrator is not supported on this platform".to_string().into())
+}
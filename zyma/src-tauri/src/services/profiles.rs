@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::services::config_file;
+use crate::services::settings::check_value;
+
+/// 配置档案保存的键 (外观、AI 服务商与模型、插件启用状态)，其余设置不随档案切换
+/// 档案可以导入，因此不包含 ai_base_url：否则导入的档案可以把密钥发往任意地址
+pub const PROFILE_KEYS: &[&str] = &[
+    "theme", "font_size", "ui_font_size",
+    "ai_provider", "ai_model",
+    "disabled_plugins",
+];
+
+/// 导出文件的格式版本
+pub const EXPORT_VERSION: u32 = 1;
+
+/// 导出文件：`{ "version": 1, "profiles": { "presentation": { "font_size": 20 } } }`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfilesExport {
+    pub version: u32,
+    pub profiles: BTreeMap<String, Map<String, Value>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProfilesInfo {
+    pub active: Option<String>,
+    pub profiles: BTreeMap<String, Map<String, Value>>,
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 64 || name.chars().any(|c| c.is_control()) {
+        return Err(format!("Invalid profile name: \"{}\"", name));
+    }
+    Ok(name.to_string())
+}
+
+fn profiles_mut(values: &mut Map<String, Value>) -> &mut Map<String, Value> {
+    let entry = values.entry("profiles".to_string()).or_insert_with(|| Value::Object(Map::new()));
+    if !entry.is_object() {
+        *entry = Value::Object(Map::new());
+    }
+    entry.as_object_mut().unwrap()
+}
+
+fn active(values: &Map<String, Value>) -> Option<String> {
+    values.get("active_profile").and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+fn profile(values: &Map<String, Value>, name: &str) -> Option<Map<String, Value>> {
+    values.get("profiles").and_then(|p| p.get(name)).and_then(|p| p.as_object()).cloned()
+}
+
+/// 当前设置中属于档案的键 (未设置的取默认值)
+pub fn snapshot(values: &Map<String, Value>) -> Map<String, Value> {
+    let merged = config_file::with_defaults(values);
+    PROFILE_KEYS.iter()
+        .filter_map(|k| merged.get(*k).map(|v| (k.to_string(), v.clone())))
+        .collect()
+}
+
+/// 只保留档案键，丢弃类型不符的值 (导入或手动传入的档案)
+pub fn sanitize(profile: &Map<String, Value>) -> Map<String, Value> {
+    let defaults = config_file::with_defaults(&Map::new());
+    profile.iter()
+        .filter(|(k, v)| PROFILE_KEYS.contains(&k.as_str()) && check_value(&defaults, k, v).is_ok())
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+pub fn info(values: &Map<String, Value>) -> ProfilesInfo {
+    let profiles = values.get("profiles").and_then(|p| p.as_object()).map(|p| {
+        p.iter().filter_map(|(k, v)| v.as_object().map(|v| (k.clone(), v.clone()))).collect()
+    }).unwrap_or_default();
+    ProfilesInfo { active: active(values), profiles }
+}
+
+/// 新建档案，未给出内容时取当前设置
+pub fn create(values: &mut Map<String, Value>, name: &str, content: Option<&Map<String, Value>>) -> Result<(), String> {
+    let name = validate_name(name)?;
+    if profile(values, &name).is_some() {
+        return Err(format!("Profile \"{}\" already exists", name));
+    }
+    let content = match content {
+        Some(content) => sanitize(content),
+        None => snapshot(values),
+    };
+    profiles_mut(values).insert(name, Value::Object(content));
+    Ok(())
+}
+
+/// 把当前设置写回正在使用的档案，切换前与每次保存用户设置时调用
+pub fn sync_active(values: &mut Map<String, Value>) {
+    if let Some(name) = active(values) {
+        if profile(values, &name).is_some() {
+            let current = snapshot(values);
+            profiles_mut(values).insert(name, Value::Object(current));
+        }
+    }
+}
+
+fn apply(values: &mut Map<String, Value>, profile: &Map<String, Value>) {
+    for (key, value) in sanitize(profile) {
+        if value.is_null() {
+            values.remove(&key);
+        } else {
+            values.insert(key, value);
+        }
+    }
+}
+
+/// 切换到指定档案：先保存当前档案的改动，再把目标档案的值写入用户设置
+pub fn switch(values: &mut Map<String, Value>, name: &str) -> Result<(), String> {
+    let target = profile(values, name).ok_or_else(|| format!("Profile \"{}\" not found", name))?;
+    sync_active(values);
+    apply(values, &target);
+    values.insert("active_profile".to_string(), Value::String(name.to_string()));
+    Ok(())
+}
+
+/// 启动时应用记录的档案 (配置文件可能被手动编辑过)；档案已不存在时清除记录
+/// 返回设置是否发生变化
+pub fn apply_active(values: &mut Map<String, Value>) -> bool {
+    let name = match active(values) {
+        Some(name) => name,
+        None => return false,
+    };
+    let before = values.clone();
+    match profile(values, &name) {
+        Some(target) => apply(values, &target),
+        None => {
+            values.remove("active_profile");
+        }
+    }
+    *values != before
+}
+
+pub fn delete(values: &mut Map<String, Value>, name: &str) -> Result<(), String> {
+    if profiles_mut(values).remove(name).is_none() {
+        return Err(format!("Profile \"{}\" not found", name));
+    }
+    if active(values).as_deref() == Some(name) {
+        values.remove("active_profile");
+    }
+    Ok(())
+}
+
+/// 导出指定档案，未指定时导出全部
+pub fn export(values: &Map<String, Value>, names: Option<&[String]>) -> Result<ProfilesExport, String> {
+    let mut profiles = info(values).profiles;
+    if let Some(names) = names {
+        if let Some(missing) = names.iter().find(|n| !profiles.contains_key(*n)) {
+            return Err(format!("Profile \"{}\" not found", missing));
+        }
+        profiles.retain(|k, _| names.contains(k));
+    }
+    Ok(ProfilesExport { version: EXPORT_VERSION, profiles })
+}
+
+/// 导入档案，同名档案被覆盖；覆盖了正在使用的档案时立即应用。返回导入的档案名
+pub fn import(values: &mut Map<String, Value>, file: &ProfilesExport) -> Result<Vec<String>, String> {
+    if file.version > EXPORT_VERSION {
+        return Err(format!("Unsupported profiles file version: {}", file.version));
+    }
+    let mut imported = Vec::new();
+    for (name, content) in &file.profiles {
+        let name = validate_name(name)?;
+        profiles_mut(values).insert(name.clone(), Value::Object(sanitize(content)));
+        imported.push(name);
+    }
+    if active(values).map(|a| imported.contains(&a)).unwrap_or(false) {
+        apply_active(values);
+    }
+    Ok(imported)
+}
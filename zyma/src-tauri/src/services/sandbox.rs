@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use async_trait::async_trait;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use serde::{Serialize, Deserialize};
+use crate::models::{AppSettings, FileItem, FileReadResponse, WorkspaceFolder};
+use crate::services::transfer::{ConflictPolicy, ProgressFn};
+use crate::services::vfs::{normalize_path, FileStat, FileSystem, FsError};
+
+/// 文件访问范围策略
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxMode {
+    /// 不限制 (编辑器默认行为)
+    #[default]
+    Unrestricted,
+    /// 仅允许访问工作区以及用户显式打开的文件
+    Workspace,
+    /// 工作区外只读
+    ReadOnlyOutside,
+}
+
+impl SandboxMode {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        match settings.fs_sandbox.as_deref() {
+            Some("workspace") => SandboxMode::Workspace,
+            Some("read_only_outside") => SandboxMode::ReadOnlyOutside,
+            _ => SandboxMode::Unrestricted,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// 发起文件操作的一方 (经由 IPC 的请求由 CallerRegistry 认定)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Caller {
+    User,
+    Plugin,
+    Agent,
+}
+
+impl Caller {
+    /// 插件与 Agent 无论用户如何配置，一律使用最严格的 Workspace 模式
+    pub fn effective_mode(&self, configured: SandboxMode) -> SandboxMode {
+        match self {
+            Caller::User => configured,
+            Caller::Plugin | Caller::Agent => SandboxMode::Workspace,
+        }
+    }
+}
+
+/// 由后端认定 IPC 请求的调用方，前端无法自行声明身份
+/// - 每个窗口的界面在加载时领取一次用户令牌，之后的请求在请求头中带上该令牌即视为用户
+/// - 页面重新加载时令牌作废，由新页面重新领取；插件在界面之后加载，拿不到令牌
+/// - 原生 Chat Participant 登记的命令始终视为 Agent
+/// - 其余请求 (未带令牌或令牌不符) 一律视为插件
+#[derive(Default)]
+pub struct CallerRegistry {
+    /// webview label -> 已领取的令牌
+    user_tokens: RwLock<HashMap<String, String>>,
+    agent_commands: RwLock<HashSet<String>>,
+}
+
+impl CallerRegistry {
+    /// 登记以 Agent 身份运行的命令
+    pub fn set_agent_commands(&self, commands: impl IntoIterator<Item = String>) {
+        *self.agent_commands.write().unwrap() = commands.into_iter().collect();
+    }
+
+    /// 为窗口发放用户令牌，每次页面加载只能领取一次
+    pub fn claim_user_token(&self, webview: &str) -> Result<String, String> {
+        let mut tokens = self.user_tokens.write().unwrap();
+        if tokens.contains_key(webview) {
+            return Err(format!("User token for window '{}' has already been claimed", webview));
+        }
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        tokens.insert(webview.to_string(), token.clone());
+        Ok(token)
+    }
+
+    /// 页面重新加载或窗口关闭时作废该窗口的令牌
+    pub fn reset(&self, webview: &str) {
+        self.user_tokens.write().unwrap().remove(webview);
+    }
+
+    pub fn resolve(&self, webview: &str, command: &str, token: Option<&str>) -> Caller {
+        if self.agent_commands.read().unwrap().contains(command) {
+            return Caller::Agent;
+        }
+        match (self.user_tokens.read().unwrap().get(webview), token) {
+            (Some(expected), Some(token)) if expected == token => Caller::User,
+            _ => Caller::Plugin,
+        }
+    }
+}
+
+/// 解析符号链接后的真实路径；目标尚不存在时解析最近的已存在祖先再拼回剩余部分
+pub fn resolve_real(path: &Path) -> PathBuf {
+    let path = normalize_path(path);
+    let mut tail = Vec::new();
+    let mut cur = path.as_path();
+    loop {
+        if let Ok(mut real) = std::fs::canonicalize(cur) {
+            for name in tail.iter().rev() {
+                real.push(name);
+            }
+            return real;
+        }
+        match (cur.parent(), cur.file_name()) {
+            (Some(parent), Some(name)) => {
+                tail.push(name.to_os_string());
+                cur = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
+/// 判断已解析的真实路径在给定模式下是否允许访问
+pub fn is_permitted(mode: SandboxMode, access: Access, real: &Path, roots: &[PathBuf], opened: &HashSet<PathBuf>) -> bool {
+    if mode == SandboxMode::Unrestricted || roots.iter().any(|r| real.starts_with(r)) || opened.contains(real) {
+        return true;
+    }
+    mode == SandboxMode::ReadOnlyOutside && access == Access::Read
+}
+
+/// 以指定调用方身份访问底层文件系统，每次操作前都会做沙箱检查
+/// 插件 / Agent 发起的操作必须经由此包装访问 WorkspaceService
+pub struct SandboxedFs<'a> {
+    inner: &'a (dyn FileSystem + Send + Sync),
+    caller: Caller,
+    mode: SandboxMode,
+}
+
+impl<'a> SandboxedFs<'a> {
+    pub fn new(inner: &'a (dyn FileSystem + Send + Sync), caller: Caller) -> Self {
+        let mode = caller.effective_mode(inner.sandbox_mode());
+        Self { inner, caller, mode }
+    }
+
+    fn check(&self, path: &str, access: Access) -> Result<(), FsError> {
+        self.check_access(path, access, self.mode)
+    }
+}
+
+#[async_trait]
+impl FileSystem for SandboxedFs<'_> {
+    fn get_cwd(&self) -> String {
+        self.inner.get_cwd()
+    }
+
+    /// 切换工作区等同于扩大沙箱范围，仅允许用户操作
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> {
+        if self.caller != Caller::User {
+            return Err(FsError::AccessDenied { path: path.to_string(), access: Access::Write, mode: self.mode });
+        }
+        self.inner.set_cwd(path)
+    }
+
+    fn roots(&self) -> Vec<WorkspaceFolder> {
+        self.inner.roots()
+    }
+
+    fn set_roots(&self, roots: Vec<WorkspaceFolder>) -> Result<(), FsError> {
+        if self.caller != Caller::User {
+            let path = roots.first().map(|f| f.path.clone()).unwrap_or_default();
+            return Err(FsError::AccessDenied { path, access: Access::Write, mode: self.mode });
+        }
+        self.inner.set_roots(roots)
+    }
+
+    fn sandbox_mode(&self) -> SandboxMode {
+        self.mode
+    }
+
+    /// 插件与 Agent 只能访问显式开放的实现 (如远程主机默认只对用户开放)
+    fn check_access(&self, path: &str, access: Access, mode: SandboxMode) -> Result<(), FsError> {
+        if self.caller != Caller::User && !self.inner.allows_restricted_callers(path) {
+            return Err(FsError::AccessDenied { path: path.to_string(), access, mode });
+        }
+        self.inner.check_access(path, access, mode)
+    }
+
+    fn allows_restricted_callers(&self, path: &str) -> bool {
+        self.inner.allows_restricted_callers(path)
+    }
+
+    fn absolute_path(&self, path: &str) -> String {
+        self.inner.absolute_path(path)
+    }
+
+    fn allow_path(&self, path: &str) {
+        if self.caller == Caller::User {
+            self.inner.allow_path(path);
+        }
+    }
+
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.read_dir(path).await
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.read_bytes(path).await
+    }
+
+    fn supports_range_reads(&self, path: &str) -> bool {
+        self.inner.supports_range_reads(path)
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.read_range(path, offset, length).await
+    }
+
+    async fn read_file(&self, path: &str) -> Result<FileReadResponse, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.read_file(path).await
+    }
+
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.write_bytes(path, data).await
+    }
+
+    async fn write_range(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.write_range(path, offset, data).await
+    }
+
+    async fn create_file(&self, path: &str) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.create_file(path).await
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.create_dir(path).await
+    }
+
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.remove_item(path).await
+    }
+
+    async fn trash_item(&self, path: &str) -> Result<bool, FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.trash_item(path).await
+    }
+
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        self.check(from, Access::Write)?;
+        self.check(to, Access::Write)?;
+        self.inner.rename_item(from, to).await
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), FsError> {
+        self.check(from, Access::Read)?;
+        self.check(to, Access::Write)?;
+        self.inner.copy_file(from, to).await
+    }
+
+    async fn copy_item(&self, from: &str, to: &str, policy: ConflictPolicy, op_id: &str, progress: ProgressFn<'_>) -> Result<Option<String>, FsError> {
+        self.check(from, Access::Read)?;
+        self.check(to, Access::Write)?;
+        self.inner.copy_item(from, to, policy, op_id, progress).await
+    }
+
+    async fn move_item(&self, from: &str, to: &str, policy: ConflictPolicy, op_id: &str, progress: ProgressFn<'_>) -> Result<Option<String>, FsError> {
+        self.check(from, Access::Write)?;
+        self.check(to, Access::Write)?;
+        self.inner.move_item(from, to, policy, op_id, progress).await
+    }
+
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.stat(path).await
+    }
+
+    async fn read_link(&self, path: &str) -> Result<String, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.read_link(path).await
+    }
+
+    async fn real_path(&self, path: &str) -> Result<String, FsError> {
+        self.check(path, Access::Read)?;
+        self.inner.real_path(path).await
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.set_mode(path, mode).await
+    }
+
+    async fn set_readonly(&self, path: &str, readonly: bool) -> Result<(), FsError> {
+        self.check(path, Access::Write)?;
+        self.inner.set_readonly(path, readonly).await
+    }
+
+    async fn is_writable(&self, path: &str) -> Result<bool, FsError> {
+        if self.check(path, Access::Write).is_err() {
+            return Ok(false);
+        }
+        self.inner.is_writable(path).await
+    }
+}
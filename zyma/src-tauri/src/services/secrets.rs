@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+/// 系统钥匙串中的服务名
+const KEYRING_SERVICE: &str = "zyma";
+/// AI 服务密钥的引用前缀 (设置中的 ai_api_key_ref)，未指定服务商时即为完整引用
+pub const AI_API_KEY_SECRET: &str = "ai_api_key";
+
+/// AI 密钥按服务商分别保存 (`ai_api_key:<provider>`)，切换服务商 (如切换配置档案) 后不会把其它服务商的密钥发出去
+pub fn ai_api_key_id(provider: Option<&str>) -> String {
+    match provider.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(p) => format!("{}:{}", AI_API_KEY_SECRET, p),
+        None => AI_API_KEY_SECRET.to_string(),
+    }
+}
+
+// 用于校验主口令的固定条目
+const CHECK_ID: &str = "__check__";
+const CHECK_VALUE: &[u8] = b"zyma-vault";
+
+/// 实际使用的存储后端
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackendKind {
+    Keyring,
+    Vault,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SecretsStatus {
+    pub backend: SecretBackendKind,
+    pub vault_exists: bool,
+    /// 钥匙串始终可用；加密文件需要先用主口令解锁
+    pub unlocked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Sealed {
+    nonce: String,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    check: Option<Sealed>,
+    entries: BTreeMap<String, Sealed>,
+}
+
+/// 系统钥匙串 (macOS 钥匙串、Windows 凭据管理器、Linux Secret Service)
+pub struct KeyringBackend;
+
+impl KeyringBackend {
+    fn entry(id: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYRING_SERVICE, id).map_err(|e| e.to_string())
+    }
+
+    /// 钥匙串是否可用 (没有桌面会话的 Linux 上通常不可用)
+    pub fn available() -> bool {
+        matches!(Self::entry("__probe__").map(|e| e.get_password()), Ok(Ok(_)) | Ok(Err(keyring::Error::NoEntry)))
+    }
+
+    pub fn get(id: &str) -> Result<Option<String>, String> {
+        match Self::entry(id)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn set(id: &str, value: &str) -> Result<(), String> {
+        Self::entry(id)?.set_password(value).map_err(|e| e.to_string())
+    }
+
+    pub fn delete(id: &str) -> Result<(), String> {
+        match Self::entry(id)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// 以主口令加密的密钥文件：Argon2id 从口令派生密钥，XChaCha20-Poly1305 逐条加密，
+/// 条目 id 作为附加数据，防止密文被挪到其他条目下
+pub struct FileVault {
+    path: PathBuf,
+    key: RwLock<Option<[u8; 32]>>,
+    lock: Mutex<()>,
+}
+
+impl FileVault {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, key: RwLock::new(None), lock: Mutex::new(()) }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.read().unwrap().is_some()
+    }
+
+    /// 用主口令解锁；文件不存在时以该口令新建
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        if passphrase.is_empty() {
+            return Err("Passphrase must not be empty".to_string());
+        }
+        let _guard = self.lock.lock().unwrap();
+        let key = match self.read_file()? {
+            Some(file) => {
+                let key = derive_key(passphrase, &from_hex(&file.salt)?)?;
+                let check = file.check.as_ref().ok_or_else(|| "Secret vault is corrupted".to_string())?;
+                match open(&key, CHECK_ID, check) {
+                    Ok(value) if value == CHECK_VALUE => key,
+                    _ => return Err("Wrong passphrase".to_string()),
+                }
+            }
+            None => {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                let key = derive_key(passphrase, &salt)?;
+                let file = VaultFile { version: 1, salt: to_hex(&salt), check: Some(seal(&key, CHECK_ID, CHECK_VALUE)?), entries: BTreeMap::new() };
+                self.write_file(&file)?;
+                key
+            }
+        };
+        *self.key.write().unwrap() = Some(key);
+        Ok(())
+    }
+
+    pub fn lock(&self) {
+        *self.key.write().unwrap() = None;
+    }
+
+    fn key(&self) -> Result<[u8; 32], String> {
+        self.key.read().unwrap().ok_or_else(|| "Secret vault is locked".to_string())
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<String>, String> {
+        let key = self.key()?;
+        let _guard = self.lock.lock().unwrap();
+        let file = self.read_file()?.unwrap_or_default();
+        match file.entries.get(id) {
+            Some(sealed) => {
+                let bytes = open(&key, id, sealed)?;
+                String::from_utf8(bytes).map(Some).map_err(|e| e.to_string())
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn set(&self, id: &str, value: &str) -> Result<(), String> {
+        let key = self.key()?;
+        let _guard = self.lock.lock().unwrap();
+        let mut file = self.read_file()?.ok_or_else(|| "Secret vault is locked".to_string())?;
+        file.entries.insert(id.to_string(), seal(&key, id, value.as_bytes())?);
+        self.write_file(&file)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        self.key()?;
+        let _guard = self.lock.lock().unwrap();
+        if let Some(mut file) = self.read_file()? {
+            if file.entries.remove(id).is_some() {
+                self.write_file(&file)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_file(&self) -> Result<Option<VaultFile>, String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).map(Some).map_err(|e| format!("Secret vault is corrupted: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn write_file(&self, file: &VaultFile) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+        let tmp = self.path.with_extension("vault.tmp");
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+        }
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+/// 密钥服务：优先使用系统钥匙串，不可用时改用需要主口令的加密文件 (数据目录下的 `secrets.vault`)
+/// 设置中只保存密钥的引用，取值只在后端进行，原始密钥不会经过 IPC 返回前端
+pub struct SecretService {
+    use_keyring: bool,
+    vault: FileVault,
+}
+
+impl SecretService {
+    pub fn new(vault_path: PathBuf, use_keyring: bool) -> Self {
+        Self { use_keyring, vault: FileVault::new(vault_path) }
+    }
+
+    /// 探测系统钥匙串是否可用并选择后端
+    pub fn detect(vault_path: PathBuf) -> Self {
+        let use_keyring = KeyringBackend::available();
+        if !use_keyring {
+            log::info!("OS keyring unavailable, using encrypted vault at {}", vault_path.display());
+        }
+        Self::new(vault_path, use_keyring)
+    }
+
+    pub fn default_vault_path() -> PathBuf {
+        crate::services::paths::dirs().data_path("secrets.vault")
+    }
+
+    pub fn status(&self) -> SecretsStatus {
+        SecretsStatus {
+            backend: if self.use_keyring { SecretBackendKind::Keyring } else { SecretBackendKind::Vault },
+            vault_exists: self.vault.exists(),
+            unlocked: self.use_keyring || self.vault.is_unlocked(),
+        }
+    }
+
+    pub fn unlock(&self, passphrase: &str) -> Result<(), String> {
+        self.vault.unlock(passphrase)
+    }
+
+    pub fn lock(&self) {
+        self.vault.lock();
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<String>, String> {
+        if self.use_keyring { KeyringBackend::get(id) } else { self.vault.get(id) }
+    }
+
+    pub fn set(&self, id: &str, value: &str) -> Result<(), String> {
+        if self.use_keyring { KeyringBackend::set(id, value) } else { self.vault.set(id, value) }
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        if self.use_keyring { KeyringBackend::delete(id) } else { self.vault.delete(id) }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default().hash_password_into(passphrase.as_bytes(), salt, &mut key).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], id: &str, plaintext: &[u8]) -> Result<Sealed, String> {
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let data = cipher.encrypt(&nonce, Payload { msg: plaintext, aad: id.as_bytes() })
+        .map_err(|_| "Failed to encrypt secret".to_string())?;
+    Ok(Sealed { nonce: to_hex(&nonce), data: to_hex(&data) })
+}
+
+fn open(key: &[u8; 32], id: &str, sealed: &Sealed) -> Result<Vec<u8>, String> {
+    let nonce = from_hex(&sealed.nonce)?;
+    if nonce.len() != 24 {
+        return Err("Secret vault is corrupted".to_string());
+    }
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(key));
+    cipher.decrypt(XNonce::from_slice(&nonce), Payload { msg: &from_hex(&sealed.data)?, aad: id.as_bytes() })
+        .map_err(|_| "Failed to decrypt secret".to_string())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    if text.len() % 2 != 0 {
+        return Err("Secret vault is corrupted".to_string());
+    }
+    (0..text.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).map_err(|_| "Secret vault is corrupted".to_string()))
+        .collect()
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use sha2::{Digest, Sha256};
+use crate::models::{DirtyBuffer, SessionInfo};
+
+/// 按工作区根目录分别保存的会话 (数据目录下的 `sessions/<根目录哈希>.json`)
+/// 内存中保留当前工作区的最新会话，切换工作区时先落盘再加载新的会话
+pub struct SessionStore {
+    dir: PathBuf,
+    current: Mutex<Option<SessionInfo>>,
+    // 启动时恢复的热退出缓冲区，随第一次 load 交给前端
+    restored_buffers: Mutex<Vec<DirtyBuffer>>,
+}
+
+impl SessionStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, current: Mutex::new(None), restored_buffers: Mutex::new(Vec::new()) }
+    }
+
+    pub fn default_dir() -> PathBuf {
+        crate::services::paths::dirs().data_path("sessions")
+    }
+
+    /// 统一分隔符并去掉末尾的 "/"，Windows 下忽略大小写
+    pub fn normalize_root(root: &str) -> String {
+        let normalized = root.replace('\\', "/");
+        let trimmed = if normalized.len() > 1 { normalized.trim_end_matches('/') } else { &normalized };
+        if cfg!(windows) { trimmed.to_lowercase() } else { trimmed.to_string() }
+    }
+
+    fn file_for(&self, root: &str) -> PathBuf {
+        let digest = Sha256::digest(Self::normalize_root(root).as_bytes());
+        let name: String = digest.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    fn same_root(a: &str, b: &str) -> bool {
+        Self::normalize_root(a) == Self::normalize_root(b)
+    }
+
+    pub fn set_restored_buffers(&self, buffers: Vec<DirtyBuffer>) {
+        *self.restored_buffers.lock().unwrap() = buffers;
+    }
+
+    /// 读取工作区的会话；没有记录时返回只带 root_path 的空会话
+    pub fn load(&self, root: &str) -> SessionInfo {
+        let mut session = std::fs::read_to_string(self.file_for(root)).ok()
+            .and_then(|content| serde_json::from_str::<SessionInfo>(&content).ok())
+            .unwrap_or_default();
+        session.root_path = Some(root.to_string());
+        session
+    }
+
+    /// 当前工作区的会话 (内存中的最新状态)，首次调用时附带启动恢复的未保存内容
+    pub fn current(&self, root: &str) -> SessionInfo {
+        let mut current = self.current.lock().unwrap();
+        let mut session = match current.as_ref() {
+            Some(s) if s.root_path.as_deref().map(|r| Self::same_root(r, root)).unwrap_or(false) => s.clone(),
+            _ => {
+                let loaded = self.load(root);
+                *current = Some(loaded.clone());
+                loaded
+            }
+        };
+        session.dirty_buffers = std::mem::take(&mut *self.restored_buffers.lock().unwrap());
+        session
+    }
+
+    /// 更新当前工作区的会话并落盘；root_path 与当前工作区不符的 (切换前迟到的) 更新会被忽略
+    pub fn update(&self, cwd: &str, mut session: SessionInfo) -> Result<bool, String> {
+        match session.root_path.as_deref() {
+            Some(root) if Self::same_root(root, cwd) => {}
+            _ => return Ok(false),
+        }
+        session.dirty_buffers.clear();
+        self.write(&session)?;
+        *self.current.lock().unwrap() = Some(session);
+        Ok(true)
+    }
+
+    /// 切换工作区：保存离开的会话，加载进入的会话
+    pub fn switch(&self, incoming_root: &str) -> Result<SessionInfo, String> {
+        let outgoing = self.current.lock().unwrap().take();
+        if let Some(session) = outgoing {
+            if !session.root_path.as_deref().map(|r| Self::same_root(r, incoming_root)).unwrap_or(false) {
+                self.write(&session)?;
+            }
+        }
+        let incoming = self.load(incoming_root);
+        *self.current.lock().unwrap() = Some(incoming.clone());
+        Ok(incoming)
+    }
+
+    fn write(&self, session: &SessionInfo) -> Result<(), String> {
+        let root = match session.root_path.as_deref() {
+            Some(root) => root,
+            None => return Ok(()),
+        };
+        std::fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let target = self.file_for(root);
+        let tmp = target.with_extension("json.tmp");
+        let content = serde_json::to_string_pretty(session).map_err(|e| e.to_string())?;
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &target).map_err(|e| e.to_string())
+    }
+}
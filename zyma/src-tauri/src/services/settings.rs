@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::models::{AppSettings, WorkspaceFolder};
+use crate::services::config_file::strip_jsonc;
+use crate::services::workspace;
+
+/// 文件夹级设置文件 (相对根目录)，可随仓库提交
+pub const FOLDER_SETTINGS_FILE: &str = ".zyma/settings.json";
+
+/// 只能在用户设置中修改的键：凭据与 AI 服务商、远程连接、沙箱、插件启用状态与窗口状态不允许被仓库内的配置覆盖
+pub const USER_ONLY_KEYS: &[&str] = &[
+    "ai_api_key", "ai_api_key_ref", "ai_base_url", "ssh_profiles", "fs_sandbox",
+    "session", "recent_workspaces", "windows",
+    "window_width", "window_height", "window_x", "window_y", "is_maximized",
+    "single_instance", "context_menu", "auto_update",
+    "profiles", "active_profile", "disabled_plugins", "ai_provider",
+];
+
+/// 由后端维护的键：会话、最近工作区、窗口状态、密钥引用与配置档案各有专门的命令写入，
+/// 设置面板回传的整份设置中的这些键 (可能已过期) 一律忽略
+pub const BACKEND_OWNED_KEYS: &[&str] = &[
+    "version", "session", "recent_workspaces", "windows",
+    "window_width", "window_height", "window_x", "window_y", "is_maximized",
+    "ai_api_key_ref", "profiles", "active_profile",
+];
+
+/// 设置的作用域，优先级从低到高
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsScope {
+    Default,
+    #[default]
+    User,
+    /// `.zyma-workspace` 中的 settings，未打开工作区文件时为主根目录的 `.zyma/settings.json`
+    Workspace,
+    /// 多根工作区中某个根目录的 `.zyma/settings.json`
+    Folder,
+}
+
+/// 某个键的生效来源
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SettingSource {
+    pub scope: SettingsScope,
+    /// 来自 `"[rust]": { ... }` 这样的语言专属块
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+/// 一层设置的原始键值
+#[derive(Debug, Clone)]
+pub struct SettingsLayer {
+    pub scope: SettingsScope,
+    pub values: Map<String, Value>,
+}
+
+/// 合并后的设置及各键的来源 (未列出的键为默认值)
+#[derive(Serialize, Debug, Clone)]
+pub struct EffectiveSettings {
+    #[serde(flatten)]
+    pub settings: AppSettings,
+    pub sources: BTreeMap<String, SettingSource>,
+}
+
+/// 设置存放的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsTarget {
+    /// 整个文件即为设置对象 (用户配置、`.zyma/settings.json`)
+    File(PathBuf),
+    /// `.zyma-workspace` 文件中的 settings 字段
+    WorkspaceFile(PathBuf),
+}
+
+impl SettingsTarget {
+    pub fn path(&self) -> &Path {
+        match self {
+            SettingsTarget::File(path) | SettingsTarget::WorkspaceFile(path) => path,
+        }
+    }
+
+    pub fn read(&self) -> Result<Map<String, Value>, String> {
+        match self {
+            SettingsTarget::File(path) => read_json_map(path),
+            SettingsTarget::WorkspaceFile(path) => workspace::load_settings(path),
+        }
+    }
+
+    pub fn write(&self, values: &Map<String, Value>) -> Result<(), String> {
+        match self {
+            SettingsTarget::File(path) => {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+                }
+                let content = serde_json::to_string_pretty(values).map_err(|e| e.to_string())?;
+                std::fs::write(path, content).map_err(|e| e.to_string())
+            }
+            SettingsTarget::WorkspaceFile(path) => workspace::save_settings(path, values),
+        }
+    }
+}
+
+/// 读取 JSON 对象文件 (允许注释与末尾逗号)；文件不存在时为空
+pub fn read_json_map(path: &Path) -> Result<Map<String, Value>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => match serde_json::from_str::<Value>(&strip_jsonc(&content)) {
+            Ok(Value::Object(map)) => Ok(map),
+            Ok(_) => Err(format!("Settings file must contain a JSON object: {}", path.display())),
+            Err(e) => Err(format!("Invalid settings file {}: {}", path.display(), e)),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 工作区与文件夹两层设置的位置
+/// resource 为当前文件时，文件夹层取其所在的根目录，否则取主根目录
+pub fn locate(roots: &[WorkspaceFolder], workspace_file: Option<&str>, resource: Option<&str>) -> Vec<(SettingsScope, SettingsTarget)> {
+    let folder_settings = |root: &str| SettingsTarget::File(Path::new(root).join(FOLDER_SETTINGS_FILE));
+    let primary = match roots.first() {
+        // 尚未打开工作区时没有工作区与文件夹设置
+        Some(root) if root.path != "." && root.path != "./" => root.path.as_str(),
+        _ => return Vec::new(),
+    };
+    let folder = resource
+        .map(|r| r.replace('\\', "/"))
+        .and_then(|r| roots.iter().find(|f| r == f.path || r.starts_with(&format!("{}/", f.path.trim_end_matches('/')))))
+        .map(|f| f.path.as_str())
+        .unwrap_or(primary);
+
+    let mut targets = Vec::new();
+    match workspace_file {
+        Some(file) => {
+            targets.push((SettingsScope::Workspace, SettingsTarget::WorkspaceFile(PathBuf::from(file))));
+            targets.push((SettingsScope::Folder, folder_settings(folder)));
+        }
+        None => {
+            targets.push((SettingsScope::Workspace, folder_settings(primary)));
+            if folder != primary {
+                targets.push((SettingsScope::Folder, folder_settings(folder)));
+            }
+        }
+    }
+    targets
+}
+
+/// `"[rust]"` -> `Some("rust")`
+pub fn language_of(key: &str) -> Option<&str> {
+    key.strip_prefix('[').and_then(|k| k.strip_suffix(']')).filter(|k| !k.is_empty())
+}
+
+fn allowed(scope: SettingsScope, key: &str) -> bool {
+    scope == SettingsScope::User || !USER_ONLY_KEYS.contains(&key)
+}
+
+/// 检查在已合并的设置上写入某个键后是否仍能解析为 AppSettings，返回解析错误
+pub fn check_value(merged: &Map<String, Value>, key: &str, value: &Value) -> Result<(), String> {
+    let mut candidate = merged.clone();
+    candidate.insert(key.to_string(), value.clone());
+    serde_json::from_value::<AppSettings>(Value::Object(candidate)).map(|_| ()).map_err(|e| e.to_string())
+}
+
+// 类型不符的值 (如 "tab_size": "two") 会让整个 AppSettings 反序列化失败，逐个校验后跳过
+fn accepts(merged: &Map<String, Value>, key: &str, value: &Value) -> bool {
+    check_value(merged, key, value).is_ok()
+}
+
+/// 按 默认 < 用户 < 工作区 < 文件夹 的顺序合并，指定 language 时再叠加各层的语言专属块
+pub fn resolve(layers: &[SettingsLayer], language: Option<&str>) -> Result<EffectiveSettings, String> {
+    let mut merged = match serde_json::to_value(AppSettings::default()).map_err(|e| e.to_string())? {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    let mut sources = BTreeMap::new();
+
+    for layer in layers {
+        for (key, value) in &layer.values {
+            if language_of(key).is_some() {
+                // 语言块跨层合并，便于一次看到全部覆盖
+                let entry = merged.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+                if let (Value::Object(target), Value::Object(block)) = (entry, value) {
+                    for (k, v) in block.iter().filter(|(k, _)| allowed(layer.scope, k)) {
+                        target.insert(k.clone(), v.clone());
+                    }
+                }
+                continue;
+            }
+            if !allowed(layer.scope, key) || (layer.scope != SettingsScope::User && !accepts(&merged, key, value)) {
+                continue;
+            }
+            merged.insert(key.clone(), value.clone());
+            sources.insert(key.clone(), SettingSource { scope: layer.scope, language: None });
+        }
+    }
+
+    if let Some(lang) = language {
+        let block_key = format!("[{}]", lang);
+        for layer in layers {
+            if let Some(Value::Object(block)) = layer.values.get(&block_key) {
+                for (key, value) in block {
+                    if language_of(key).is_some() || !allowed(layer.scope, key) || !accepts(&merged, key, value) {
+                        continue;
+                    }
+                    merged.insert(key.clone(), value.clone());
+                    sources.insert(key.clone(), SettingSource { scope: layer.scope, language: Some(lang.to_string()) });
+                }
+            }
+        }
+    }
+
+    let settings = serde_json::from_value(Value::Object(merged)).map_err(|e| e.to_string())?;
+    Ok(EffectiveSettings { settings, sources })
+}
+
+/// 把前端回传的完整设置 (生效值) 还原为用户层：
+/// 与生效值相同的键保留用户原值，因此来自工作区的值不会被顺带写进用户配置；后端维护的键保持不变
+pub fn user_layer_from_effective(incoming: &Map<String, Value>, effective: &EffectiveSettings, user: &Map<String, Value>) -> Result<Map<String, Value>, String> {
+    let effective_map = match serde_json::to_value(&effective.settings).map_err(|e| e.to_string())? {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    let mut result = user.clone();
+    for (key, value) in incoming {
+        if key == "sources" || BACKEND_OWNED_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        if effective_map.get(key) == Some(value) {
+            let from_other_layer = language_of(key).is_some()
+                || effective.sources.get(key).map(|s| s.scope != SettingsScope::User || s.language.is_some()).unwrap_or(false);
+            if user.contains_key(key) || from_other_layer {
+                continue;
+            }
+        }
+        result.insert(key.clone(), value.clone());
+    }
+    Ok(result)
+}
+
+/// 在一层设置上应用修改，值为 null 表示删除该键；指定 language 时写入对应的语言专属块
+pub fn apply_patch(target: &mut Map<String, Value>, scope: SettingsScope, values: &Map<String, Value>, language: Option<&str>) -> Result<(), String> {
+    if let Some(key) = values.keys().find(|k| !allowed(scope, k)) {
+        return Err(format!("Setting \"{}\" can only be changed in user settings", key));
+    }
+    let block = match language {
+        Some(lang) => {
+            let entry = target.entry(format!("[{}]", lang)).or_insert_with(|| Value::Object(Map::new()));
+            if !entry.is_object() {
+                *entry = Value::Object(Map::new());
+            }
+            entry.as_object_mut().unwrap()
+        }
+        None => target,
+    };
+    for (key, value) in values {
+        if value.is_null() {
+            block.remove(key);
+        } else {
+            block.insert(key.clone(), value.clone());
+        }
+    }
+    if let Some(lang) = language {
+        let key = format!("[{}]", lang);
+        if target.get(&key).and_then(|b| b.as_object()).map(|b| b.is_empty()).unwrap_or(false) {
+            target.remove(&key);
+        }
+    }
+    Ok(())
+}
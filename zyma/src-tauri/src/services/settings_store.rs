@@ -0,0 +1,164 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::models::AppSettings;
+use crate::services::{config_file, profiles};
+use crate::services::settings::SettingsScope;
+
+/// 设置变化通知 ("settings-changed" 事件)，keys 为发生变化的顶层键
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct SettingsChange {
+    pub scope: SettingsScope,
+    pub keys: Vec<String>,
+}
+
+type Listener = Box<dyn Fn(&SettingsChange) + Send + Sync>;
+
+/// 用户设置的唯一持有者：内存中保留最新的键值，所有读改写在同一把锁内完成，
+/// 文件被手动编辑或其他窗口保存后由监听回调 reload，并把变化的键通知出去
+pub struct SettingsStore {
+    path: PathBuf,
+    values: RwLock<Option<Map<String, Value>>>,
+    // 串行化读改写，避免窗口状态与最近工作区等并发写入互相覆盖
+    lock: Mutex<()>,
+    // 工作区与文件夹设置文件的上一次内容，用于计算变化的键
+    layers: Mutex<HashMap<PathBuf, Map<String, Value>>>,
+    listener: RwLock<Option<Listener>>,
+}
+
+impl SettingsStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            values: RwLock::new(None),
+            lock: Mutex::new(()),
+            layers: Mutex::new(HashMap::new()),
+            listener: RwLock::new(None),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn set_listener(&self, listener: Listener) {
+        *self.listener.write().unwrap() = Some(listener);
+    }
+
+    fn notify(&self, scope: SettingsScope, keys: Vec<String>) {
+        if keys.is_empty() {
+            return;
+        }
+        if let Some(listener) = self.listener.read().unwrap().as_ref() {
+            listener(&SettingsChange { scope, keys });
+        }
+    }
+
+    // 文件暂时无法解析 (如手动编辑到一半) 或由更新的版本写入时返回错误，保留内存中的设置
+    fn read_disk(&self) -> Result<Map<String, Value>, String> {
+        let loaded = config_file::load(&self.path);
+        match loaded.blocking_issue() {
+            Some(issue) => Err(format!("{} has errors and will not be overwritten: {}", self.path.display(), issue.message)),
+            None => Ok(loaded.values),
+        }
+    }
+
+    /// 用户设置的键值 (已迁移并去掉无效的键)，首次调用时从文件读取
+    /// 文件有问题时在此另存一份 `.corrupt` (仅首次读取时)
+    pub fn values(&self) -> Map<String, Value> {
+        if let Some(values) = self.values.read().unwrap().as_ref() {
+            return values.clone();
+        }
+        let loaded = config_file::load(&self.path);
+        if !loaded.issues.is_empty() {
+            config_file::keep_corrupt_copy(&self.path);
+        }
+        *self.values.write().unwrap() = Some(loaded.values.clone());
+        loaded.values
+    }
+
+    pub fn settings(&self) -> AppSettings {
+        config_file::to_settings(&self.values()).unwrap_or_default()
+    }
+
+    /// 重新读取文件 (文件监听回调)，返回变化的键
+    pub fn reload(&self) -> Vec<String> {
+        let _guard = self.lock.lock().unwrap();
+        let new = match self.read_disk() {
+            Ok(values) => values,
+            Err(_) => return Vec::new(),
+        };
+        let old = self.values();
+        *self.values.write().unwrap() = Some(new.clone());
+        let keys = diff_keys(&config_file::with_defaults(&old), &config_file::with_defaults(&new));
+        self.notify(SettingsScope::User, keys.clone());
+        keys
+    }
+
+    /// 在锁内修改用户设置的键值并写回，返回变化的键
+    pub fn update_values<F>(&self, f: F) -> Result<Vec<String>, String>
+    where
+        F: FnOnce(&mut Map<String, Value>) -> Result<(), String>,
+    {
+        let _guard = self.lock.lock().unwrap();
+        let old = self.values();
+        // 以磁盘为准，监听回调尚未到达的手动修改不会被覆盖；
+        // 文件无法解析时拒绝写入，否则一处笔误就会让整份设置被默认值替换
+        let mut values = self.read_disk()?;
+        f(&mut values)?;
+        // 使用档案时，档案中的键随用户设置一起保存
+        profiles::sync_active(&mut values);
+        values.insert("version".to_string(), Value::from(config_file::CURRENT_VERSION));
+        config_file::to_settings(&values)?;
+        self.write(&values)?;
+        *self.values.write().unwrap() = Some(values.clone());
+        let keys = diff_keys(&config_file::with_defaults(&old), &config_file::with_defaults(&values));
+        self.notify(SettingsScope::User, keys.clone());
+        Ok(keys)
+    }
+
+    /// 在锁内修改 AppSettings 并写回 (后端保存窗口状态、最近工作区等)
+    pub fn update<F: FnOnce(&mut AppSettings)>(&self, f: F) -> Result<Vec<String>, String> {
+        self.update_values(|values| {
+            let mut settings = config_file::to_settings(values)?;
+            f(&mut settings);
+            match serde_json::to_value(&settings).map_err(|e| e.to_string())? {
+                Value::Object(map) => *values = map,
+                _ => return Err("Settings must serialize to an object".to_string()),
+            }
+            Ok(())
+        })
+    }
+
+    /// 工作区或文件夹设置文件发生变化 (写入或外部修改)，返回变化的键
+    pub fn layer_changed(&self, scope: SettingsScope, path: &Path, values: &Map<String, Value>) -> Vec<String> {
+        let old = self.layers.lock().unwrap().insert(path.to_path_buf(), values.clone()).unwrap_or_default();
+        let keys = diff_keys(&old, values);
+        self.notify(scope, keys.clone());
+        keys
+    }
+
+    fn write(&self, values: &Map<String, Value>) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(values).map_err(|e| e.to_string())?;
+        let mut tmp = self.path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        std::fs::write(&tmp, content).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+/// 两份键值中不同的顶层键 (忽略 version)
+pub fn diff_keys(old: &Map<String, Value>, new: &Map<String, Value>) -> Vec<String> {
+    old.keys().chain(new.keys())
+        .filter(|k| k.as_str() != "version" && old.get(*k) != new.get(*k))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
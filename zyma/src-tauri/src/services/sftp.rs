@@ -0,0 +1,537 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use ssh2::{CheckResult, HashType, KnownHostFileKind, OpenFlags, OpenType, RenameFlags, Session, Sftp};
+use crate::models::{FileAttributes, FileItem, SshAuthMethod, SshProfile};
+use crate::services::{largefile, paths, transfer};
+use crate::services::vfs::{FileStat, FileSystem, FsError};
+
+/// 远程文件系统挂载的 scheme，路径形如 `sftp://<连接 id>/home/user/a.txt`
+pub const SFTP_SCHEME: &str = "sftp";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 单次 SSH/SFTP 操作的超时 (毫秒)
+const OPERATION_TIMEOUT_MS: u32 = 30_000;
+
+/// 连接所需的秘密信息，只在内存中使用，不写入配置
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SshCredentials {
+    pub password: Option<String>,
+    /// 私钥口令
+    pub passphrase: Option<String>,
+}
+
+/// 连接失败的原因；需要用户介入的情况带有结构化信息，其余为纯字符串
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SshError {
+    /// 主机密钥未记录在 known_hosts 中，用户确认指纹后以 accept_host_key 重新连接
+    UnknownHostKey { host: String, fingerprint: String },
+    /// 主机密钥与 known_hosts 中的记录不一致，拒绝连接
+    HostKeyMismatch { host: String, fingerprint: String },
+    /// 缺少密码或私钥口令；provider 为连接配置指定的认证提供者，前端先向它索取凭据，没有时再询问用户
+    AuthRequired { profile: String, method: SshAuthMethod, provider: Option<String> },
+    #[serde(untagged)]
+    Other(String),
+}
+
+impl std::fmt::Display for SshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SshError::UnknownHostKey { host, fingerprint } => write!(f, "Unknown host key for {} ({})", host, fingerprint),
+            SshError::HostKeyMismatch { host, .. } => write!(f, "Host key for {} does not match known_hosts", host),
+            SshError::AuthRequired { profile, .. } => write!(f, "Credentials required for {}", profile),
+            SshError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for SshError {
+    fn from(message: String) -> Self {
+        SshError::Other(message)
+    }
+}
+
+impl From<std::io::Error> for SshError {
+    fn from(e: std::io::Error) -> Self {
+        SshError::Other(e.to_string())
+    }
+}
+
+impl From<ssh2::Error> for SshError {
+    fn from(e: ssh2::Error) -> Self {
+        SshError::Other(e.to_string())
+    }
+}
+
+impl From<SshError> for String {
+    fn from(e: SshError) -> Self {
+        e.to_string()
+    }
+}
+
+fn sftp_error(e: ssh2::Error) -> FsError {
+    // ssh2 会把 SFTP 状态码映射为对应的 io::ErrorKind (NotFound、PermissionDenied 等)
+    FsError::from(std::io::Error::from(e))
+}
+
+/// 已建立的连接，返回给前端用于展示
+#[derive(Serialize, Debug, Clone)]
+pub struct RemoteConnection {
+    pub id: String,
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    /// 远程根目录的完整路径 (`sftp://<id>/...`)，可直接传给 read_dir
+    pub root: String,
+}
+
+struct Connection {
+    info: RemoteConnection,
+    /// 远程根目录 (不带 scheme 前缀)
+    root: String,
+    session: Session,
+    sftp: Sftp,
+}
+
+/// 基于 SFTP 的远程文件系统，挂载在 `sftp:` scheme 下
+/// 同时持有多个连接，路径中的第一段为连接 id；所有阻塞的 libssh2 调用都放到 blocking 线程执行
+pub struct SftpFileSystem {
+    connections: RwLock<HashMap<String, Arc<Connection>>>,
+}
+
+impl Default for SftpFileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SftpFileSystem {
+    pub fn new() -> Self {
+        Self { connections: RwLock::new(HashMap::new()) }
+    }
+
+    /// 建立连接 (阻塞)，同 id 的旧连接会被替换
+    pub fn connect(&self, profile: &SshProfile, credentials: &SshCredentials, accept_host_key: bool) -> Result<RemoteConnection, SshError> {
+        if profile.id.is_empty() || profile.id.contains('/') {
+            return Err(format!("Invalid connection id: '{}'", profile.id).into());
+        }
+        let addr = (profile.host.as_str(), profile.port).to_socket_addrs()?.next()
+            .ok_or_else(|| format!("Cannot resolve host: {}", profile.host))?;
+        let tcp = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        let mut session = Session::new()?;
+        session.set_tcp_stream(tcp);
+        session.set_timeout(OPERATION_TIMEOUT_MS);
+        session.handshake()?;
+        verify_host_key(&session, profile, accept_host_key)?;
+        authenticate(&session, profile, credentials)?;
+
+        let sftp = session.sftp()?;
+        let root = match profile.remote_root.as_deref().filter(|r| !r.trim().is_empty()) {
+            Some(r) => r.trim_end_matches('/').to_string(),
+            None => sftp.realpath(Path::new("."))?.to_string_lossy().to_string(),
+        };
+        let root = if root.is_empty() { "/".to_string() } else { root };
+        let info = RemoteConnection {
+            id: profile.id.clone(),
+            name: if profile.name.is_empty() { format!("{}@{}", profile.username, profile.host) } else { profile.name.clone() },
+            host: profile.host.clone(),
+            port: profile.port,
+            username: profile.username.clone(),
+            root: format!("{}://{}{}", SFTP_SCHEME, profile.id, root),
+        };
+        let conn = Arc::new(Connection { info: info.clone(), root, session, sftp });
+        if let Some(old) = self.connections.write().unwrap().insert(profile.id.clone(), conn) {
+            let _ = old.session.disconnect(None, "replaced", None);
+        }
+        Ok(info)
+    }
+
+    /// 断开连接，返回此前是否已连接
+    pub fn disconnect(&self, id: &str) -> bool {
+        match self.connections.write().unwrap().remove(id) {
+            Some(conn) => {
+                let _ = conn.session.disconnect(None, "bye", None);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn connections(&self) -> Vec<RemoteConnection> {
+        let mut list: Vec<RemoteConnection> = self.connections.read().unwrap().values().map(|c| c.info.clone()).collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    /// 挂载表传入的路径形如 `//<id>/remote/path`，返回 (连接, 远程绝对路径)
+    /// 只有 `//<id>` 时指向该连接的根目录
+    fn resolve(&self, path: &str) -> Result<(Arc<Connection>, String), FsError> {
+        let rest = path.strip_prefix("//")
+            .ok_or_else(|| format!("Invalid remote path, expected {}://<connection>/path: {}", SFTP_SCHEME, path))?;
+        let (id, remote) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        let conn = self.connections.read().unwrap().get(id).cloned()
+            .ok_or_else(|| format!("Not connected: {}", id))?;
+        let remote = if remote.is_empty() { conn.root.clone() } else { remote.to_string() };
+        Ok((conn, remote))
+    }
+
+    /// 在 blocking 线程中对解析后的远程路径执行 SFTP 操作
+    async fn run<T, F>(&self, path: &str, op: F) -> Result<T, FsError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection, &Path) -> Result<T, FsError> + Send + 'static,
+    {
+        let (conn, remote) = self.resolve(path)?;
+        tokio::task::spawn_blocking(move || op(&conn, Path::new(&remote)))
+            .await
+            .map_err(|e| FsError::from(e.to_string()))?
+    }
+}
+
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    match path.strip_prefix("~/") {
+        Some(rest) => Ok(paths::home_dir()?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+/// 与 `ssh-keygen -lf` 相同格式的指纹 (`SHA256:<不带填充的 base64>`)
+pub fn fingerprint(sha256: &[u8]) -> String {
+    format!("SHA256:{}", base64::engine::general_purpose::STANDARD_NO_PAD.encode(sha256))
+}
+
+/// 按 ~/.ssh/known_hosts 校验主机密钥；未知主机需显式接受，接受后向 known_hosts 追加一行
+fn verify_host_key(session: &Session, profile: &SshProfile, accept: bool) -> Result<(), SshError> {
+    let (key, key_type) = session.host_key().ok_or_else(|| "Server did not provide a host key".to_string())?;
+    let fingerprint = session.host_key_hash(HashType::Sha256).map(fingerprint).unwrap_or_default();
+    let host = if profile.port == 22 { profile.host.clone() } else { format!("[{}]:{}", profile.host, profile.port) };
+
+    let known_hosts_path = paths::home_dir()?.join(".ssh").join("known_hosts");
+    let mut known_hosts = session.known_hosts()?;
+    if known_hosts_path.exists() {
+        known_hosts.read_file(&known_hosts_path, KnownHostFileKind::OpenSSH)?;
+    }
+    match known_hosts.check_port(&profile.host, profile.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(SshError::HostKeyMismatch { host, fingerprint }),
+        CheckResult::NotFound | CheckResult::Failure if !accept => Err(SshError::UnknownHostKey { host, fingerprint }),
+        CheckResult::NotFound | CheckResult::Failure => {
+            // 只序列化新增的这一条，不重写整个文件 (libssh2 无法解析的 @cert-authority、注释等会丢失)
+            let mut added = session.known_hosts()?;
+            added.add(&host, key, "added by zyma", key_type.into())?;
+            let entry = added.hosts()?.pop().ok_or_else(|| "Failed to record host key".to_string())?;
+            let line = added.write_string(&entry, KnownHostFileKind::OpenSSH)?;
+            append_known_host(&known_hosts_path, line.trim_end())?;
+            Ok(())
+        }
+    }
+}
+
+/// 向 known_hosts 追加一行 (原文件不以换行结尾时先补一个换行)
+pub fn append_known_host(path: &Path, line: &str) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let needs_newline = std::fs::read(path).map(|b| b.last().is_some_and(|c| *c != b'\n')).unwrap_or(false);
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    let prefix = if needs_newline { "\n" } else { "" };
+    file.write_all(format!("{}{}\n", prefix, line).as_bytes())
+}
+
+fn authenticate(session: &Session, profile: &SshProfile, credentials: &SshCredentials) -> Result<(), SshError> {
+    let user = profile.username.as_str();
+    let auth_required = || SshError::AuthRequired { profile: profile.id.clone(), method: profile.auth, provider: profile.auth_provider.clone() };
+    let result = match profile.auth {
+        SshAuthMethod::Password => match credentials.password.as_deref() {
+            Some(password) => session.userauth_password(user, password),
+            None => return Err(auth_required()),
+        },
+        SshAuthMethod::Key => {
+            let key_path = profile.key_path.as_deref().filter(|p| !p.is_empty())
+                .ok_or_else(|| format!("No key file configured for {}", profile.id))?;
+            session.userauth_pubkey_file(user, None, &expand_home(key_path)?, credentials.passphrase.as_deref())
+        }
+        SshAuthMethod::Agent => session.userauth_agent(user),
+    };
+    if result.is_ok() && session.authenticated() {
+        return Ok(());
+    }
+    // 私钥可能带口令，未提供时交给前端询问
+    if profile.auth == SshAuthMethod::Key && credentials.passphrase.is_none() {
+        return Err(auth_required());
+    }
+    Err(format!("Authentication failed for {}@{}", user, profile.host).into())
+}
+
+fn to_file_stat(stat: &ssh2::FileStat, attrs: FileAttributes) -> FileStat {
+    let size = stat.size.unwrap_or(0);
+    let file_type = if attrs.broken { "symlink" } else if stat.is_dir() { "dir" } else { "file" };
+    FileStat {
+        file_type: file_type.to_string(),
+        size,
+        mtime: stat.mtime.unwrap_or(0),
+        is_large: largefile::is_large(size),
+        attrs,
+    }
+}
+
+/// 由 lstat 结果生成扩展属性，返回的 stat 跟随链接 (失效的链接返回链接本身的 stat)
+fn inspect(sftp: &Sftp, path: &Path, link_stat: ssh2::FileStat) -> (ssh2::FileStat, FileAttributes) {
+    let is_symlink = link_stat.file_type().is_symlink();
+    let (stat, broken, target) = if is_symlink {
+        let target = sftp.readlink(path).ok().map(|t| t.to_string_lossy().to_string());
+        match sftp.stat(path) {
+            Ok(s) => (s, false, target),
+            Err(_) => (link_stat, true, target),
+        }
+    } else {
+        (link_stat, false, None)
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mode = stat.perm.map(|m| m & 0o7777);
+    let attrs = FileAttributes {
+        is_symlink,
+        target,
+        broken,
+        mode,
+        // SFTP 不提供当前用户的有效权限，按是否有任何写权限位近似
+        readonly: mode.is_some_and(|m| m & 0o222 == 0),
+        hidden: name.starts_with('.'),
+        created: None,
+    };
+    (stat, attrs)
+}
+
+const SSH_FX_FAILURE: i32 = 4;
+const SSH_FX_OP_UNSUPPORTED: i32 = 8;
+
+/// 服务器不支持覆盖式重命名 (posix-rename 扩展) 时返回的 SFTP 状态码
+fn rename_unsupported(e: &ssh2::Error) -> bool {
+    matches!(e.code(), ssh2::ErrorCode::SFTP(SSH_FX_FAILURE) | ssh2::ErrorCode::SFTP(SSH_FX_OP_UNSUPPORTED))
+}
+
+fn set_perm(sftp: &Sftp, path: &Path, mode: u32) -> Result<(), FsError> {
+    let stat = ssh2::FileStat { size: None, uid: None, gid: None, perm: Some(mode), atime: None, mtime: None };
+    sftp.setstat(path, stat).map_err(sftp_error)
+}
+
+fn remove_recursive(sftp: &Sftp, path: &Path) -> Result<(), FsError> {
+    let stat = sftp.lstat(path).map_err(sftp_error)?;
+    if !stat.is_dir() {
+        return sftp.unlink(path).map_err(sftp_error);
+    }
+    for (child, _) in sftp.readdir(path).map_err(sftp_error)? {
+        remove_recursive(sftp, &child)?;
+    }
+    sftp.rmdir(path).map_err(sftp_error)
+}
+
+#[async_trait]
+impl FileSystem for SftpFileSystem {
+    fn get_cwd(&self) -> String {
+        "/".to_string()
+    }
+
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> {
+        Err(format!("Cannot open a remote path as workspace: {}", path).into())
+    }
+
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> {
+        self.run(path, |conn, dir| {
+            let entries = conn.sftp.readdir(dir).map_err(sftp_error)?;
+            let mut items: Vec<FileItem> = entries.into_iter()
+                .filter_map(|(p, stat)| {
+                    let name = p.file_name()?.to_string_lossy().to_string();
+                    if name == "." || name == ".." { return None; }
+                    let full = dir.join(&name);
+                    let (stat, attrs) = inspect(&conn.sftp, &full, stat);
+                    Some(FileItem {
+                        name,
+                        path: format!("//{}{}", conn.info.id, full.to_string_lossy()),
+                        is_dir: !attrs.broken && stat.is_dir(),
+                        attrs,
+                    })
+                })
+                .collect();
+            items.sort_by(|a, b| {
+                if a.is_dir != b.is_dir { b.is_dir.cmp(&a.is_dir) }
+                else { a.name.cmp(&b.name) }
+            });
+            Ok(items)
+        }).await
+    }
+
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        self.run(path, |conn, p| {
+            let mut file = conn.sftp.open(p).map_err(sftp_error)?;
+            let mut buf = Vec::new();
+            file.read_to_end(&mut buf)?;
+            Ok(buf)
+        }).await
+    }
+
+    fn supports_range_reads(&self, _path: &str) -> bool {
+        true
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        self.run(path, move |conn, p| {
+            let mut file = conn.sftp.open(p).map_err(sftp_error)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut buf = Vec::with_capacity(length.min(largefile::LARGE_FILE_THRESHOLD as usize));
+            file.take(length as u64).read_to_end(&mut buf)?;
+            Ok(buf)
+        }).await
+    }
+
+    /// 先写入同目录下的临时文件再重命名覆盖，避免连接中断时留下半截文件
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        let data = data.to_vec();
+        self.run(path, move |conn, p| {
+            if p.file_name().is_none() {
+                return Err(format!("Invalid file path: {}", p.display()).into());
+            }
+            // 临时名带进程号与时间戳，多个窗口或会话同时保存同一文件时互不覆盖
+            let tmp = PathBuf::from(transfer::sibling_temp(&p.to_string_lossy(), "tmp"));
+            // 保留原文件的权限位
+            let mode = conn.sftp.stat(p).ok().and_then(|s| s.perm).map(|m| (m & 0o7777) as i32).unwrap_or(0o644);
+            let flags = OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE;
+            {
+                let mut file = conn.sftp.open_mode(&tmp, flags, mode, OpenType::File).map_err(sftp_error)?;
+                if let Err(e) = file.write_all(&data).and_then(|_| file.flush()) {
+                    drop(file);
+                    let _ = conn.sftp.unlink(&tmp);
+                    return Err(e.into());
+                }
+            }
+            let overwrite = RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE;
+            let e = match conn.sftp.rename(&tmp, p, Some(overwrite)) {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            // 权限不足等错误直接失败；只有服务器不支持覆盖式重命名时才回退
+            if !rename_unsupported(&e) || conn.sftp.lstat(p).is_err() {
+                let _ = conn.sftp.unlink(&tmp);
+                return Err(sftp_error(e));
+            }
+            // 回退：原文件先改名为备份，替换成功后再删除，失败时改回
+            let backup = PathBuf::from(transfer::sibling_temp(&p.to_string_lossy(), "bak"));
+            if let Err(e) = conn.sftp.rename(p, &backup, None) {
+                let _ = conn.sftp.unlink(&tmp);
+                return Err(sftp_error(e));
+            }
+            match conn.sftp.rename(&tmp, p, None) {
+                Ok(()) => {
+                    let _ = conn.sftp.unlink(&backup);
+                    Ok(())
+                }
+                Err(e) => {
+                    let _ = conn.sftp.rename(&backup, p, None);
+                    let _ = conn.sftp.unlink(&tmp);
+                    Err(sftp_error(e))
+                }
+            }
+        }).await
+    }
+
+    async fn write_range(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        let data = data.to_vec();
+        self.run(path, move |conn, p| {
+            let size = conn.sftp.stat(p).map_err(sftp_error)?.size.unwrap_or(0);
+            if offset > size {
+                return Err(format!("Offset {} is beyond the end of file ({} bytes)", offset, size).into());
+            }
+            let mut file = conn.sftp.open_mode(p, OpenFlags::WRITE, 0o644, OpenType::File).map_err(sftp_error)?;
+            file.seek(SeekFrom::Start(offset))?;
+            file.write_all(&data)?;
+            file.flush()?;
+            Ok(())
+        }).await
+    }
+
+    async fn create_file(&self, path: &str) -> Result<(), FsError> {
+        self.run(path, |conn, p| conn.sftp.create(p).map(|_| ()).map_err(sftp_error)).await
+    }
+
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        self.run(path, |conn, p| {
+            // 与本地实现一致，逐级创建缺失的父目录
+            let mut missing: Vec<&Path> = p.ancestors()
+                .take_while(|a| !a.as_os_str().is_empty() && conn.sftp.stat(a).is_err())
+                .collect();
+            missing.reverse();
+            for dir in missing {
+                conn.sftp.mkdir(dir, 0o755).map_err(sftp_error)?;
+            }
+            Ok(())
+        }).await
+    }
+
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> {
+        self.run(path, |conn, p| remove_recursive(&conn.sftp, p)).await
+    }
+
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        let (to_conn, to_remote) = self.resolve(to)?;
+        self.run(from, move |conn, p| {
+            if conn.info.id != to_conn.info.id {
+                return Err(format!("Cannot rename across connections: {} -> {}", conn.info.id, to_conn.info.id).into());
+            }
+            conn.sftp.rename(p, Path::new(&to_remote), None).map_err(sftp_error)
+        }).await
+    }
+
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> {
+        self.run(path, |conn, p| {
+            let link_stat = conn.sftp.lstat(p).map_err(sftp_error)?;
+            let (stat, attrs) = inspect(&conn.sftp, p, link_stat);
+            Ok(to_file_stat(&stat, attrs))
+        }).await
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> Result<(), FsError> {
+        self.run(path, move |conn, p| set_perm(&conn.sftp, p, mode & 0o7777)).await
+    }
+
+    async fn set_readonly(&self, path: &str, readonly: bool) -> Result<(), FsError> {
+        self.run(path, move |conn, p| {
+            let mode = conn.sftp.stat(p).map_err(sftp_error)?.perm.unwrap_or(0o644) & 0o7777;
+            set_perm(&conn.sftp, p, if readonly { mode & !0o222 } else { mode | 0o200 })
+        }).await
+    }
+
+    /// SFTP 无法查询有效权限，按属主写权限位近似
+    async fn is_writable(&self, path: &str) -> Result<bool, FsError> {
+        self.run(path, |conn, p| {
+            Ok(match conn.sftp.stat(p) {
+                Ok(stat) => stat.perm.map_or(true, |m| m & 0o200 != 0),
+                Err(_) => true,
+            })
+        }).await
+    }
+
+    async fn read_link(&self, path: &str) -> Result<String, FsError> {
+        self.run(path, |conn, p| {
+            conn.sftp.readlink(p).map(|t| t.to_string_lossy().to_string()).map_err(sftp_error)
+        }).await
+    }
+
+    async fn real_path(&self, path: &str) -> Result<String, FsError> {
+        self.run(path, |conn, p| {
+            let real = conn.sftp.realpath(p).map_err(sftp_error)?;
+            Ok(format!("//{}{}", conn.info.id, real.to_string_lossy()))
+        }).await
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::services::mount;
+use crate::services::vfs::{normalize_path, FileSystem, FsError};
+
+/// 目标已存在时的处理策略
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// 替换已有目标 (新内容完整写入后才替换)
+    Overwrite,
+    /// 跳过，不做任何修改
+    Skip,
+    /// 自动改名为 "name copy.ext"、"name copy 2.ext" ...
+    #[default]
+    Rename,
+}
+
+/// 复制 / 移动的进度 (按文件计数)
+#[derive(Serialize, Debug, Clone)]
+pub struct TransferProgress {
+    pub op_id: String,
+    pub done: usize,
+    pub total: usize,
+    pub current: String,
+}
+
+/// 进度回调
+pub type ProgressFn<'a> = &'a (dyn Fn(TransferProgress) + Send + Sync);
+
+/// 冲突处理后的写入位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// 最终位置
+    pub target: String,
+    /// 覆盖已有目标时先写入同目录下的临时位置，完成后再与目标交换 (见 commit)
+    pub staging: Option<String>,
+}
+
+impl Placement {
+    /// 复制或移动时实际写入的位置
+    pub fn write_path(&self) -> &str {
+        self.staging.as_deref().unwrap_or(&self.target)
+    }
+}
+
+/// 按策略确定最终写入位置，返回 None 表示跳过
+/// 覆盖时不会提前删除已有目标，新内容完整写入后才由 commit 替换
+/// 源与目标互相包含时不允许覆盖 (否则会替换掉源本身)
+pub async fn resolve_target<F: FileSystem + ?Sized>(fs: &F, from: &str, to: &str, policy: ConflictPolicy) -> Result<Option<Placement>, FsError> {
+    let existing = match fs.stat(to).await {
+        Ok(stat) => stat,
+        Err(_) => return Ok(Some(Placement { target: to.to_string(), staging: None })),
+    };
+    match policy {
+        ConflictPolicy::Skip => Ok(None),
+        ConflictPolicy::Overwrite => {
+            if is_within_real(fs, from, to).await || is_within_real(fs, to, from).await {
+                return Err(format!("Cannot overwrite a directory containing the source: {}", to).into());
+            }
+            Ok(Some(Placement { target: to.to_string(), staging: Some(sibling_temp(to, "new")) }))
+        }
+        ConflictPolicy::Rename => {
+            for n in 1.. {
+                let candidate = copy_name(to, n, existing.file_type == "dir");
+                if fs.stat(&candidate).await.is_err() {
+                    return Ok(Some(Placement { target: candidate, staging: None }));
+                }
+            }
+            unreachable!()
+        }
+    }
+}
+
+/// 用暂存的内容替换目标：旧目标先改名为同目录下的备份，换入成功后再删除备份，换入失败时还原旧目标
+pub async fn commit<F: FileSystem + ?Sized>(fs: &F, placement: &Placement) -> Result<(), FsError> {
+    let staging = match &placement.staging {
+        Some(staging) => staging,
+        None => return Ok(()),
+    };
+    let backup = sibling_temp(&placement.target, "old");
+    fs.rename_item(&placement.target, &backup).await?;
+    if let Err(e) = fs.rename_item(staging, &placement.target).await {
+        let _ = fs.rename_item(&backup, &placement.target).await;
+        return Err(e);
+    }
+    if let Err(e) = fs.remove_item(&backup).await {
+        log::warn!("Failed to remove replaced item {}: {}", backup, e);
+    }
+    Ok(())
+}
+
+/// 递归复制 (仅依赖 FileSystem 的基础操作，任何实现均可复用)
+pub async fn copy_tree<F: FileSystem + ?Sized>(fs: &F, from: &str, to: &str, op_id: &str, progress: ProgressFn<'_>) -> Result<(), FsError> {
+    // 先完整遍历，得到文件总数用于进度展示
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+    if fs.stat(from).await?.file_type == "dir" {
+        // chain 为从根到当前目录经过的真实路径，用于发现指回祖先目录的符号链接
+        // 真实路径优先由实现解析 (链接目标本身经过其它链接时字面比较会漏判)，不支持时按字面解析
+        let root = fs.real_path(from).await.unwrap_or_else(|_| from.to_string());
+        let mut pending = vec![(from.to_string(), to.to_string(), vec![root])];
+        while let Some((src, dst, chain)) = pending.pop() {
+            let real_dir = chain.last().cloned().unwrap_or_default();
+            for item in fs.read_dir(&src).await? {
+                let child_dst = join(&dst, &item.name);
+                // 失效的链接无法复制内容，跳过
+                if item.attrs.broken {
+                    continue;
+                }
+                if !item.is_dir {
+                    files.push((item.path, child_dst));
+                    continue;
+                }
+                let real = match (&item.attrs.is_symlink, &item.attrs.target) {
+                    (true, target) => match fs.real_path(&item.path).await {
+                        Ok(real) => real,
+                        Err(_) => match target {
+                            Some(target) => resolve_link(&real_dir, target),
+                            None => join(&real_dir, &item.name),
+                        },
+                    },
+                    _ => join(&real_dir, &item.name),
+                };
+                // 链接指向链路上的某个目录 (自身或祖先) 时跟随会无限递归
+                if item.attrs.is_symlink && chain.iter().any(|c| is_within(c, &real)) {
+                    continue;
+                }
+                let mut child_chain = chain.clone();
+                child_chain.push(real);
+                pending.push((item.path, child_dst, child_chain));
+            }
+            dirs.push(dst);
+        }
+    } else {
+        files.push((from.to_string(), to.to_string()));
+    }
+
+    // 父目录总是先于子目录入栈，按顺序创建即可
+    for dir in &dirs {
+        fs.create_dir(dir).await?;
+    }
+    let total = files.len();
+    for (done, (src, dst)) in files.iter().enumerate() {
+        fs.copy_file(src, dst).await?;
+        progress(TransferProgress { op_id: op_id.to_string(), done: done + 1, total, current: dst.clone() });
+    }
+    Ok(())
+}
+
+/// 第 n 个副本的名称：`a.txt` -> `a copy.txt` / `a copy 2.txt` (目录名中的点不视为扩展名)
+fn copy_name(path: &str, n: usize, is_dir: bool) -> String {
+    let p = Path::new(path);
+    let suffix = if n == 1 { " copy".to_string() } else { format!(" copy {}", n) };
+    let name = match (p.file_stem(), p.extension()) {
+        (Some(stem), Some(ext)) if !is_dir => format!("{}{}.{}", stem.to_string_lossy(), suffix, ext.to_string_lossy()),
+        _ => format!("{}{}", p.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(), suffix),
+    };
+    match p.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => join(&parent.to_string_lossy(), &name),
+        _ => name,
+    }
+}
+
+/// 同目录下的隐藏临时名称 (`.name.zyma-<tag>-<pid>-<nanos>`)
+pub(crate) fn sibling_temp(path: &str, tag: &str) -> String {
+    let p = Path::new(path);
+    let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    let temp = format!(".{}.zyma-{}-{}-{}", name, tag, std::process::id(), nanos);
+    match p.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => join(&parent.to_string_lossy(), &temp),
+        _ => temp,
+    }
+}
+
+fn join(dir: &str, name: &str) -> String {
+    Path::new(dir).join(name).to_string_lossy().to_string().replace("\\", "/")
+}
+
+// scheme 与连接前缀 (`sftp://id`) 的长度，本地路径为 0
+fn prefix_len(path: &str) -> usize {
+    match mount::scheme_of(path) {
+        Some(scheme) => {
+            let rest = &path[scheme.len() + 1..];
+            scheme.len() + 1 + rest.strip_prefix("//").map(|r| 2 + r.find('/').unwrap_or(r.len())).unwrap_or(0)
+        }
+        None => 0,
+    }
+}
+
+/// 按链接所在目录解析链接目标；绝对目标保留 scheme 与连接前缀 (`sftp://id`)
+fn resolve_link(dir: &str, target: &str) -> String {
+    let (prefix, local) = dir.split_at(prefix_len(dir));
+    let resolved = normalize_path(&Path::new(local).join(target));
+    format!("{}{}", prefix, resolved.to_string_lossy().replace("\\", "/"))
+}
+
+/// target 是否位于 base 之内 (含相等)，按字面比较，两者需已解析为真实路径
+pub fn is_within(target: &str, base: &str) -> bool {
+    Path::new(target).starts_with(Path::new(base))
+}
+
+/// 按真实路径判断 target 是否位于 base 之内：`a/../a/b`、经过符号链接或大小写不同的写法都能识别
+pub async fn is_within_real<F: FileSystem + ?Sized>(fs: &F, target: &str, base: &str) -> bool {
+    is_within(&real_or_nearest(fs, target).await, &real_or_nearest(fs, base).await)
+}
+
+// 由实现解析真实路径；路径尚不存在时解析最近的已存在祖先再拼回剩余部分，
+// 实现不支持解析时退回消去 `.` 与 `..` 后的路径
+async fn real_or_nearest<F: FileSystem + ?Sized>(fs: &F, path: &str) -> String {
+    let absolute = fs.absolute_path(path);
+    let (prefix, local) = absolute.split_at(prefix_len(&absolute));
+    let mut cur = normalize_path(Path::new(local));
+    let mut tail = Vec::new();
+    loop {
+        let candidate = format!("{}{}", prefix, cur.to_string_lossy().replace("\\", "/"));
+        let base = match fs.real_path(&candidate).await {
+            Ok(real) => real,
+            Err(_) => match (cur.parent(), cur.file_name()) {
+                (Some(parent), Some(name)) => {
+                    tail.push(name.to_string_lossy().to_string());
+                    cur = parent.to_path_buf();
+                    continue;
+                }
+                _ => candidate,
+            },
+        };
+        return tail.iter().rev().fold(base, |dir, name| join(&dir, name));
+    }
+}
@@ -0,0 +1,418 @@
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use tokio::fs;
+use tokio::sync::Mutex;
+
+/// 由 Zyma 移入回收站的条目 (记录于数据目录下的 trash.json)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrashEntry {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    // 回收站中的实际位置 (仅 Linux 可用，其他平台由系统回收站管理)
+    pub trashed_path: Option<String>,
+    pub deleted_at: u64,
+    pub is_dir: bool,
+}
+
+// 串行化对登记文件的读-改-写
+static REGISTRY_LOCK: Mutex<()> = Mutex::const_new(());
+
+fn registry_path() -> PathBuf {
+    crate::services::paths::dirs().data_path("trash.json")
+}
+
+// 登记文件无法解析时另存为 trash.json.corrupt 再从空表开始，避免记录被下一次保存覆盖而丢失
+async fn load_registry() -> Vec<TrashEntry> {
+    let path = registry_path();
+    let content = match fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    match serde_json::from_str(&content) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let backup = crate::services::config_file::corrupt_path(&path);
+            match fs::write(&backup, &content).await {
+                Ok(()) => log::warn!("Trash registry is corrupted ({}), original kept at {}", e, backup.display()),
+                Err(err) => log::error!("Failed to keep a copy of the corrupted trash registry: {}", err),
+            }
+            Vec::new()
+        }
+    }
+}
+
+async fn save_registry(entries: &[TrashEntry]) -> Result<(), String> {
+    let path = registry_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await.map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
+    fs::write(path, content).await.map_err(|e| e.to_string())
+}
+
+/// 将文件或目录移入系统回收站并登记
+pub async fn move_to_trash(path: &Path) -> Result<TrashEntry, String> {
+    let metadata = fs::symlink_metadata(path).await.map_err(|e| e.to_string())?;
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Cannot trash path: {}", path.display()))?;
+    let deleted_at = now_secs();
+
+    let trashed_path = platform::trash(path, &name, deleted_at).await?;
+    let _guard = REGISTRY_LOCK.lock().await;
+    let mut entries = load_registry().await;
+    // 回收站中的文件名只在各自的回收站内唯一 (主目录与各挂载点的回收站可能重名)
+    let id = trashed_path.as_ref()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .filter(|id| !entries.iter().any(|e| &e.id == id))
+        .unwrap_or_else(|| format!("{}-{}", deleted_at, name));
+    let entry = TrashEntry {
+        id,
+        name,
+        original_path: path.to_string_lossy().to_string().replace("\\", "/"),
+        trashed_path: trashed_path.map(|p| p.to_string_lossy().to_string()),
+        deleted_at,
+        is_dir: metadata.is_dir(),
+    };
+    entries.push(entry.clone());
+    save_registry(&entries).await?;
+    Ok(entry)
+}
+
+/// 列出仍在回收站中的 Zyma 条目 (自动剔除已在外部被清空的记录)
+pub async fn list() -> Result<Vec<TrashEntry>, String> {
+    let _guard = REGISTRY_LOCK.lock().await;
+    let entries = load_registry().await;
+    let mut alive = Vec::with_capacity(entries.len());
+    for e in entries.iter() {
+        if platform::still_trashed(e).await {
+            alive.push(e.clone());
+        }
+    }
+    if alive.len() != entries.len() {
+        save_registry(&alive).await?;
+    }
+    alive.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+    Ok(alive)
+}
+
+/// 还原条目到原位置，返回还原后的路径
+pub async fn restore(id: &str) -> Result<String, String> {
+    let _guard = REGISTRY_LOCK.lock().await;
+    let mut entries = load_registry().await;
+    let idx = entries.iter().position(|e| e.id == id)
+        .ok_or_else(|| format!("Trash entry not found: {}", id))?;
+    let entry = entries[idx].clone();
+
+    if fs::symlink_metadata(&entry.original_path).await.is_ok() {
+        return Err(format!("Cannot restore, target already exists: {}", entry.original_path));
+    }
+    platform::restore(&entry).await?;
+
+    entries.remove(idx);
+    save_registry(&entries).await?;
+    Ok(entry.original_path)
+}
+
+/// 永久清除指定条目 (ids 为空时清除全部 Zyma 条目)
+pub async fn empty(ids: Option<Vec<String>>) -> Result<usize, String> {
+    let _guard = REGISTRY_LOCK.lock().await;
+    let entries = load_registry().await;
+    let (to_purge, keep): (Vec<_>, Vec<_>) = entries.into_iter()
+        .partition(|e| match &ids {
+            Some(ids) => ids.contains(&e.id),
+            None => true,
+        });
+
+    let mut purged = 0;
+    let mut remaining = keep;
+    for entry in to_purge {
+        match platform::purge(&entry).await {
+            Ok(()) => purged += 1,
+            Err(_) => remaining.push(entry),
+        }
+    }
+    save_registry(&remaining).await?;
+    Ok(purged)
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// freedesktop.org Trash 规范实现 (~/.local/share/Trash)
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use std::io::Write;
+    use crate::services::transfer;
+    use crate::services::vfs::{is_cross_device, LocalFileSystem};
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    // XDG 规范要求忽略相对路径
+    fn trash_dir() -> Result<PathBuf, String> {
+        match std::env::var("XDG_DATA_HOME").map(PathBuf::from) {
+            Ok(dir) if dir.is_absolute() => Ok(dir.join("Trash")),
+            _ => Ok(crate::services::paths::home_dir()?.join(".local").join("share").join("Trash")),
+        }
+    }
+
+    pub async fn trash(path: &Path, name: &str, deleted_at: u64) -> Result<Option<PathBuf>, String> {
+        let abs = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir().map_err(|e| e.to_string())?.join(path)
+        };
+        let (root, topdir) = {
+            let abs = abs.clone();
+            tokio::task::spawn_blocking(move || trash_root_for(&abs)).await.map_err(|e| e.to_string())??
+        };
+        let files_dir = root.join("files");
+        let info_dir = root.join("info");
+        fs::create_dir_all(&files_dir).await.map_err(|e| e.to_string())?;
+        fs::create_dir_all(&info_dir).await.map_err(|e| e.to_string())?;
+
+        // 挂载点回收站中的 Path 相对于挂载点，主目录回收站中为绝对路径
+        let info_target = match &topdir {
+            Some(top) => abs.strip_prefix(top).unwrap_or(&abs).to_path_buf(),
+            None => abs.clone(),
+        };
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            percent_encode(&info_target.to_string_lossy()),
+            format_deletion_date(deleted_at)
+        );
+
+        // 规范要求先以 O_EXCL 创建 .trashinfo 以占用名称
+        let mut n = 1;
+        let (trash_name, info_path) = loop {
+            let candidate = if n == 1 { name.to_string() } else { format!("{}.{}", name, n) };
+            let info_path = info_dir.join(format!("{}.trashinfo", candidate));
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+                Ok(mut f) => {
+                    f.write_all(info.as_bytes()).map_err(|e| e.to_string())?;
+                    break (candidate, info_path);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+
+        let target = files_dir.join(&trash_name);
+        if let Err(e) = move_path(&abs, &target).await {
+            let _ = fs::remove_file(&info_path).await;
+            return Err(e);
+        }
+        Ok(Some(target))
+    }
+
+    pub async fn still_trashed(entry: &TrashEntry) -> bool {
+        match &entry.trashed_path {
+            Some(p) => fs::symlink_metadata(p).await.is_ok(),
+            None => false,
+        }
+    }
+
+    pub async fn restore(entry: &TrashEntry) -> Result<(), String> {
+        let trashed = entry.trashed_path.as_ref()
+            .ok_or_else(|| "Trash entry has no location".to_string())?;
+        if let Some(parent) = Path::new(&entry.original_path).parent() {
+            fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+        move_path(Path::new(trashed), Path::new(&entry.original_path)).await?;
+        if let Ok(info) = info_path_for(entry) {
+            let _ = fs::remove_file(info).await;
+        }
+        Ok(())
+    }
+
+    pub async fn purge(entry: &TrashEntry) -> Result<(), String> {
+        if let Some(trashed) = &entry.trashed_path {
+            let p = Path::new(trashed);
+            match fs::symlink_metadata(p).await {
+                Ok(m) if m.is_dir() => fs::remove_dir_all(p).await.map_err(|e| e.to_string())?,
+                Ok(_) => fs::remove_file(p).await.map_err(|e| e.to_string())?,
+                Err(_) => {}
+            }
+        }
+        if let Ok(info) = info_path_for(entry) {
+            let _ = fs::remove_file(info).await;
+        }
+        Ok(())
+    }
+
+    // trashinfo 与条目位于同一个回收站 (<root>/files/<name> 对应 <root>/info/<name>.trashinfo)
+    fn info_path_for(entry: &TrashEntry) -> Result<PathBuf, String> {
+        let trashed = entry.trashed_path.as_deref().map(Path::new);
+        let root = match trashed.and_then(|p| p.parent()).and_then(Path::parent) {
+            Some(root) => root.to_path_buf(),
+            None => trash_dir()?,
+        };
+        let name = trashed.and_then(|p| p.file_name()).map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| entry.id.clone());
+        Ok(root.join("info").join(format!("{}.trashinfo", name)))
+    }
+
+    /// 选择回收站，返回 (回收站目录, 挂载点)
+    /// 与主目录回收站位于同一文件系统时使用主目录回收站 (挂载点为 None)；否则按规范使用该文件系统
+    /// 挂载点下的 `.Trash/$uid` (需为带粘滞位的真实目录) 或 `.Trash-$uid`，避免跨设备复制整个目录树。
+    /// 都无法使用时退回主目录回收站，由 move_path 复制后删除
+    fn trash_root_for(abs: &Path) -> Result<(PathBuf, Option<PathBuf>), String> {
+        let home_trash = trash_dir()?;
+        let _ = std::fs::create_dir_all(&home_trash);
+        let (item_dev, home_dev) = match (std::fs::symlink_metadata(abs), std::fs::metadata(&home_trash)) {
+            (Ok(item), Ok(home)) => (item.dev(), home.dev()),
+            _ => return Ok((home_trash, None)),
+        };
+        if item_dev == home_dev {
+            return Ok((home_trash, None));
+        }
+        let top = mount_point(abs, item_dev);
+        Ok(match topdir_trash(&top) {
+            Some(root) => (root, Some(top)),
+            None => (home_trash, None),
+        })
+    }
+
+    // 向上查找仍属于同一设备的最高一级目录
+    fn mount_point(path: &Path, dev: u64) -> PathBuf {
+        let mut top = path.to_path_buf();
+        while let Some(parent) = top.parent() {
+            match std::fs::symlink_metadata(parent) {
+                Ok(m) if m.dev() == dev => top = parent.to_path_buf(),
+                _ => break,
+            }
+        }
+        top
+    }
+
+    fn topdir_trash(top: &Path) -> Option<PathBuf> {
+        let uid = unsafe { libc::getuid() };
+        let shared = top.join(".Trash");
+        let shared_ok = std::fs::symlink_metadata(&shared)
+            .map(|m| m.is_dir() && m.permissions().mode() & 0o1000 != 0)
+            .unwrap_or(false);
+        if shared_ok {
+            let dir = shared.join(uid.to_string());
+            if ensure_private_dir(&dir, uid) {
+                return Some(dir);
+            }
+        }
+        let dir = top.join(format!(".Trash-{}", uid));
+        if ensure_private_dir(&dir, uid) { Some(dir) } else { None }
+    }
+
+    // 不存在时以 0700 创建；已存在时必须是属于当前用户的真实目录
+    fn ensure_private_dir(dir: &Path, uid: u32) -> bool {
+        use std::os::unix::fs::DirBuilderExt;
+        if let Err(e) = std::fs::DirBuilder::new().mode(0o700).create(dir) {
+            if e.kind() != std::io::ErrorKind::AlreadyExists {
+                return false;
+            }
+        }
+        std::fs::symlink_metadata(dir).map(|m| m.is_dir() && m.uid() == uid).unwrap_or(false)
+    }
+
+    /// rename，跨设备时退化为复制后删除 (复制与跨目录移动共用 transfer::copy_tree，含符号链接环路的处理)
+    async fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+        match fs::rename(from, to).await {
+            Ok(()) => Ok(()),
+            Err(e) if is_cross_device(&e) => {
+                let local = LocalFileSystem::new(PathBuf::from("/"));
+                transfer::copy_tree(&local, &from.to_string_lossy(), &to.to_string_lossy(), "trash", &|_| {}).await?;
+                match fs::symlink_metadata(from).await {
+                    Ok(m) if m.is_dir() => fs::remove_dir_all(from).await.map_err(|e| e.to_string()),
+                    _ => fs::remove_file(from).await.map_err(|e| e.to_string()),
+                }
+            }
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn percent_encode(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        for b in s.bytes() {
+            match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => out.push(b as char),
+                _ => out.push_str(&format!("%{:02X}", b)),
+            }
+        }
+        out
+    }
+
+    /// 规范要求 YYYY-MM-DDThh:mm:ss (此处使用 UTC)
+    fn format_deletion_date(secs: u64) -> String {
+        let days = (secs / 86400) as i64;
+        let rem = secs % 86400;
+        // Howard Hinnant 的 civil_from_days 算法
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z - era * 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let d = doy - (153 * mp + 2) / 5 + 1;
+        let m = if mp < 10 { mp + 3 } else { mp - 9 };
+        let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", y, m, d, rem / 3600, (rem % 3600) / 60, rem % 60)
+    }
+}
+
+/// 其他平台交给系统回收站 (trash crate)
+/// Windows 上通过回收站接口还原与清除；macOS 的废纸篓没有公开的还原接口，还原与清除需在访达中进行
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::*;
+
+    pub async fn trash(path: &Path, _name: &str, _deleted_at: u64) -> Result<Option<PathBuf>, String> {
+        let p = path.to_path_buf();
+        tokio::task::spawn_blocking(move || trash::delete(&p))
+            .await.map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+        Ok(None)
+    }
+
+    pub async fn still_trashed(entry: &TrashEntry) -> bool {
+        fs::symlink_metadata(&entry.original_path).await.is_err()
+    }
+
+    // 按原路径在回收站中查找，同一路径被多次删除时取删除时间最接近的一项
+    #[cfg(windows)]
+    fn find_item(entry: &TrashEntry) -> Result<trash::TrashItem, String> {
+        trash::os_limited::list().map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|item| item.original_path().to_string_lossy().replace('\\', "/") == entry.original_path)
+            .min_by_key(|item| (item.time_deleted - entry.deleted_at as i64).abs())
+            .ok_or_else(|| format!("Item is no longer in the Recycle Bin: {}", entry.original_path))
+    }
+
+    #[cfg(windows)]
+    pub async fn restore(entry: &TrashEntry) -> Result<(), String> {
+        let entry = entry.clone();
+        tokio::task::spawn_blocking(move || {
+            let item = find_item(&entry)?;
+            trash::os_limited::restore_all([item]).map_err(|e| e.to_string())
+        }).await.map_err(|e| e.to_string())?
+    }
+
+    #[cfg(windows)]
+    pub async fn purge(entry: &TrashEntry) -> Result<(), String> {
+        let entry = entry.clone();
+        tokio::task::spawn_blocking(move || {
+            let item = find_item(&entry)?;
+            trash::os_limited::purge_all([item]).map_err(|e| e.to_string())
+        }).await.map_err(|e| e.to_string())?
+    }
+
+    #[cfg(not(windows))]
+    pub async fn restore(_entry: &TrashEntry) -> Result<(), String> {
+        Err("Restoring from the macOS Trash is not supported; use \"Put Back\" in Finder instead".to_string())
+    }
+
+    #[cfg(not(windows))]
+    pub async fn purge(_entry: &TrashEntry) -> Result<(), String> {
+        Err("Emptying the macOS Trash is not supported; empty it from Finder instead".to_string())
+    }
+}
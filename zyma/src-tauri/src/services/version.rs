@@ -0,0 +1,44 @@
+use sha2::{Digest, Sha256};
+use crate::services::vfs::FileStat;
+
+/// 文件版本标记 (mtime + size + 内容哈希)，用于检测外部修改
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileVersion {
+    pub mtime: u64,
+    pub size: u64,
+    pub hash: String,
+}
+
+impl FileVersion {
+    pub fn compute(stat: &FileStat, bytes: &[u8]) -> Self {
+        Self {
+            mtime: stat.mtime,
+            size: bytes.len() as u64,
+            hash: content_hash(bytes),
+        }
+    }
+
+    /// 序列化为前端透传的不透明字符串
+    pub fn token(&self) -> String {
+        format!("{}-{}-{}", self.mtime, self.size, self.hash)
+    }
+
+    pub fn parse(token: &str) -> Option<Self> {
+        let mut parts = token.splitn(3, '-');
+        let mtime = parts.next()?.parse().ok()?;
+        let size = parts.next()?.parse().ok()?;
+        let hash = parts.next()?.to_string();
+        Some(Self { mtime, size, hash })
+    }
+
+    /// 内容一致即视为同一版本 (仅 mtime 变化，如 touch，不算冲突)
+    pub fn same_content(&self, other: &Self) -> bool {
+        self.size == other.size && self.hash == other.hash
+    }
+}
+
+/// 截取 SHA-256 前 16 个十六进制字符，足以区分同一文件的不同版本
+pub fn content_hash(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    digest.iter().take(8).map(|b| format!("{:02x}", b)).collect()
+}
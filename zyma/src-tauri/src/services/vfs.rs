@@ -1,22 +1,300 @@
 use std::path::{Path, PathBuf, Component};
-use std::sync::Mutex;
-use crate::models::{FileItem, FileReadResponse};
+use std::collections::HashSet;
+use std::sync::{Mutex, RwLock};
+use crate::models::{AppSettings, FileAttributes, FileItem, FileReadResponse, WorkspaceFolder, WriteOptions};
+use crate::services::{binary, encoding, largefile, permissions, transfer, trash, workspace};
+use crate::services::transfer::{ConflictPolicy, ProgressFn};
+use crate::services::sandbox::{self, Access, SandboxMode};
+use crate::services::atomic::{atomic_write, BackupPolicy};
+use crate::services::version::FileVersion;
 use tokio::fs;
 use async_trait::async_trait;
 
 /// 异步核心文件系统接口
 #[async_trait]
 pub trait FileSystem: Send + Sync {
-    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, String>;
-    async fn read_file(&self, path: &str) -> Result<FileReadResponse, String>;
-    async fn write_file(&self, path: &str, content: &str) -> Result<(), String>;
-    async fn create_file(&self, path: &str) -> Result<(), String>;
-    async fn create_dir(&self, path: &str) -> Result<(), String>;
-    async fn remove_item(&self, path: &str) -> Result<(), String>;
-    async fn rename_item(&self, from: &str, to: &str) -> Result<(), String>;
-    async fn stat(&self, path: &str) -> Result<FileStat, String>;
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError>;
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError>;
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError>;
+    async fn create_file(&self, path: &str) -> Result<(), FsError>;
+    async fn create_dir(&self, path: &str) -> Result<(), FsError>;
+    async fn remove_item(&self, path: &str) -> Result<(), FsError>;
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError>;
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError>;
     fn get_cwd(&self) -> String;
-    fn set_cwd(&self, path: &str) -> Result<(), String>;
+    fn set_cwd(&self, path: &str) -> Result<(), FsError>;
+
+    /// 工作区的全部根目录，第一个为主根目录 (即 get_cwd)
+    fn roots(&self) -> Vec<WorkspaceFolder> {
+        vec![workspace::new_folder(&self.get_cwd(), None)]
+    }
+
+    /// 切换为多根工作区，不支持多根的实现只使用第一个根目录
+    fn set_roots(&self, roots: Vec<WorkspaceFolder>) -> Result<(), FsError> {
+        let first = roots.first().ok_or_else(|| FsError::from("Workspace has no folders".to_string()))?;
+        self.set_cwd(&first.path)
+    }
+
+    /// 用户配置的沙箱模式 (不支持沙箱的实现视为不限制)
+    fn sandbox_mode(&self) -> SandboxMode {
+        SandboxMode::Unrestricted
+    }
+
+    /// 按给定模式检查路径是否可访问，违规时返回 AccessDenied
+    fn check_access(&self, _path: &str, _access: Access, _mode: SandboxMode) -> Result<(), FsError> {
+        Ok(())
+    }
+
+    /// 插件与 Agent 能否访问该实现 (之后仍按 check_access 检查)
+    /// 默认不允许：实现需自行保证受限调用方不会越界后再开放
+    fn allows_restricted_callers(&self, _path: &str) -> bool {
+        false
+    }
+
+    /// 登记用户显式打开的文件，受限模式下仍可访问
+    fn allow_path(&self, _path: &str) {}
+
+    /// 启动时与设置变化后应用实现相关的配置 (如沙箱模式、保存前的备份策略)
+    fn apply_settings(&self, _settings: &AppSettings) {}
+
+    /// 移入回收站，返回是否真正进入了回收站
+    /// 不支持回收站的实现默认退化为永久删除 (返回 false)
+    async fn trash_item(&self, path: &str) -> Result<bool, FsError> {
+        self.remove_item(path).await.map(|_| false)
+    }
+
+    /// 能否直接读取文件的一部分；默认的 read_range 每次都会整体读入文件，
+    /// 逐块读取的调用方 (行索引、分页) 对不支持的实现应只整体读取一次
+    fn supports_range_reads(&self, _path: &str) -> bool {
+        false
+    }
+
+    /// 读取从 offset 开始的至多 length 个字节 (超出文件末尾时返回较短或空的结果)
+    /// 默认实现会读取整个文件，本地实现使用 seek 只读取所需部分
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        let bytes = self.read_bytes(path).await?;
+        let start = (offset as usize).min(bytes.len());
+        let end = start.saturating_add(length).min(bytes.len());
+        Ok(bytes[start..end].to_vec())
+    }
+
+    /// 从 offset 开始原地覆盖写入 data (offset 等于文件大小时为追加)，不改变其余内容
+    /// 默认实现会重写整个文件，本地实现只写入修改的部分：不经过原子替换、备份与版本检查，
+    /// 写入中途崩溃会留下改了一半的文件。只用于用户明确确认的原地修改，调用方需先校验版本
+    async fn write_range(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        let mut bytes = self.read_bytes(path).await?;
+        crate::services::binary::apply_patch(&mut bytes, offset, data)?;
+        self.write_bytes(path, &bytes).await
+    }
+
+    /// 修改权限位 (Unix mode，如 0o755)，不支持的实现返回错误
+    async fn set_mode(&self, path: &str, _mode: u32) -> Result<(), FsError> {
+        Err(format!("Changing permissions is not supported: {}", path).into())
+    }
+
+    /// 设置或清除只读 (Unix 下为去掉全部写权限 / 恢复属主写权限)
+    async fn set_readonly(&self, path: &str, _readonly: bool) -> Result<(), FsError> {
+        Err(format!("Changing permissions is not supported: {}", path).into())
+    }
+
+    /// 当前用户能否写入该路径 (不存在时检查能否创建)
+    async fn is_writable(&self, _path: &str) -> Result<bool, FsError> {
+        Ok(true)
+    }
+
+    /// 读取符号链接的目标 (不做解析)，不支持链接的实现返回错误
+    async fn read_link(&self, path: &str) -> Result<String, FsError> {
+        Err(format!("Not a symbolic link: {}", path).into())
+    }
+
+    /// 路径的规范形式 (相对路径按工作区解析为绝对路径，并消去 `.` 与 `..`)，不访问磁盘
+    /// 同一文件的不同写法得到相同结果，用作本地历史等按路径索引的键
+    fn absolute_path(&self, path: &str) -> String {
+        path.to_string()
+    }
+
+    /// 解析所有符号链接后的真实路径 (用于判断两个路径是否指向同一位置)，不支持的实现返回错误
+    async fn real_path(&self, path: &str) -> Result<String, FsError> {
+        Err(format!("Resolving real paths is not supported: {}", path).into())
+    }
+
+    /// 复制单个文件 (不处理冲突)，实现可覆盖为更高效的原生复制
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), FsError> {
+        let bytes = self.read_bytes(from).await?;
+        self.write_bytes(to, &bytes).await
+    }
+
+    /// 复制文件或目录 (目录递归复制)，返回最终写入的路径，按策略跳过时返回 None
+    async fn copy_item(&self, from: &str, to: &str, policy: ConflictPolicy, op_id: &str, progress: ProgressFn<'_>) -> Result<Option<String>, FsError> {
+        let placement = match transfer::resolve_target(self, from, to, policy).await? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        if transfer::is_within_real(self, &placement.target, from).await {
+            return Err(format!("Cannot copy a directory into itself: {}", placement.target).into());
+        }
+        let result = match transfer::copy_tree(self, from, placement.write_path(), op_id, progress).await {
+            Ok(()) => transfer::commit(self, &placement).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            // 覆盖失败时已有目标保持不变，只清理暂存的内容
+            if let Some(staging) = &placement.staging {
+                let _ = self.remove_item(staging).await;
+            }
+            return Err(e);
+        }
+        Ok(Some(placement.target))
+    }
+
+    /// 移动文件或目录，只有跨设备或跨文件系统实现 (rename 返回 CrossDevice) 时才退化为复制后删除
+    async fn move_item(&self, from: &str, to: &str, policy: ConflictPolicy, op_id: &str, progress: ProgressFn<'_>) -> Result<Option<String>, FsError> {
+        let placement = match transfer::resolve_target(self, from, to, policy).await? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        if transfer::is_within_real(self, &placement.target, from).await {
+            return Err(format!("Cannot move a directory into itself: {}", placement.target).into());
+        }
+        let dest = placement.write_path();
+        match self.rename_item(from, dest).await {
+            Ok(()) => {
+                if let Err(e) = transfer::commit(self, &placement).await {
+                    // 换入失败时把源放回原处
+                    let _ = self.rename_item(dest, from).await;
+                    return Err(e);
+                }
+            }
+            Err(FsError::CrossDevice { .. }) => {
+                let result = match transfer::copy_tree(self, from, dest, op_id, progress).await {
+                    Ok(()) => transfer::commit(self, &placement).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    // 清理复制了一半的内容，源与已有目标保持不变
+                    let _ = self.remove_item(dest).await;
+                    return Err(e);
+                }
+                self.remove_item(from).await?;
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(Some(placement.target))
+    }
+
+    /// 读取并解码文本 (自动检测编码、BOM 与换行风格)，附带版本标记
+    /// 二进制内容返回 BinaryFile，需改用十六进制视图 (或以指定编码强制打开)
+    async fn read_file(&self, path: &str) -> Result<FileReadResponse, FsError> {
+        let bytes = self.read_bytes(path).await?;
+        if binary::is_binary(&bytes) {
+            return Err(FsError::BinaryFile { path: path.to_string(), size: bytes.len() as u64 });
+        }
+        let mut res = encoding::decode(&bytes);
+        res.version = Some(self.version_of(path, &bytes).await?);
+        Ok(res)
+    }
+
+    /// 根据已读取 (或刚写入) 的内容计算版本标记
+    async fn version_of(&self, path: &str, bytes: &[u8]) -> Result<String, FsError> {
+        let stat = self.stat(path).await?;
+        Ok(FileVersion::compute(&stat, bytes).token())
+    }
+
+    /// 乐观并发检查：磁盘内容与 expected 版本不一致时返回 Conflict (附带当前磁盘内容)
+    async fn check_version(&self, path: &str, expected: &str) -> Result<(), FsError> {
+        if self.stat(path).await.is_err() {
+            // 文件已被外部删除
+            return Err(FsError::Conflict { path: path.to_string(), expected: expected.to_string(), actual: None, current: None });
+        }
+        let bytes = self.read_bytes(path).await?;
+        let actual = self.version_of(path, &bytes).await?;
+        let unchanged = match (FileVersion::parse(expected), FileVersion::parse(&actual)) {
+            (Some(e), Some(a)) => e.same_content(&a),
+            _ => false,
+        };
+        if unchanged {
+            return Ok(());
+        }
+        let mut current = encoding::decode(&bytes);
+        current.version = Some(actual.clone());
+        Err(FsError::Conflict { path: path.to_string(), expected: expected.to_string(), actual: Some(actual), current: Some(Box::new(current)) })
+    }
+
+    /// 按指定编码、BOM 与换行风格写入文本
+    async fn write_file(&self, path: &str, content: &str, options: &WriteOptions) -> Result<(), FsError> {
+        let bytes = encoding::encode(content, options)?;
+        self.write_bytes(path, &bytes).await
+    }
+}
+
+/// 可供前端区分处理的结构化文件系统错误
+/// 普通 IO 错误仍序列化为纯字符串，兼容只按字符串处理错误的调用方
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsError {
+    UnsupportedEncoding { encoding: String },
+    /// count 为无法编码的字符总数，positions 最多只列出前 100 处
+    Unmappable { encoding: String, count: usize, positions: Vec<UnmappableChar> },
+    /// 文件在打开后被外部修改 (或删除)，current 为当前磁盘内容
+    Conflict { path: String, expected: String, actual: Option<String>, current: Option<Box<FileReadResponse>> },
+    /// 沙箱策略拒绝访问
+    AccessDenied { path: String, access: Access, mode: SandboxMode },
+    /// 文件超过大文件阈值，需改用分段读取或显式强制打开
+    FileTooLarge { path: String, size: u64 },
+    /// 文件内容为二进制，不能按文本打开
+    BinaryFile { path: String, size: u64 },
+    /// 当前用户没有写权限，可改用 write_file_elevated 以管理员身份保存
+    NotWritable { path: String },
+    /// 源与目标位于不同设备或不同的文件系统实现，无法直接 rename
+    CrossDevice { from: String, to: String },
+    #[serde(untagged)]
+    Io(String),
+}
+
+/// 无法用目标编码表示的字符位置 (行列均从 1 开始，列按字符计)
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnmappableChar {
+    pub line: usize,
+    pub column: usize,
+    pub character: String,
+}
+
+impl std::fmt::Display for FsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FsError::Io(message) => write!(f, "{}", message),
+            FsError::UnsupportedEncoding { encoding } => write!(f, "Unsupported encoding: {}", encoding),
+            FsError::Unmappable { encoding, count, positions } => match positions.first() {
+                Some(p) => write!(f, "{} character(s) cannot be encoded as {} (first at line {}, column {})", count, encoding, p.line, p.column),
+                None => write!(f, "Content cannot be encoded as {}", encoding),
+            },
+            FsError::Conflict { path, current: None, .. } => write!(f, "File was deleted on disk: {}", path),
+            FsError::Conflict { path, .. } => write!(f, "File was modified on disk: {}", path),
+            FsError::AccessDenied { path, access, mode } => write!(f, "Access denied ({:?} in {:?} mode): {}", access, mode, path),
+            FsError::FileTooLarge { path, size } => write!(f, "File is too large to open in full ({} bytes): {}", size, path),
+            FsError::BinaryFile { path, .. } => write!(f, "File appears to be binary: {}", path),
+            FsError::NotWritable { path } => write!(f, "Permission denied: {}", path),
+            FsError::CrossDevice { from, to } => write!(f, "Cannot rename across file systems: {} -> {}", from, to),
+        }
+    }
+}
+
+impl From<String> for FsError {
+    fn from(message: String) -> Self {
+        FsError::Io(message)
+    }
+}
+
+impl From<std::io::Error> for FsError {
+    fn from(e: std::io::Error) -> Self {
+        FsError::Io(e.to_string())
+    }
+}
+
+impl From<FsError> for String {
+    fn from(e: FsError) -> Self {
+        e.to_string()
+    }
 }
 
 #[derive(serde::Serialize)]
@@ -24,34 +302,67 @@ pub struct FileStat {
     pub file_type: String,
     pub size: u64,
     pub mtime: u64,
+    /// 超过大文件阈值，应使用分段读取 (fs_read_lines / fs_read_range)
+    pub is_large: bool,
+    #[serde(flatten)]
+    pub attrs: FileAttributes,
 }
 
 /// 默认的本地文件系统实现
 pub struct LocalFileSystem {
     root: Mutex<PathBuf>,
+    // 多根工作区的全部根目录 (为空表示单根，仅使用 root)
+    folders: RwLock<Vec<WorkspaceFolder>>,
+    backup: RwLock<BackupPolicy>,
+    sandbox: RwLock<SandboxMode>,
+    // 用户显式打开的文件 (已解析符号链接)
+    opened: RwLock<HashSet<PathBuf>>,
 }
 
 impl LocalFileSystem {
     pub fn new(root: PathBuf) -> Self {
         Self {
             root: Mutex::new(root),
+            folders: RwLock::new(Vec::new()),
+            backup: RwLock::new(BackupPolicy::None),
+            sandbox: RwLock::new(SandboxMode::Unrestricted),
+            opened: RwLock::new(HashSet::new()),
         }
     }
 
-    /// 内部安全检查 (Relaxed for Editor usage)
-    fn validate_path(&self, target: &str) -> Result<PathBuf, String> {
-        let root = self.root.lock().unwrap();
-        let target_path = if Path::new(target).is_absolute() {
-            PathBuf::from(target)
-        } else {
-            root.join(target)
-        };
+    pub fn set_backup_policy(&self, policy: BackupPolicy) {
+        *self.backup.write().unwrap() = policy;
+    }
 
-        let clean_path = normalize_path(&target_path);
-        
-        // Editor Mode: Allow accessing files outside workspace
-        // We trust the user's intent when opening specific files.
-        Ok(clean_path)
+    pub fn set_sandbox_mode(&self, mode: SandboxMode) {
+        *self.sandbox.write().unwrap() = mode;
+    }
+
+    /// 相对路径默认基于主根目录；多根工作区下以 "根名称/..." 开头时基于对应根目录
+    /// 主根目录下存在同名条目时仍以主根目录为准 (如主根目录中的 src/ 与名为 src 的根目录)
+    fn absolute(&self, target: &str) -> PathBuf {
+        let target_path = Path::new(target);
+        if target_path.is_absolute() {
+            return normalize_path(target_path);
+        }
+        let root = self.root.lock().unwrap().clone();
+        let folders = self.folders.read().unwrap();
+        let mut components = target_path.components();
+        if let Some(Component::Normal(first)) = components.next() {
+            if std::fs::symlink_metadata(root.join(first)).is_err() {
+                if let Some(folder) = folders.iter().find(|f| first == f.name.as_str()) {
+                    return normalize_path(&Path::new(&folder.path).join(components.as_path()));
+                }
+            }
+        }
+        normalize_path(&root.join(target_path))
+    }
+
+    /// 内部安全检查：按用户配置的沙箱模式校验 (默认 Unrestricted，与以往行为一致)
+    fn validate_path(&self, target: &str, access: Access) -> Result<PathBuf, FsError> {
+        let mode = self.sandbox_mode();
+        self.check_access(target, access, mode)?;
+        Ok(self.absolute(target))
     }
 }
 
@@ -61,23 +372,79 @@ impl FileSystem for LocalFileSystem {
         self.root.lock().unwrap().to_string_lossy().to_string()
     }
 
-    fn set_cwd(&self, path: &str) -> Result<(), String> {
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> {
         let mut root = self.root.lock().unwrap();
         *root = PathBuf::from(path);
+        self.folders.write().unwrap().clear();
         Ok(())
     }
 
-    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, String> {
-        let safe_path = self.validate_path(path)?;
-        let mut entries = fs::read_dir(safe_path).await.map_err(|e| e.to_string())?;
+    fn roots(&self) -> Vec<WorkspaceFolder> {
+        let folders = self.folders.read().unwrap();
+        if folders.is_empty() {
+            vec![workspace::new_folder(&self.get_cwd(), None)]
+        } else {
+            folders.clone()
+        }
+    }
+
+    fn set_roots(&self, roots: Vec<WorkspaceFolder>) -> Result<(), FsError> {
+        let first = roots.first().ok_or_else(|| FsError::from("Workspace has no folders".to_string()))?;
+        *self.root.lock().unwrap() = PathBuf::from(&first.path);
+        *self.folders.write().unwrap() = workspace::dedupe_names(roots);
+        Ok(())
+    }
+
+    fn sandbox_mode(&self) -> SandboxMode {
+        *self.sandbox.read().unwrap()
+    }
+
+    fn allows_restricted_callers(&self, _path: &str) -> bool {
+        true
+    }
+
+    fn absolute_path(&self, path: &str) -> String {
+        self.absolute(path).to_string_lossy().replace("\\", "/")
+    }
+
+    fn check_access(&self, path: &str, access: Access, mode: SandboxMode) -> Result<(), FsError> {
+        if mode == SandboxMode::Unrestricted {
+            return Ok(());
+        }
+        // 解析符号链接，防止借助工作区内的链接逃逸
+        let real = sandbox::resolve_real(&self.absolute(path));
+        let roots: Vec<PathBuf> = self.roots().iter().map(|f| sandbox::resolve_real(Path::new(&f.path))).collect();
+        if sandbox::is_permitted(mode, access, &real, &roots, &self.opened.read().unwrap()) {
+            Ok(())
+        } else {
+            Err(FsError::AccessDenied { path: path.to_string(), access, mode })
+        }
+    }
+
+    fn allow_path(&self, path: &str) {
+        let real = sandbox::resolve_real(&self.absolute(path));
+        self.opened.write().unwrap().insert(real);
+    }
+
+    fn apply_settings(&self, settings: &AppSettings) {
+        self.set_sandbox_mode(SandboxMode::from_settings(settings));
+        self.set_backup_policy(BackupPolicy::from_settings(settings));
+    }
+
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> {
+        let safe_path = self.validate_path(path, Access::Read)?;
+        let mut entries = fs::read_dir(safe_path).await.map_err(FsError::from)?;
         let mut items = Vec::new();
         
         while let Ok(Some(entry)) = entries.next_entry().await {
             let p = entry.path();
+            // 条目在列出后被删除时跳过
+            let Ok((metadata, attrs)) = inspect(&p).await else { continue };
             items.push(FileItem {
                 name: entry.file_name().to_string_lossy().to_string(),
                 path: p.to_string_lossy().to_string().replace("\\", "/"),
-                is_dir: p.is_dir(),
+                is_dir: !attrs.broken && metadata.is_dir(),
+                attrs,
             });
         }
         
@@ -88,87 +455,211 @@ impl FileSystem for LocalFileSystem {
         Ok(items)
     }
 
-    async fn read_file(&self, path: &str) -> Result<FileReadResponse, String> {
-        let safe_path = self.validate_path(path)?;
-        let bytes = fs::read(safe_path).await.map_err(|e| e.to_string())?;
-        
-        // 自动检测编码
-        let mut detector = chardetng::EncodingDetector::new();
-        detector.feed(&bytes, true);
-        let encoding = detector.guess(None, true);
-        
-        let (res, _, has_errors) = encoding.decode(&bytes);
-        let mut encoding_name = encoding.name().to_string();
-
-        if has_errors && encoding == encoding_rs::UTF_8 {
-            // 如果 UTF-8 解码失败，尝试 GBK (Windows 常见 ANSI)
-            let (res_gbk, _, errors_gbk) = encoding_rs::GBK.decode(&bytes);
-            if !errors_gbk {
-                return Ok(FileReadResponse { 
-                    content: res_gbk.into_owned(), 
-                    encoding: "GBK".to_string() 
-                });
-            } else {
-                encoding_name = "Unknown".to_string();
-            }
-        }
-        
-        // 规范化显示名称
-        encoding_name = match encoding_name.as_str() {
-            "UTF-8" => "UTF-8".to_string(),
-            "windows-1252" | "ISO-8859-1" => "ANSI".to_string(),
-            "Unknown" => "Unknown".to_string(),
-            _ => encoding_name
-        };
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        let safe_path = self.validate_path(path, Access::Read)?;
+        fs::read(safe_path).await.map_err(FsError::from)
+    }
 
-        Ok(FileReadResponse { 
-            content: res.into_owned(), 
-            encoding: encoding_name 
-        })
+    fn supports_range_reads(&self, _path: &str) -> bool {
+        true
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, length: usize) -> Result<Vec<u8>, FsError> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+        let safe_path = self.validate_path(path, Access::Read)?;
+        let mut file = fs::File::open(safe_path).await?;
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        let mut buf = Vec::with_capacity(length.min(largefile::LARGE_FILE_THRESHOLD as usize));
+        file.take(length as u64).read_to_end(&mut buf).await?;
+        Ok(buf)
+    }
+
+    // 原地写入，见 FileSystem::write_range 的说明
+    async fn write_range(&self, path: &str, offset: u64, data: &[u8]) -> Result<(), FsError> {
+        use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+        let safe_path = self.validate_path(path, Access::Write)?;
+        let mut file = fs::OpenOptions::new().write(true).open(safe_path).await?;
+        let size = file.metadata().await?.len();
+        if offset > size {
+            return Err(format!("Offset {} is beyond the end of file ({} bytes)", offset, size).into());
+        }
+        file.seek(std::io::SeekFrom::Start(offset)).await?;
+        file.write_all(data).await?;
+        file.flush().await?;
+        Ok(())
     }
 
-    async fn write_file(&self, path: &str, content: &str) -> Result<(), String> {
-        let safe_path = self.validate_path(path)?;
-        fs::write(safe_path, content).await.map_err(|e| e.to_string())
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> {
+        let safe_path = self.validate_path(path, Access::Write)?;
+        let backup = *self.backup.read().unwrap();
+        atomic_write(&safe_path, data, backup).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => FsError::NotWritable { path: path.to_string() },
+            _ => FsError::from(e),
+        })
     }
 
-    async fn create_file(&self, path: &str) -> Result<(), String> {
-        let safe_path = self.validate_path(path)?;
-        fs::write(safe_path, "").await.map_err(|e| e.to_string())
+    async fn create_file(&self, path: &str) -> Result<(), FsError> {
+        let safe_path = self.validate_path(path, Access::Write)?;
+        fs::write(safe_path, "").await.map_err(FsError::from)
     }
 
-    async fn create_dir(&self, path: &str) -> Result<(), String> {
-        let safe_path = self.validate_path(path)?;
-        fs::create_dir_all(safe_path).await.map_err(|e| e.to_string())
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> {
+        let safe_path = self.validate_path(path, Access::Write)?;
+        fs::create_dir_all(safe_path).await.map_err(FsError::from)
     }
 
-    async fn remove_item(&self, path: &str) -> Result<(), String> {
-        let safe_path = self.validate_path(path)?;
-        let metadata = fs::metadata(&safe_path).await.map_err(|e| e.to_string())?;
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> {
+        let safe_path = self.validate_path(path, Access::Write)?;
+        // 不跟随链接：删除链接本身 (包括失效的链接)，而不是链接指向的内容
+        let metadata = fs::symlink_metadata(&safe_path).await.map_err(FsError::from)?;
         if metadata.is_dir() { 
-            fs::remove_dir_all(safe_path).await.map_err(|e| e.to_string()) 
+            fs::remove_dir_all(safe_path).await.map_err(FsError::from) 
         } else { 
-            fs::remove_file(safe_path).await.map_err(|e| e.to_string()) 
+            fs::remove_file(safe_path).await.map_err(FsError::from) 
         }
     }
 
-    async fn rename_item(&self, from: &str, to: &str) -> Result<(), String> {
-        let safe_from = self.validate_path(from)?;
-        let safe_to = self.validate_path(to)?;
-        fs::rename(safe_from, safe_to).await.map_err(|e| e.to_string())
+    async fn trash_item(&self, path: &str) -> Result<bool, FsError> {
+        let safe_path = self.validate_path(path, Access::Write)?;
+        trash::move_to_trash(&safe_path).await?;
+        Ok(true)
+    }
+
+    async fn copy_file(&self, from: &str, to: &str) -> Result<(), FsError> {
+        let safe_from = self.validate_path(from, Access::Read)?;
+        let safe_to = self.validate_path(to, Access::Write)?;
+        // fs::copy 会一并复制权限位
+        fs::copy(safe_from, safe_to).await.map(|_| ()).map_err(FsError::from)
+    }
+
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        let safe_from = self.validate_path(from, Access::Write)?;
+        let safe_to = self.validate_path(to, Access::Write)?;
+        fs::rename(safe_from, safe_to).await.map_err(|e| {
+            if is_cross_device(&e) {
+                FsError::CrossDevice { from: from.to_string(), to: to.to_string() }
+            } else {
+                FsError::from(e)
+            }
+        })
+    }
+
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> {
+        let safe_path = self.validate_path(path, Access::Read)?;
+        let (metadata, attrs) = inspect(&safe_path).await?;
+        let ftype = if attrs.broken { "symlink" } else if metadata.is_dir() { "dir" } else { "file" };
+        let mtime = metadata.modified().ok().map(unix_secs).unwrap_or(0);
+        Ok(FileStat { file_type: ftype.to_string(), size: metadata.len(), mtime, is_large: largefile::is_large(metadata.len()), attrs })
+    }
+
+    #[cfg(unix)]
+    async fn set_mode(&self, path: &str, mode: u32) -> Result<(), FsError> {
+        use std::os::unix::fs::PermissionsExt;
+        let safe_path = self.validate_path(path, Access::Write)?;
+        fs::set_permissions(safe_path, std::fs::Permissions::from_mode(mode & 0o7777)).await.map_err(FsError::from)
+    }
+
+    async fn set_readonly(&self, path: &str, readonly: bool) -> Result<(), FsError> {
+        let safe_path = self.validate_path(path, Access::Write)?;
+        let mut permissions = fs::metadata(&safe_path).await?.permissions();
+        #[cfg(unix)]
+        {
+            // std 的 set_readonly(false) 在 Unix 上会让所有人可写，这里只恢复属主的写权限
+            use std::os::unix::fs::PermissionsExt;
+            let mode = permissions.mode();
+            permissions.set_mode(if readonly { mode & !0o222 } else { mode | 0o200 });
+        }
+        #[cfg(not(unix))]
+        permissions.set_readonly(readonly);
+        fs::set_permissions(safe_path, permissions).await.map_err(FsError::from)
+    }
+
+    async fn is_writable(&self, path: &str) -> Result<bool, FsError> {
+        let safe_path = self.absolute(path);
+        // 沙箱拒绝写入同样视为不可写
+        if self.check_access(path, Access::Write, self.sandbox_mode()).is_err() {
+            return Ok(false);
+        }
+        Ok(tokio::task::spawn_blocking(move || permissions::is_writable(&safe_path)).await.unwrap_or(false))
     }
 
-    async fn stat(&self, path: &str) -> Result<FileStat, String> {
-        let safe_path = self.validate_path(path)?;
-        let metadata = fs::metadata(safe_path).await.map_err(|e| e.to_string())?;
-        let ftype = if metadata.is_dir() { "dir" } else { "file" };
-        let mtime = metadata.modified().map_err(|e| e.to_string())?
-            .duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs();
-        Ok(FileStat { file_type: ftype.to_string(), size: metadata.len(), mtime })
+    async fn read_link(&self, path: &str) -> Result<String, FsError> {
+        let safe_path = self.validate_path(path, Access::Read)?;
+        let target = fs::read_link(safe_path).await?;
+        Ok(target.to_string_lossy().replace("\\", "/"))
     }
+
+    async fn real_path(&self, path: &str) -> Result<String, FsError> {
+        let safe_path = self.validate_path(path, Access::Read)?;
+        let real = fs::canonicalize(safe_path).await?;
+        Ok(real.to_string_lossy().replace("\\", "/"))
+    }
+}
+
+/// rename 因源与目标位于不同设备而失败 (Unix 的 EXDEV，Windows 的 ERROR_NOT_SAME_DEVICE)
+pub(crate) fn is_cross_device(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    let code = libc::EXDEV;
+    #[cfg(windows)]
+    let code = 17;
+    e.raw_os_error() == Some(code)
+}
+
+fn unix_secs(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// 读取路径的元数据与扩展属性，返回的元数据跟随链接 (失效的链接返回链接本身的元数据)
+async fn inspect(path: &Path) -> std::io::Result<(std::fs::Metadata, FileAttributes)> {
+    let link_metadata = fs::symlink_metadata(path).await?;
+    let is_symlink = link_metadata.file_type().is_symlink();
+    let (metadata, broken, target) = if is_symlink {
+        let target = fs::read_link(path).await.ok().map(|t| t.to_string_lossy().replace("\\", "/"));
+        // 链接成环时 metadata 返回 ELOOP，同样视为失效
+        match fs::metadata(path).await {
+            Ok(m) => (m, false, target),
+            Err(_) => (link_metadata, true, target),
+        }
+    } else {
+        (link_metadata, false, None)
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let attrs = FileAttributes {
+        is_symlink,
+        target,
+        broken,
+        mode: unix_mode(&metadata),
+        readonly: metadata.permissions().readonly(),
+        hidden: name.starts_with('.') || has_hidden_attribute(&metadata),
+        created: metadata.created().ok().map(unix_secs),
+    };
+    Ok((metadata, attrs))
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(windows)]
+fn has_hidden_attribute(metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(not(windows))]
+fn has_hidden_attribute(_metadata: &std::fs::Metadata) -> bool {
+    false
 }
 
-fn normalize_path(path: &Path) -> PathBuf {
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
     let mut components = path.components().peekable();
     let mut ret = if let Some(c @ Component::Prefix(..)) = components.peek().cloned() {
         components.next();
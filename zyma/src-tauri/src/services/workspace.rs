@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use crate::models::{SessionInfo, WorkspaceFolder};
+use crate::services::vfs::normalize_path;
+
+/// 多根工作区文件的扩展名
+pub const WORKSPACE_EXT: &str = "zyma-workspace";
+
+/// `.zyma-workspace` 文件格式，相对路径以工作区文件所在目录为基准
+/// ```json
+/// { "folders": [ { "name": "backend", "path": "server" }, { "path": "/abs/web" } ], "settings": { "tab_size": 2 } }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct WorkspaceFile {
+    folders: Vec<FolderEntry>,
+    // 保留未识别的字段，写回时不丢失
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct FolderEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    path: String,
+}
+
+pub fn is_workspace_file(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|e| e == WORKSPACE_EXT)
+}
+
+/// 读取工作区文件，返回解析为绝对路径的根目录列表
+pub fn load(path: &Path) -> Result<Vec<WorkspaceFolder>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: WorkspaceFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid workspace file {}: {}", path.display(), e))?;
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    let folders: Vec<WorkspaceFolder> = file.folders.into_iter().map(|entry| {
+        let p = Path::new(&entry.path);
+        let abs = if p.is_absolute() { p.to_path_buf() } else { base.join(p) };
+        let abs = display(&normalize_path(&abs));
+        WorkspaceFolder { name: entry.name.unwrap_or_else(|| folder_name(&abs)), path: abs }
+    }).collect();
+
+    if folders.is_empty() {
+        return Err(format!("Workspace file has no folders: {}", path.display()));
+    }
+    Ok(dedupe_names(folders))
+}
+
+/// 写入工作区文件，位于其目录之下的根目录以相对路径保存，便于随仓库共享
+pub fn save(path: &Path, folders: &[WorkspaceFolder]) -> Result<(), String> {
+    let mut file = match std::fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str::<WorkspaceFile>(&content).unwrap_or_default(),
+        Err(_) => WorkspaceFile::default(),
+    };
+    let base = path.parent().map(normalize_path).unwrap_or_default();
+    file.folders = folders.iter().map(|f| {
+        let p = normalize_path(Path::new(&f.path));
+        let stored = match p.strip_prefix(&base) {
+            Ok(rel) if !rel.as_os_str().is_empty() => display(rel),
+            Ok(_) => ".".to_string(),
+            Err(_) => display(&p),
+        };
+        let name = if f.name == folder_name(&f.path) { None } else { Some(f.name.clone()) };
+        FolderEntry { name, path: stored }
+    }).collect();
+
+    let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// 读取工作区文件中的 settings 字段 (工作区级设置)
+pub fn load_settings(path: &Path) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: WorkspaceFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid workspace file {}: {}", path.display(), e))?;
+    match file.extra.get("settings") {
+        Some(serde_json::Value::Object(settings)) => Ok(settings.clone()),
+        _ => Ok(serde_json::Map::new()),
+    }
+}
+
+/// 写回工作区文件中的 settings 字段，其余内容保持不变
+pub fn save_settings(path: &Path, settings: &serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut file: WorkspaceFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid workspace file {}: {}", path.display(), e))?;
+    if settings.is_empty() {
+        file.extra.remove("settings");
+    } else {
+        file.extra.insert("settings".to_string(), serde_json::Value::Object(settings.clone()));
+    }
+    let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// 以目录名作为默认名称
+pub fn folder_name(path: &str) -> String {
+    Path::new(path).file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+pub fn new_folder(path: &str, name: Option<String>) -> WorkspaceFolder {
+    let abs = display(&normalize_path(&PathBuf::from(path)));
+    WorkspaceFolder { name: name.unwrap_or_else(|| folder_name(&abs)), path: abs }
+}
+
+/// 名称用于解析 "name/relative/path"，重名时追加序号
+pub fn dedupe_names(folders: Vec<WorkspaceFolder>) -> Vec<WorkspaceFolder> {
+    let mut result: Vec<WorkspaceFolder> = Vec::with_capacity(folders.len());
+    for mut folder in folders {
+        let base = folder.name.clone();
+        let mut n = 2;
+        while result.iter().any(|f| f.name == folder.name) {
+            folder.name = format!("{} ({})", base, n);
+            n += 1;
+        }
+        result.push(folder);
+    }
+    result
+}
+
+fn display(p: &Path) -> String {
+    p.to_string_lossy().to_string().replace("\\", "/")
+}
+
+/// 从上次会话恢复多根工作区 (单根工作区返回 None，由 root_path 恢复)
+pub fn restore_from_session(session: &SessionInfo) -> Option<Vec<WorkspaceFolder>> {
+    if let Some(file) = &session.workspace_file {
+        if let Ok(folders) = load(Path::new(file)) {
+            return Some(folders);
+        }
+    }
+    let folders: Vec<WorkspaceFolder> = session.folders.iter()
+        .filter(|f| Path::new(&f.path).is_dir())
+        .cloned()
+        .collect();
+    if folders.is_empty() { None } else { Some(folders) }
+}
@@ -0,0 +1,108 @@
+use std::io::Write;
+use zyma_lib::commands::fs::WorkspaceService;
+use zyma_lib::services::{FileSystem, LocalFileSystem};
+use zyma_lib::services::sandbox::Caller;
+use zyma_lib::services::transfer::ConflictPolicy;
+use zyma_lib::services::vfs::FsError;
+
+#[tokio::test]
+async fn archives() {
+    let dir = std::env::temp_dir().join(format!("arc-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let d = dir.to_string_lossy().replace('\\', "/");
+    {
+        let f = std::fs::File::create(dir.join("a.jar")).unwrap();
+        let mut z = zip::ZipWriter::new(f);
+        let o = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        z.start_file("META-INF/MANIFEST.MF", o).unwrap();
+        z.write_all(b"Manifest-Version: 1.0\n").unwrap();
+        z.start_file("com/x/A.class", o).unwrap();
+        z.write_all(b"\xca\xfe\xba\xbe\0\0").unwrap();
+        z.start_file("readme.txt", o).unwrap();
+        z.write_all(b"hi").unwrap();
+        z.finish().unwrap();
+    }
+    {
+        let f = std::fs::File::create(dir.join("b.tar.gz")).unwrap();
+        let gz = flate2::write::GzEncoder::new(f, flate2::Compression::default());
+        let mut t = tar::Builder::new(gz);
+        let mut h = tar::Header::new_gnu();
+        h.set_size(5); h.set_mode(0o644); h.set_cksum();
+        t.append_data(&mut h, "./pkg/src/main.rs", &b"fn m\n"[..]).unwrap();
+        t.into_inner().unwrap().finish().unwrap();
+    }
+    let ws = WorkspaceService::with_fs(Box::new(LocalFileSystem::new(dir.clone())));
+    let fs = ws.fs_for(Caller::User);
+    let root = format!("zip:{}/a.jar!/", d);
+    let items = fs.read_dir(&root).await.unwrap();
+    let names: Vec<_> = items.iter().map(|i| (i.name.as_str(), i.is_dir)).collect();
+    assert_eq!(names, vec![("META-INF", true), ("com", true), ("readme.txt", false)]);
+    assert_eq!(items[1].path, format!("zip:{}/a.jar!/com", d));
+    let sub = fs.read_dir(&items[1].path).await.unwrap();
+    assert_eq!(sub[0].path, format!("zip:{}/a.jar!/com/x", d));
+    let r = fs.read_file(&format!("zip:{}/a.jar!/META-INF/MANIFEST.MF", d)).await.unwrap();
+    assert_eq!(r.content, "Manifest-Version: 1.0\n");
+    assert!(fs.read_file(&format!("zip:{}/a.jar!/com/x/A.class", d)).await.is_err());
+    let st = fs.stat(&format!("zip:{}/a.jar!/readme.txt", d)).await.unwrap();
+    assert_eq!((st.file_type.as_str(), st.size), ("file", 2));
+    assert!(fs.write_bytes(&format!("zip:{}/a.jar!/readme.txt", d), b"x").await.is_err());
+    let noop = |_| {};
+    // 解压到磁盘
+    let out = fs.copy_item(&format!("zip:{}/a.jar!/com", d), &format!("{}/out", d), ConflictPolicy::Rename, "op", &noop).await.unwrap();
+    assert_eq!(out.unwrap(), format!("{}/out", d));
+    assert_eq!(std::fs::read(dir.join("out/x/A.class")).unwrap(), b"\xca\xfe\xba\xbe\0\0");
+
+    let tg = fs.read_dir(&format!("zip:{}/b.tar.gz", d)).await.unwrap();
+    assert_eq!(tg[0].name, "pkg");
+    let r = fs.read_file(&format!("zip:{}/b.tar.gz!/pkg/src/main.rs", d)).await.unwrap();
+    assert_eq!(r.content, "fn m\n");
+
+    // 插件不能读取工作区外的压缩包
+    let outside = std::env::temp_dir().join(format!("arc-out-{}.zip", std::process::id()));
+    std::fs::copy(dir.join("a.jar"), &outside).unwrap();
+    let p = format!("zip:{}!/readme.txt", outside.to_string_lossy());
+    assert!(ws.fs_for(Caller::Plugin).read_bytes(&p).await.is_err());
+    assert_eq!(ws.fs_for(Caller::User).read_bytes(&p).await.unwrap(), b"hi");
+    std::fs::remove_file(outside).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn large_archives_and_entries_need_force() {
+    let dir = std::env::temp_dir().join(format!("arc-large-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let d = dir.to_string_lossy().replace('\\', "/");
+    {
+        // 压缩后很小、解压后超过大文件阈值的条目
+        let f = std::fs::File::create(dir.join("bomb.zip")).unwrap();
+        let mut z = zip::ZipWriter::new(f);
+        let o = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        z.start_file("zeros.bin", o).unwrap();
+        let chunk = vec![0u8; 1024 * 1024];
+        for _ in 0..51 {
+            z.write_all(&chunk).unwrap();
+        }
+        z.finish().unwrap();
+    }
+    std::fs::File::create(dir.join("huge.tar")).unwrap().set_len(60 * 1024 * 1024).unwrap();
+
+    let ws = WorkspaceService::with_fs(Box::new(LocalFileSystem::new(dir.clone())));
+    let fs = ws.fs_for(Caller::User);
+    let entry = format!("zip:{}/bomb.zip!/zeros.bin", d);
+    assert!(matches!(fs.read_bytes(&entry).await, Err(FsError::FileTooLarge { .. })));
+    ws.force_archive(&entry);
+    assert_eq!(fs.read_bytes(&entry).await.unwrap().len(), 51 * 1024 * 1024);
+    assert_eq!(fs.read_bytes(&entry).await.unwrap().len(), 51 * 1024 * 1024);
+    // 压缩包变化后之前的确认失效
+    {
+        let f = std::fs::OpenOptions::new().append(true).open(dir.join("bomb.zip")).unwrap();
+        f.set_len(f.metadata().unwrap().len() + 1).unwrap();
+    }
+    assert!(matches!(fs.read_bytes(&entry).await, Err(FsError::FileTooLarge { .. })));
+
+    let huge = format!("zip:{}/huge.tar!/", d);
+    assert!(matches!(fs.read_dir(&huge).await, Err(FsError::FileTooLarge { size, .. }) if size == 60 * 1024 * 1024));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use zyma_lib::models::AppSettings;
+use zyma_lib::services::atomic::{atomic_write, BackupPolicy};
+use zyma_lib::services::mount::MountTable;
+use zyma_lib::services::{FileSystem, LocalFileSystem};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zyma-atomic-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn names(dir: &std::path::Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir).unwrap().map(|e| e.unwrap().file_name().into_string().unwrap()).collect();
+    names.sort();
+    names
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn keeps_permissions_and_rotates_numbered_backups() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = temp_dir("rotate");
+    let file = dir.join("x.sh");
+    std::fs::write(&file, "v0").unwrap();
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o755)).unwrap();
+    for i in 1..=4 {
+        atomic_write(&file, format!("v{}", i).as_bytes(), BackupPolicy::Numbered(2)).await.unwrap();
+    }
+    atomic_write(&file, b"v5", BackupPolicy::Single).await.unwrap();
+
+    assert_eq!(names(&dir), ["x.sh", "x.sh.bak", "x.sh.~3~", "x.sh.~4~"]);
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "v5");
+    assert_eq!(std::fs::read_to_string(dir.join("x.sh.~4~")).unwrap(), "v3");
+    assert_eq!(std::fs::metadata(&file).unwrap().permissions().mode() & 0o777, 0o755);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn huge_backup_numbers_do_not_overflow() {
+    let dir = temp_dir("overflow");
+    let file = dir.join("a.txt");
+    std::fs::write(&file, "old").unwrap();
+    std::fs::write(dir.join("a.txt.~4294967295~"), "manual").unwrap();
+    atomic_write(&file, b"new", BackupPolicy::Numbered(3)).await.unwrap();
+
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt.~4294967296~")).unwrap(), "old");
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "new");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn backup_policy_follows_settings_changes() {
+    let dir = temp_dir("settings");
+    let fs = MountTable::new(Box::new(LocalFileSystem::new(dir.clone())));
+    fs.write_bytes("a.txt", b"v1").await.unwrap();
+    fs.write_bytes("a.txt", b"v2").await.unwrap();
+    assert_eq!(names(&dir), ["a.txt"]);
+
+    fs.apply_settings(&AppSettings { save_backup: Some("bak".into()), ..Default::default() });
+    fs.write_bytes("a.txt", b"v3").await.unwrap();
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt.bak")).unwrap(), "v2");
+
+    fs.apply_settings(&AppSettings::default());
+    fs.write_bytes("a.txt", b"v4").await.unwrap();
+    assert_eq!(std::fs::read_to_string(dir.join("a.txt.bak")).unwrap(), "v2");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,67 @@
+use zyma_lib::commands::fs::WorkspaceService;
+use zyma_lib::services::binary;
+use zyma_lib::services::history::{HistoryPolicy, HistorySource};
+use zyma_lib::services::vfs::FsError;
+use zyma_lib::services::{FileSystem, LocalFileSystem, MemoryFileSystem};
+
+#[tokio::test]
+async fn binary_and_hex() {
+    assert!(!binary::is_binary(b"hello\r\n\tworld\x1b[0m"));
+    assert!(binary::is_binary(b"MZ\x90\x00\x03"));
+    assert!(!binary::is_binary(b"\xff\xfeh\x00i\x00"));
+    assert!(!binary::is_binary(b""));
+    assert_eq!(binary::parse_hex("4d5a 90 00").unwrap(), vec![0x4d, 0x5a, 0x90, 0]);
+    assert!(binary::parse_hex("4d5").is_err());
+    let mut patched = b"abc".to_vec();
+    binary::apply_patch(&mut patched, 2, b"XY").unwrap();
+    assert_eq!(patched, b"abXY");
+    assert!(binary::apply_patch(&mut patched, 5, b"Z").is_err());
+
+    let dir = std::env::temp_dir().join(format!("bin-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let data: Vec<u8> = (0..=255u8).chain(0..10).collect();
+    std::fs::write(dir.join("a.bin"), &data).unwrap();
+    let fs = LocalFileSystem::new(dir.clone());
+    let p = dir.join("a.bin").to_string_lossy().to_string();
+    assert!(matches!(fs.read_file(&p).await, Err(FsError::BinaryFile { size: 266, .. })));
+    let d = binary::hex_dump(&fs, &p, 20, 2).await.unwrap();
+    assert_eq!(d.offset, 16);
+    assert_eq!(d.rows[0].hex, "10 11 12 13 14 15 16 17 18 19 1a 1b 1c 1d 1e 1f");
+    assert_eq!(d.rows[1].ascii, " !\"#$%&'()*+,-./");
+    let t = binary::hex_dump(&fs, &p, 256, 10).await.unwrap();
+    assert_eq!(t.rows.len(), 1);
+    assert!(t.eof);
+    fs.write_range(&p, 2, &[0xaa, 0xbb]).await.unwrap();
+    fs.write_range(&p, 265, &[1, 2, 3]).await.unwrap();
+    assert!(fs.write_range(&p, 1000, &[1]).await.is_err());
+    let b = std::fs::read(dir.join("a.bin")).unwrap();
+    assert_eq!(&b[..5], &[0, 1, 0xaa, 0xbb, 4]);
+    assert_eq!(b.len(), 268);
+    assert_eq!(&b[264..], &[8, 1, 2, 3]);
+
+    let m = MemoryFileSystem::new(std::path::PathBuf::from("/m"));
+    m.write_bytes("/m/x", b"abcdef").await.unwrap();
+    m.write_range("/m/x", 4, b"XYZ").await.unwrap();
+    assert_eq!(m.read_bytes("/m/x").await.unwrap(), b"abcdXYZ");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn patches_keep_a_history_snapshot() {
+    let dir = std::env::temp_dir().join(format!("zyma-bin-history-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let ws = WorkspaceService::with_fs(Box::new(MemoryFileSystem::new(std::path::PathBuf::from("/m"))));
+    ws.history.set_root(dir.clone());
+    ws.history.set_policy(HistoryPolicy::default());
+    ws.fs.write_bytes("/m/fw.bin", b"\x00\x01\x02").await.unwrap();
+
+    // 与 fs_patch_bytes 的默认路径相同：内存中修改后经由 write_with_history 保存
+    let mut content = ws.fs.read_bytes("/m/fw.bin").await.unwrap();
+    binary::apply_patch(&mut content, 1, &[0xff]).unwrap();
+    ws.write_with_history(&ws.fs, "/m/fw.bin", &content, HistorySource::Save).await.unwrap();
+    let entries = ws.history.list("/m/fw.bin").await.unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].source, HistorySource::External);
+    assert_eq!(ws.history.read("/m/fw.bin", &entries[1].id).await.unwrap(), b"\x00\x01\x02");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,126 @@
+use zyma_lib::models::AppSettings;
+use zyma_lib::services::config_file::{self, CURRENT_VERSION};
+use zyma_lib::services::settings_store::SettingsStore;
+use serde_json::{json, Value};
+
+#[test]
+fn invalid_key_only_drops_that_key_with_position() {
+    let content = "{\n  \"theme\": \"light\",\n  \"tab_size\": \"two\",\n  \"ai_api_key\": \"sk-1\"\n}";
+    let loaded = config_file::parse(content);
+    assert_eq!(loaded.settings.theme, "light");
+    assert_eq!(loaded.settings.ai_api_key.as_deref(), Some("sk-1"));
+    assert_eq!(loaded.settings.tab_size, 4);
+    assert_eq!(loaded.issues.len(), 1);
+    let issue = &loaded.issues[0];
+    assert_eq!(issue.key.as_deref(), Some("tab_size"));
+    assert_eq!((issue.line, issue.column), (3, 3));
+    assert!(!loaded.values.contains_key("tab_size"));
+}
+
+#[test]
+fn legacy_config_is_migrated() {
+    let content = r#"{ "theme": "dark", "font_size": "16", "session": { "open_files": [], "dirty_buffers": [{ "id": "u1", "name": "a", "content": "x", "has_bom": false, "timestamp": 1 }] } }"#;
+    let loaded = config_file::parse(content);
+    assert!(loaded.issues.is_empty(), "{:?}", loaded.issues);
+    assert_eq!(loaded.migrated_from, Some(0));
+    assert_eq!(loaded.settings.font_size, 16);
+    assert_eq!(loaded.settings.version, CURRENT_VERSION);
+    assert_eq!(loaded.values["version"], json!(CURRENT_VERSION));
+    assert!(loaded.settings.session.unwrap().dirty_buffers.is_empty());
+}
+
+#[test]
+fn comments_trailing_commas_and_bad_array_items() {
+    let content = r#"{
+        // user theme
+        "theme": "abyss", /* block */
+        "ssh_profiles": [
+            { "id": "a", "host": "h", "username": "u" },
+            { "id": "b", "host": "h" },
+        ],
+    }"#;
+    let loaded = config_file::parse(content);
+    assert_eq!(loaded.settings.theme, "abyss");
+    assert_eq!(loaded.settings.ssh_profiles.len(), 1);
+    assert_eq!(loaded.issues.len(), 1);
+    assert_eq!(loaded.issues[0].line, 4);
+}
+
+#[test]
+fn syntax_error_keeps_corrupt_copy() {
+    let dir = std::env::temp_dir().join(format!("zyma-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(".zyma_config.json");
+    std::fs::write(&path, "{\n  \"theme\": \"dark\"\n  \"tab_size\": 2\n}").unwrap();
+    let loaded = config_file::load(&path);
+    assert_eq!(loaded.issues.len(), 1);
+    assert_eq!(loaded.issues[0].line, 3);
+    assert!(loaded.blocking_issue().is_some());
+    assert_eq!(loaded.settings.theme, AppSettings::default().theme);
+    // 读取 (诊断) 不修改文件，副本由设置存储首次读取时保存
+    let copy = config_file::corrupt_path(&path);
+    assert_eq!(copy.file_name().unwrap(), ".zyma_config.json.corrupt");
+    assert!(!copy.exists());
+    SettingsStore::new(path.clone()).values();
+    assert!(std::fs::read_to_string(&copy).unwrap().contains("tab_size"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn broken_file_is_not_overwritten_by_backend_writes() {
+    let dir = std::env::temp_dir().join(format!("zyma-config-broken-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("settings.json");
+    let broken = "{\n  \"theme\": \"light\",\n  \"font_size\": 18\n  \"tab_size\": 2\n}";
+    std::fs::write(&path, broken).unwrap();
+    let store = SettingsStore::new(path.clone());
+    // 保存窗口状态被拒绝，原文件保持不变
+    assert!(store.update(|s| s.window_width = 1234.0).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), broken);
+    // 修好之后照常写入，且保留用户的设置
+    std::fs::write(&path, broken.replace("18\n", "18,\n")).unwrap();
+    store.update(|s| s.window_width = 1234.0).unwrap();
+    let saved = config_file::load(&path);
+    assert!(saved.issues.is_empty(), "{:?}", saved.issues);
+    assert_eq!(saved.settings.theme, "light");
+    assert_eq!(saved.settings.font_size, 18);
+    assert_eq!(saved.settings.window_width, 1234.0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn newer_version_is_read_but_never_written() {
+    let content = r#"{ "version": 99, "theme": "light", "future_option": true }"#;
+    let loaded = config_file::parse(content);
+    let issue = loaded.blocking_issue().unwrap();
+    assert!(issue.message.contains("newer version"));
+    assert_eq!(loaded.settings.theme, "light");
+    assert_eq!(loaded.migrated_from, None);
+
+    let dir = std::env::temp_dir().join(format!("zyma-config-newer-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("settings.json");
+    std::fs::write(&path, content).unwrap();
+    let store = SettingsStore::new(path.clone());
+    assert_eq!(store.settings().theme, "light");
+    assert!(store.update(|s| s.theme = "dark".to_string()).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn schema_covers_every_setting() {
+    let schema = config_file::schema();
+    let properties = schema["properties"].as_object().unwrap();
+    let defaults = match serde_json::to_value(AppSettings::default()).unwrap() { Value::Object(m) => m, _ => unreachable!() };
+    for key in defaults.keys() {
+        assert!(properties.contains_key(key), "schema is missing {}", key);
+    }
+    assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+    // 各层设置文件都只写部分键
+    assert!(schema.get("required").is_none());
+    assert_eq!(properties["theme"]["enum"], json!(["dark", "light", "abyss"]));
+    assert_eq!(properties["fs_sandbox"]["enum"], json!(["workspace", "read_only_outside", null]));
+    assert!(properties["font_size"]["description"].is_string());
+    assert!(schema["patternProperties"].get("^\\[.+\\]$").is_some());
+}
@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use zyma_lib::models::WriteOptions;
+use zyma_lib::services::encoding;
+use zyma_lib::services::vfs::FsError;
+use zyma_lib::services::{FileSystem, MemoryFileSystem};
+
+fn shift_jis() -> WriteOptions {
+    WriteOptions { encoding: Some("Shift_JIS".into()), has_bom: false, line_ending: Some("CRLF".into()) }
+}
+
+#[tokio::test]
+async fn round_trip_keeps_encoding_bom_and_line_endings() {
+    let (gbk, _, _) = encoding_rs::GBK.encode("你好世界，这是一个中文文件的测试内容。\r\n第二行也是中文。\r\n");
+    let samples = [gbk.to_vec(), b"\xEF\xBB\xBFhello\r\nworld\r\n".to_vec(), b"\xFF\xFEh\0i\0\r\0\n\0".to_vec(), b"plain\nascii\n".to_vec()];
+    for bytes in samples {
+        let fs = MemoryFileSystem::new(PathBuf::from("/w"));
+        fs.insert_file("f", bytes.clone());
+        let read = fs.read_file("f").await.unwrap();
+        let options = WriteOptions { encoding: Some(read.encoding.clone()), has_bom: read.has_bom, line_ending: Some(read.line_ending.clone()) };
+        // 界面中统一为 LF 后再写回
+        fs.write_file("f", &read.content.replace("\r\n", "\n"), &options).await.unwrap();
+        assert_eq!(fs.read_bytes("f").await.unwrap(), bytes, "{}", read.encoding);
+    }
+}
+
+#[test]
+fn unmappable_reports_position_and_total_count() {
+    match encoding::encode("こんにちは\nab한국c\n😀", &shift_jis()).unwrap_err() {
+        FsError::Unmappable { count, positions, .. } => {
+            assert_eq!(count, 3);
+            assert_eq!((positions[0].line, positions[0].column), (2, 3));
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+
+    // 位置列表有上限，但总数与提示信息按实际数量计算
+    let err = encoding::encode(&"한".repeat(250), &shift_jis()).unwrap_err();
+    assert!(err.to_string().starts_with("250 character(s)"), "{}", err);
+    match err {
+        FsError::Unmappable { count, positions, .. } => assert_eq!((count, positions.len()), (250, 100)),
+        e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn decode_with_explicit_encoding() {
+    let bytes = encoding::encode("こんにちは\n", &shift_jis()).unwrap();
+    assert_eq!(encoding::decode_with(&bytes, "Shift_JIS").unwrap().content, "こんにちは\r\n");
+    assert!(encoding::decode_with(&bytes, "bogus").is_err());
+}
@@ -0,0 +1,106 @@
+use zyma_lib::commands::fs::WorkspaceService;
+use zyma_lib::services::diff::{diff_lines, to_unified, DiffLineKind};
+use zyma_lib::services::history::{HistoryPolicy, HistoryService, HistorySource};
+use zyma_lib::services::LocalFileSystem;
+use zyma_lib::services::sandbox::Caller;
+
+#[test]
+fn diff_basic() {
+    assert!(diff_lines("a\nb\n", "a\nb\n").is_empty());
+    let old = (1..=20).map(|i| i.to_string()).collect::<Vec<_>>().join("\n");
+    let new = old.replace("5\n", "five\n").replace("\n18", "\n18\nextra");
+    let hunks = diff_lines(&old, &new);
+    assert_eq!(hunks.len(), 2);
+    assert_eq!((hunks[0].old_start, hunks[0].old_lines, hunks[0].new_start, hunks[0].new_lines), (2, 7, 2, 7));
+    assert_eq!(hunks[1].new_lines, hunks[1].old_lines + 1);
+    let u = to_unified(&hunks, "a", "b");
+    assert!(u.contains("-5\n+five\n"), "{}", u);
+    assert!(u.contains("+extra\n"));
+    // 全新文件
+    let h = diff_lines("", "x\ny");
+    assert_eq!((h[0].old_start, h[0].old_lines, h[0].new_start, h[0].new_lines), (0, 0, 1, 2));
+    // 交错修改
+    let h = diff_lines("a\nb\nc\nd", "b\nx\nd\ne");
+    let adds: Vec<_> = h[0].lines.iter().filter(|l| l.kind == DiffLineKind::Added).map(|l| l.text.as_str()).collect();
+    let dels: Vec<_> = h[0].lines.iter().filter(|l| l.kind == DiffLineKind::Removed).map(|l| l.text.as_str()).collect();
+    assert_eq!(adds, vec!["x", "e"]);
+    assert_eq!(dels, vec!["a", "c"]);
+}
+
+#[test]
+fn diff_large_replacement() {
+    let old: String = (0..6000).map(|i| format!("o{}\n", i)).collect();
+    let new: String = (0..6000).map(|i| format!("n{}\n", i)).collect();
+    let h = diff_lines(&old, &new);
+    assert_eq!(h.len(), 1);
+    assert_eq!(h[0].old_lines, 6000);
+    assert_eq!(h[0].new_lines, 6000);
+}
+
+#[tokio::test]
+async fn history_record_and_prune() {
+    let tmp = std::env::temp_dir().join(format!("zyma-hist-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let f = tmp.join("a.txt");
+    let p = f.to_str().unwrap().to_string();
+    std::fs::write(&f, "v0").unwrap();
+
+    let mut ws = WorkspaceService::with_fs(Box::new(LocalFileSystem::new(tmp.clone())));
+    ws.history = HistoryService::new(tmp.join("hist"), HistoryPolicy { enabled: true, max_entries: 3, max_age_days: 30 });
+    let fs = ws.fs_for(Caller::User);
+    ws.write_with_history(&fs, &p, b"v1", HistorySource::Save).await.unwrap();
+    let list = ws.history.list(&p).await.unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].source, HistorySource::Save);
+    assert_eq!(list[1].source, HistorySource::External);
+    // 相同内容不重复记录
+    ws.write_with_history(&fs, &p, b"v1", HistorySource::Save).await.unwrap();
+    assert_eq!(ws.history.list(&p).await.unwrap().len(), 2);
+    for v in ["v2", "v3", "v4"] {
+        ws.write_with_history(&fs, &p, v.as_bytes(), HistorySource::Save).await.unwrap();
+    }
+    let list = ws.history.list(&p).await.unwrap();
+    assert_eq!(list.len(), 3);
+    assert_eq!(ws.history.read(&p, &list[0].id).await.unwrap(), b"v4");
+    assert_eq!(ws.history.read(&p, &list[2].id).await.unwrap(), b"v2");
+    let dir = std::fs::read_dir(tmp.join("hist")).unwrap().next().unwrap().unwrap().path();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 4);
+    assert!(ws.history.read(&p, "nope").await.is_err());
+
+    // 关闭后不再记录
+    ws.history.set_policy(HistoryPolicy { enabled: false, ..Default::default() });
+    ws.write_with_history(&fs, &p, b"v5", HistorySource::Save).await.unwrap();
+    assert_eq!(ws.history.list(&p).await.unwrap().len(), 3);
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[tokio::test]
+async fn history_keys_and_corrupt_index() {
+    let tmp = std::env::temp_dir().join(format!("zyma-hist-key-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(tmp.join("sub")).unwrap();
+    let abs = tmp.join("foo.txt").to_str().unwrap().to_string();
+
+    let mut ws = WorkspaceService::with_fs(Box::new(LocalFileSystem::new(tmp.clone())));
+    ws.history = HistoryService::new(tmp.join("hist"), HistoryPolicy::default());
+    let fs = ws.fs_for(Caller::User);
+    // 相对路径、绝对路径与 file: URI 共用一条时间线
+    ws.write_with_history(&fs, "foo.txt", b"v1", HistorySource::Save).await.unwrap();
+    ws.write_with_history(&fs, &abs, b"v2", HistorySource::Save).await.unwrap();
+    ws.write_with_history(&fs, &format!("file:{}", abs), b"v3", HistorySource::Save).await.unwrap();
+    ws.write_with_history(&fs, "sub/../foo.txt", b"v4", HistorySource::Save).await.unwrap();
+    assert_eq!(ws.history_key("foo.txt"), abs);
+    assert_eq!(ws.history_key(&format!("file://{}", abs)), abs);
+    assert_eq!(ws.history.list(&abs).await.unwrap().len(), 4);
+    assert_eq!(std::fs::read_dir(tmp.join("hist")).unwrap().count(), 1);
+
+    // 索引损坏时报错，且不会被下一次保存覆盖
+    let dir = std::fs::read_dir(tmp.join("hist")).unwrap().next().unwrap().unwrap().path();
+    std::fs::write(dir.join("entries.json"), "{ not json").unwrap();
+    assert!(ws.history.list(&abs).await.is_err());
+    assert!(ws.history.record(&abs, b"v5", HistorySource::Save).await.is_err());
+    ws.write_with_history(&fs, &abs, b"v6", HistorySource::Save).await.unwrap();
+    assert_eq!(std::fs::read_to_string(dir.join("entries.json")).unwrap(), "{ not json");
+    let _ = std::fs::remove_dir_all(&tmp);
+}
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use async_trait::async_trait;
+use zyma_lib::models::FileItem;
+use zyma_lib::services::largefile::{self, LineIndexService, MAX_RANGE_BYTES};
+use zyma_lib::services::vfs::{FileStat, FsError};
+use zyma_lib::services::{FileSystem, LocalFileSystem, MemoryFileSystem};
+
+#[tokio::test]
+async fn pages_lines_with_and_without_index() {
+    let dir = std::env::temp_dir().join(format!("zyma-largefile-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut content = String::from("\u{feff}");
+    for i in 0..300_000 {
+        content.push_str(&format!("line number {} here\r\n", i));
+    }
+    content.push_str("last");
+    std::fs::write(dir.join("big.txt"), &content).unwrap();
+    let fs = LocalFileSystem::new(dir.clone());
+    let path = dir.join("big.txt").to_string_lossy().to_string();
+    let stat = fs.stat(&path).await.unwrap();
+
+    let page = largefile::read_lines(&fs, None, &path, 0, 3, None).await.unwrap();
+    assert_eq!(page.lines, ["line number 0 here", "line number 1 here", "line number 2 here"]);
+    let indexes = LineIndexService::new();
+    let (index, is_new) = indexes.get_or_create(&path, stat.size, stat.mtime);
+    assert!(is_new);
+    index.build(&fs, &path, &|_| {}).await.unwrap();
+    assert_eq!(index.status(&path).total_lines, Some(300_001));
+    for start in [0u64, 999, 1000, 123_456, 299_998] {
+        let indexed = largefile::read_lines(&fs, Some(&index), &path, start, 5, None).await.unwrap();
+        let scanned = largefile::read_lines(&fs, None, &path, start, 5, None).await.unwrap();
+        assert_eq!(indexed.lines, scanned.lines);
+        assert_eq!(indexed.lines[0], format!("line number {} here", start));
+    }
+    let tail = largefile::read_lines(&fs, Some(&index), &path, 299_999, 5, None).await.unwrap();
+    assert_eq!(tail.lines, ["line number 299999 here", "last"]);
+    assert!(tail.eof);
+
+    assert_eq!(largefile::read_range(&fs, &path, 3, 6, None).await.unwrap().content, "line n");
+    // 请求的长度按上限截断
+    let range = largefile::read_range(&fs, &path, 0, usize::MAX, None).await.unwrap();
+    assert_eq!(range.length, MAX_RANGE_BYTES as u64);
+    assert!(!range.eof);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 不支持范围读取的实现，统计整体读取的次数
+struct WholeFileOnly {
+    inner: MemoryFileSystem,
+    reads: AtomicUsize,
+}
+
+#[async_trait]
+impl FileSystem for WholeFileOnly {
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> { self.inner.read_dir(path).await }
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.inner.read_bytes(path).await
+    }
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> { self.inner.write_bytes(path, data).await }
+    async fn create_file(&self, path: &str) -> Result<(), FsError> { self.inner.create_file(path).await }
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> { self.inner.create_dir(path).await }
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> { self.inner.remove_item(path).await }
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> { self.inner.rename_item(from, to).await }
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> { self.inner.stat(path).await }
+    fn get_cwd(&self) -> String { self.inner.get_cwd() }
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> { self.inner.set_cwd(path) }
+}
+
+#[tokio::test]
+async fn reads_whole_file_once_without_range_support() {
+    let fs = WholeFileOnly { inner: MemoryFileSystem::new(PathBuf::from("/w")), reads: AtomicUsize::new(0) };
+    let content: String = (0..600_000).map(|i| format!("{}\n", i)).collect();
+    assert!(content.len() > 3 * 1024 * 1024);
+    fs.inner.insert_file("/w/log.txt", content.as_str());
+
+    let indexes = LineIndexService::new();
+    let (index, _) = indexes.get_or_create("/w/log.txt", content.len() as u64, 0);
+    index.build(&fs, "/w/log.txt", &|_| {}).await.unwrap();
+    assert_eq!(fs.reads.load(Ordering::SeqCst), 1);
+    assert_eq!(index.status("/w/log.txt").total_lines, Some(600_001));
+
+    let page = largefile::read_lines(&fs, None, "/w/log.txt", 550_000, 2, None).await.unwrap();
+    assert_eq!(page.lines, ["550000", "550001"]);
+    assert_eq!(fs.reads.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn long_lines_are_truncated_instead_of_buffered() {
+    let fs = MemoryFileSystem::new(PathBuf::from("/w"));
+    let mut content = "x".repeat(MAX_RANGE_BYTES + 1024 * 1024 + 7);
+    content.push_str("\nnext\nlast");
+    fs.insert_file("/w/min.json", content.as_str());
+
+    // 截断的行已占满整页的字节上限，本页只返回这一行
+    let page = largefile::read_lines(&fs, None, "/w/min.json", 0, 3, None).await.unwrap();
+    assert_eq!(page.lines.len(), 1);
+    assert_eq!(page.lines[0].len(), MAX_RANGE_BYTES);
+    assert_eq!(page.truncated_lines, [0]);
+    assert!(!page.eof);
+
+    let rest = largefile::read_lines(&fs, None, "/w/min.json", 1, 3, None).await.unwrap();
+    assert_eq!(rest.lines, ["next", "last"]);
+    assert!(rest.truncated_lines.is_empty());
+    assert!(rest.eof);
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use zyma_lib::services::{FileSystem, MemoryFileSystem};
+
+#[tokio::test]
+async fn create_read_rename_and_remove() {
+    let fs = MemoryFileSystem::new(PathBuf::from("/untitled"));
+    fs.create_dir("a/b").await.unwrap();
+    fs.write_file("a/b/x.txt", "hello", &Default::default()).await.unwrap();
+    // 父目录不存在时与本地文件系统一样报错
+    assert!(fs.write_file("nope/x.txt", "hello", &Default::default()).await.is_err());
+
+    let items = fs.read_dir("a").await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].path, "/untitled/a/b");
+    assert!(items[0].is_dir);
+    assert_eq!(fs.read_file("/untitled/a/b/x.txt").await.unwrap().content, "hello");
+
+    fs.rename_item("a", "c").await.unwrap();
+    assert_eq!(fs.read_file("c/b/x.txt").await.unwrap().content, "hello");
+    assert_eq!(fs.stat("c/b/x.txt").await.unwrap().size, 5);
+    assert!(fs.rename_item("c", "c/b/inner").await.is_err());
+
+    fs.remove_item("c").await.unwrap();
+    assert!(fs.stat("c/b/x.txt").await.is_err());
+    assert!(fs.read_dir("").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn rename_matches_local_semantics() {
+    let fs = MemoryFileSystem::new(PathBuf::from("/w"));
+    fs.insert_file("a.txt", "a");
+    fs.insert_file("b.txt", "b");
+    fs.insert_file("full/keep.txt", "keep");
+    fs.insert_file("src/inner.txt", "inner");
+    fs.create_dir("empty").await.unwrap();
+
+    // 文件覆盖文件
+    fs.rename_item("a.txt", "b.txt").await.unwrap();
+    assert_eq!(fs.read_bytes("b.txt").await.unwrap(), b"a");
+    assert!(fs.stat("a.txt").await.is_err());
+
+    // 文件不能覆盖目录，目录不能覆盖文件
+    assert!(fs.rename_item("b.txt", "empty").await.is_err());
+    assert!(fs.rename_item("src", "b.txt").await.is_err());
+
+    // 目录不能覆盖非空目录，原内容保持不变
+    assert!(fs.rename_item("src", "full").await.is_err());
+    assert_eq!(fs.read_bytes("full/keep.txt").await.unwrap(), b"keep");
+    assert_eq!(fs.read_bytes("src/inner.txt").await.unwrap(), b"inner");
+
+    // 目录可以覆盖空目录
+    fs.rename_item("src", "empty").await.unwrap();
+    assert_eq!(fs.read_bytes("empty/inner.txt").await.unwrap(), b"inner");
+    assert!(fs.stat("src").await.is_err());
+}
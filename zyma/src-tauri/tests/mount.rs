@@ -0,0 +1,74 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use zyma_lib::commands::fs::WorkspaceService;
+use zyma_lib::models::FileItem;
+use zyma_lib::services::vfs::{FileStat, FsError};
+use zyma_lib::services::{FileSystem, LocalFileSystem, MemoryFileSystem};
+use zyma_lib::services::mount::scheme_of;
+use zyma_lib::services::sandbox::Caller;
+use zyma_lib::services::transfer::ConflictPolicy;
+
+#[tokio::test]
+async fn mounts() {
+    assert_eq!(scheme_of("mem:/a"), Some("mem"));
+    assert_eq!(scheme_of("C:/a"), None);
+    assert_eq!(scheme_of("/a/b"), None);
+    assert_eq!(scheme_of("notes:v2.md"), Some("notes"));
+    let dir = std::env::temp_dir().join(format!("mnt-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let d = dir.to_string_lossy().replace('\\', "/");
+    let ws = WorkspaceService::with_fs(Box::new(LocalFileSystem::new(dir.clone())));
+    assert!(ws.mount("file", Arc::new(MemoryFileSystem::new("/".into()))).is_err());
+    assert!(ws.mount("zip", Arc::new(MemoryFileSystem::new("/".into()))).is_err());
+    ws.mount("gen", Arc::new(MemoryFileSystem::new("/".into()))).unwrap();
+    assert_eq!(ws.fs.schemes(), vec!["gen", "mem"]);
+    let fs = ws.fs_for(Caller::User);
+    fs.create_dir("gen:/views").await.unwrap();
+    fs.write_bytes("gen:/views/a.txt", b"generated").await.unwrap();
+    let items = fs.read_dir("gen:/views").await.unwrap();
+    assert_eq!(items[0].path, "gen:/views/a.txt");
+    assert_eq!(fs.read_file("gen:/views/a.txt").await.unwrap().content, "generated");
+    fs.write_bytes(&format!("file://{}/local.txt", d), b"L").await.unwrap();
+    assert_eq!(std::fs::read(dir.join("local.txt")).unwrap(), b"L");
+    assert!(fs.read_bytes("nope:/x").await.is_err());
+    // 未注册的 "scheme" 是普通的文件名
+    fs.write_bytes("notes:v2.md", b"n").await.unwrap();
+    assert_eq!(std::fs::read(dir.join("notes:v2.md")).unwrap(), b"n");
+    assert_eq!(fs.read_dir(&d).await.unwrap().iter().filter(|i| i.name == "notes:v2.md").count(), 1);
+    // 跨实现移动
+    let noop = |_| {};
+    fs.move_item("gen:/views", &format!("{}/views", d), ConflictPolicy::Rename, "op", &noop).await.unwrap();
+    assert_eq!(std::fs::read(dir.join("views/a.txt")).unwrap(), b"generated");
+    assert!(fs.stat("gen:/views").await.is_err());
+    // 插件可以访问虚拟文档
+    let p = ws.fs_for(Caller::Plugin);
+    p.write_bytes("mem:/x.txt", b"m").await.unwrap();
+    assert_eq!(p.read_bytes("mem:/x.txt").await.unwrap(), b"m");
+    assert!(p.read_bytes("/etc/hostname").await.is_err());
+    assert!(p.set_cwd("mem:/").is_err());
+    // 未显式开放的实现 (如远程主机) 只对用户开放
+    ws.mount("remote", Arc::new(UserOnly(MemoryFileSystem::new("/".into())))).unwrap();
+    fs.write_bytes("remote:/r.txt", b"r").await.unwrap();
+    assert!(matches!(p.read_bytes("remote:/r.txt").await, Err(FsError::AccessDenied { .. })));
+    assert!(matches!(p.write_bytes("remote:/w.txt", b"w").await, Err(FsError::AccessDenied { .. })));
+    assert!(matches!(ws.fs_for(Caller::Agent).read_dir("remote:/").await, Err(FsError::AccessDenied { .. })));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 不开放给插件与 Agent 的实现 (使用 trait 的默认值)
+struct UserOnly(MemoryFileSystem);
+
+#[async_trait]
+impl FileSystem for UserOnly {
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> { self.0.read_dir(path).await }
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> { self.0.read_bytes(path).await }
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> { self.0.write_bytes(path, data).await }
+    async fn create_file(&self, path: &str) -> Result<(), FsError> { self.0.create_file(path).await }
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> { self.0.create_dir(path).await }
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> { self.0.remove_item(path).await }
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> { self.0.rename_item(from, to).await }
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> { self.0.stat(path).await }
+    fn get_cwd(&self) -> String { self.0.get_cwd() }
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> { self.0.set_cwd(path) }
+}
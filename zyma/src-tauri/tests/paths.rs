@@ -0,0 +1,94 @@
+use zyma_lib::services::paths::{self, AppDirs, DirsMode};
+use std::path::PathBuf;
+
+fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+    move |k| vars.iter().find(|(n, _)| *n == k).map(|(_, v)| v.to_string())
+}
+
+fn temp(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zyma-paths-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn xdg_directories_and_fallbacks() {
+    let dirs = AppDirs::resolve(&[], None, env(&[("HOME", "/home/u"), ("XDG_CONFIG_HOME", "/cfg"), ("XDG_CACHE_HOME", "relative")])).unwrap();
+    assert_eq!(dirs.mode, DirsMode::Standard);
+    assert_eq!(dirs.config, PathBuf::from("/cfg/zyma"));
+    assert_eq!(dirs.settings_file(), PathBuf::from("/cfg/zyma/settings.json"));
+    assert_eq!(dirs.data, PathBuf::from("/home/u/.local/share/zyma"));
+    // 相对路径按规范忽略
+    assert_eq!(dirs.cache, PathBuf::from("/home/u/.cache/zyma"));
+
+    // 没有主目录时报错，不退回共享的临时目录
+    let err = AppDirs::resolve(&[], None, env(&[])).unwrap_err();
+    assert!(err.contains("--user-data-dir"), "{}", err);
+    // XDG 变量齐全时不需要主目录
+    let xdg = AppDirs::resolve(&[], None, env(&[("XDG_CONFIG_HOME", "/c"), ("XDG_DATA_HOME", "/d"), ("XDG_CACHE_HOME", "/k")]));
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        assert_eq!(xdg.unwrap().data, PathBuf::from("/d/zyma"));
+    }
+    // 指定了数据目录时同样不需要
+    assert!(AppDirs::resolve(&["--user-data-dir=/iso".to_string()], None, env(&[])).is_ok());
+}
+
+#[test]
+fn user_data_dir_and_portable_mode() {
+    let args: Vec<String> = vec!["zyma".into(), "--user-data-dir".into(), "/tmp/iso".into()];
+    let dirs = AppDirs::resolve(&args, None, env(&[("HOME", "/home/u")])).unwrap();
+    assert_eq!(dirs.mode, DirsMode::Custom);
+    assert!(dirs.is_isolated());
+    assert_eq!(dirs.data, PathBuf::from("/tmp/iso/data"));
+    assert_eq!(paths::user_data_dir_arg(&["--user-data-dir=/x".to_string()]), Some(PathBuf::from("/x")));
+    assert_eq!(paths::user_data_dir_arg(&["--user-data-dir".to_string()]), None);
+    assert_eq!(paths::user_data_dir_arg(&["--user-data-dir=".to_string()]), None);
+    assert_eq!(paths::user_data_dir_arg(&["zyma".to_string(), "file.txt".to_string()]), None);
+    // 相对路径以当前目录为基准
+    let relative = paths::user_data_dir_arg(&["--user-data-dir".to_string(), "iso".to_string()]).unwrap();
+    assert_eq!(relative, std::env::current_dir().unwrap().join("iso"));
+    // 第一个出现的参数生效
+    let first = paths::user_data_dir_arg(&["--user-data-dir=/a".to_string(), "--user-data-dir=/b".to_string()]);
+    assert_eq!(first, Some(PathBuf::from("/a")));
+
+    let exe = temp("portable");
+    let dirs = AppDirs::resolve(&[], Some(&exe), env(&[("HOME", "/home/u")])).unwrap();
+    assert_eq!(dirs.mode, DirsMode::Standard);
+    std::fs::create_dir(exe.join(paths::PORTABLE_DIR)).unwrap();
+    let dirs = AppDirs::resolve(&[], Some(&exe), env(&[("HOME", "/home/u")])).unwrap();
+    assert_eq!(dirs.mode, DirsMode::Portable);
+    assert_eq!(dirs.config, exe.join("zyma-data").join("config"));
+    let _ = std::fs::remove_dir_all(&exe);
+}
+
+#[test]
+fn legacy_files_are_moved_once() {
+    let home = temp("legacy");
+    std::fs::write(home.join(".zyma_config.json"), r#"{"theme":"light"}"#).unwrap();
+    std::fs::create_dir_all(home.join(".zyma/history/abc")).unwrap();
+    std::fs::write(home.join(".zyma/history/abc/1.gz"), "x").unwrap();
+    std::fs::write(home.join(".zyma/trash.json"), "[]").unwrap();
+    std::fs::create_dir_all(home.join(".zyma/sessions")).unwrap();
+    std::fs::write(home.join(".zyma_config.json.corrupt"), "{").unwrap();
+
+    let root = home.join("new");
+    let dirs = AppDirs::resolve(&["--user-data-dir".to_string(), root.to_string_lossy().to_string()], None, env(&[])).unwrap();
+    // 新位置已有会话目录时保留旧目录不动
+    std::fs::create_dir_all(dirs.data_path("sessions")).unwrap();
+
+    let moved = paths::migrate_legacy(&home, &dirs);
+    assert_eq!(moved.len(), 4);
+    assert_eq!(std::fs::read_to_string(dirs.config.join("settings.json.corrupt")).unwrap(), "{");
+    assert_eq!(std::fs::read_to_string(dirs.settings_file()).unwrap(), r#"{"theme":"light"}"#);
+    assert!(dirs.data_path("history").join("abc/1.gz").exists());
+    assert!(!home.join(".zyma_config.json").exists());
+    assert!(home.join(".zyma/sessions").exists());
+
+    assert!(paths::migrate_legacy(&home, &dirs).is_empty());
+    // 已迁移的新文件不会被旧位置重新出现的文件覆盖
+    std::fs::write(home.join(".zyma_config.json"), r#"{"theme":"dark"}"#).unwrap();
+    assert!(paths::migrate_legacy(&home, &dirs).is_empty());
+    assert_eq!(std::fs::read_to_string(dirs.settings_file()).unwrap(), r#"{"theme":"light"}"#);
+    let _ = std::fs::remove_dir_all(&home);
+}
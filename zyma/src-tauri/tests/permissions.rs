@@ -0,0 +1,58 @@
+use zyma_lib::services::permissions::apply_mode_spec;
+use zyma_lib::services::{FileSystem, LocalFileSystem};
+
+#[test]
+fn mode_specs() {
+    assert_eq!(apply_mode_spec(0o644, "+x").unwrap(), 0o755);
+    assert_eq!(apply_mode_spec(0o755, "go-w").unwrap(), 0o755);
+    assert_eq!(apply_mode_spec(0o666, "go-w").unwrap(), 0o644);
+    assert_eq!(apply_mode_spec(0o644, "u+x,g=r").unwrap(), 0o744);
+    assert_eq!(apply_mode_spec(0o777, "a=r").unwrap(), 0o444);
+    assert_eq!(apply_mode_spec(0, "0640").unwrap(), 0o640);
+    assert!(apply_mode_spec(0, "u+q").is_err());
+    assert!(apply_mode_spec(0, "z+x").is_err());
+    assert!(apply_mode_spec(0, "u").is_err());
+    assert!(apply_mode_spec(0, "99").is_err());
+    assert!(apply_mode_spec(0, "").is_err());
+}
+
+#[test]
+fn special_bits() {
+    assert_eq!(apply_mode_spec(0o755, "u+s").unwrap(), 0o4755);
+    assert_eq!(apply_mode_spec(0o755, "g+s").unwrap(), 0o2755);
+    assert_eq!(apply_mode_spec(0o777, "+t").unwrap(), 0o1777);
+    assert_eq!(apply_mode_spec(0o4755, "u-s").unwrap(), 0o755);
+    assert_eq!(apply_mode_spec(0o4755, "4755").unwrap(), 0o4755);
+    assert!(apply_mode_spec(0, "17777").is_err());
+    // "=" 会清除对象对应的特殊位
+    assert_eq!(apply_mode_spec(0o4755, "u=rw").unwrap(), 0o655);
+    assert_eq!(apply_mode_spec(0o6755, "g=rx").unwrap(), 0o4755);
+    assert_eq!(apply_mode_spec(0o1777, "o=rx").unwrap(), 0o775);
+    assert_eq!(apply_mode_spec(0o7777, "a=r").unwrap(), 0o444);
+    // 与对象无关的特殊位保持不变
+    assert_eq!(apply_mode_spec(0o4755, "g=r").unwrap(), 0o4745);
+    // 未指定对象时 "=" 作用于全部
+    assert_eq!(apply_mode_spec(0o4777, "=rw").unwrap(), 0o666);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn chmod_and_readonly() {
+    let tmp = std::env::temp_dir().join(format!("zyma-perm-{}", std::process::id()));
+    std::fs::create_dir_all(&tmp).unwrap();
+    let f = tmp.join("run.sh");
+    std::fs::write(&f, "#!/bin/sh").unwrap();
+    let fs = LocalFileSystem::new(tmp.clone());
+    let p = f.to_str().unwrap();
+    fs.set_mode(p, 0o755).await.unwrap();
+    assert_eq!(fs.stat(p).await.unwrap().attrs.mode, Some(0o755));
+    fs.set_readonly(p, true).await.unwrap();
+    assert_eq!(fs.stat(p).await.unwrap().attrs.mode, Some(0o555));
+    assert!(fs.stat(p).await.unwrap().attrs.readonly);
+    fs.set_readonly(p, false).await.unwrap();
+    assert_eq!(fs.stat(p).await.unwrap().attrs.mode, Some(0o755));
+    assert!(fs.is_writable(p).await.unwrap());
+    assert!(fs.is_writable(tmp.join("new.txt").to_str().unwrap()).await.unwrap());
+    assert!(!fs.is_writable("/nonexistent-dir/x").await.unwrap());
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
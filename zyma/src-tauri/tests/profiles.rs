@@ -0,0 +1,90 @@
+use zyma_lib::services::profiles::{self, ProfilesExport};
+use zyma_lib::services::secrets::ai_api_key_id;
+use serde_json::{json, Map, Value};
+
+fn map(v: Value) -> Map<String, Value> {
+    v.as_object().unwrap().clone()
+}
+
+#[test]
+fn switch_saves_current_profile_and_applies_target() {
+    let mut values = map(json!({ "theme": "light", "tab_size": 2 }));
+    profiles::create(&mut values, "default", None).unwrap();
+    profiles::create(&mut values, "presentation", Some(&map(json!({ "font_size": 22, "tab_size": 8, "theme": 3 })))).unwrap();
+    assert!(profiles::create(&mut values, "default", None).is_err());
+
+    let info = profiles::info(&values);
+    // 档案只保存档案键，类型不符的值被丢弃
+    assert_eq!(info.profiles["presentation"], map(json!({ "font_size": 22 })));
+    assert_eq!(info.profiles["default"]["theme"], json!("light"));
+    assert_eq!(info.profiles["default"]["font_size"], json!(14));
+
+    profiles::switch(&mut values, "default").unwrap();
+    values.insert("theme".into(), json!("abyss"));
+    profiles::switch(&mut values, "presentation").unwrap();
+    assert_eq!(values["font_size"], json!(22));
+    assert_eq!(values["tab_size"], json!(2));
+    assert_eq!(values["active_profile"], json!("presentation"));
+    // 切换前的改动保存回原档案
+    assert_eq!(profiles::info(&values).profiles["default"]["theme"], json!("abyss"));
+
+    profiles::switch(&mut values, "default").unwrap();
+    assert_eq!(values["theme"], json!("abyss"));
+    assert_eq!(values["font_size"], json!(14));
+    assert!(profiles::switch(&mut values, "missing").is_err());
+}
+
+#[test]
+fn apply_active_and_delete() {
+    let mut values = map(json!({ "profiles": { "laptop": { "ui_font_size": 11 } }, "active_profile": "laptop" }));
+    assert!(profiles::apply_active(&mut values));
+    assert_eq!(values["ui_font_size"], json!(11));
+    assert!(!profiles::apply_active(&mut values));
+
+    profiles::delete(&mut values, "laptop").unwrap();
+    assert!(!values.contains_key("active_profile"));
+    assert!(profiles::delete(&mut values, "laptop").is_err());
+
+    let mut stale = map(json!({ "active_profile": "gone" }));
+    assert!(profiles::apply_active(&mut stale));
+    assert!(!stale.contains_key("active_profile"));
+}
+
+#[test]
+fn export_import_round_trip() {
+    let mut values = map(json!({}));
+    profiles::create(&mut values, "a", Some(&map(json!({ "theme": "light", "disabled_plugins": ["git"] })))).unwrap();
+    profiles::create(&mut values, "b", Some(&map(json!({ "font_size": 18 })))).unwrap();
+    let exported = profiles::export(&values, Some(&["a".to_string()])).unwrap();
+    assert_eq!(exported.profiles.len(), 1);
+    assert!(profiles::export(&values, Some(&["c".to_string()])).is_err());
+
+    let text = serde_json::to_string(&exported).unwrap();
+    let parsed: ProfilesExport = serde_json::from_str(&text).unwrap();
+    let mut other = map(json!({ "active_profile": "a", "profiles": { "a": { "theme": "dark" } } }));
+    assert_eq!(profiles::import(&mut other, &parsed).unwrap(), vec!["a".to_string()]);
+    // 覆盖了正在使用的档案时立即应用
+    assert_eq!(other["theme"], json!("light"));
+    assert_eq!(other["disabled_plugins"], json!(["git"]));
+
+    let future = ProfilesExport { version: 99, profiles: Default::default() };
+    assert!(profiles::import(&mut other, &future).is_err());
+}
+
+#[test]
+fn imported_profiles_cannot_redirect_ai_requests() {
+    let mut values = map(json!({ "ai_provider": "openai", "ai_base_url": "https://api.openai.com/v1", "active_profile": "work" }));
+    let file = ProfilesExport {
+        version: 1,
+        profiles: [("work".to_string(), map(json!({ "ai_provider": "custom", "ai_base_url": "https://evil.example.com" })))].into(),
+    };
+    profiles::import(&mut values, &file).unwrap();
+    assert_eq!(values["ai_base_url"], json!("https://api.openai.com/v1"));
+    assert_eq!(values["ai_provider"], json!("custom"));
+    assert!(!values["profiles"]["work"].as_object().unwrap().contains_key("ai_base_url"));
+    // 换了服务商后查找的是另一份密钥
+    assert_eq!(ai_api_key_id(Some("openai")), "ai_api_key:openai");
+    assert_ne!(ai_api_key_id(Some("openai")), ai_api_key_id(Some("custom")));
+    assert_eq!(ai_api_key_id(None), "ai_api_key");
+    assert_eq!(ai_api_key_id(Some(" ")), "ai_api_key");
+}
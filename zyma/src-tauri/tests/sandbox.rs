@@ -0,0 +1,72 @@
+#![cfg(unix)]
+
+use zyma_lib::models::AppSettings;
+use zyma_lib::services::sandbox::{Caller, CallerRegistry, SandboxMode, SandboxedFs};
+use zyma_lib::services::vfs::FsError;
+use zyma_lib::services::{FileSystem, LocalFileSystem};
+
+#[tokio::test]
+async fn plugins_are_confined_to_the_workspace() {
+    let tmp = std::env::temp_dir().join(format!("zyma-sandbox-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    let ws = tmp.join("ws");
+    let out = tmp.join("out");
+    std::fs::create_dir_all(&ws).unwrap();
+    std::fs::create_dir_all(&out).unwrap();
+    std::fs::write(out.join("o.txt"), "o").unwrap();
+    std::fs::write(out.join("opened.txt"), "o").unwrap();
+    std::os::unix::fs::symlink(&out, ws.join("link")).unwrap();
+    let fs = LocalFileSystem::new(ws.clone());
+    let outside = out.join("o.txt").to_string_lossy().to_string();
+    let opened = out.join("opened.txt").to_string_lossy().to_string();
+
+    assert!(fs.read_bytes(&outside).await.is_ok());
+    let plugin = SandboxedFs::new(&fs, Caller::Plugin);
+    assert!(matches!(plugin.read_bytes(&outside).await, Err(FsError::AccessDenied { .. })));
+    // 经由工作区内的符号链接也不能越界
+    assert!(matches!(plugin.read_bytes("link/o.txt").await, Err(FsError::AccessDenied { .. })));
+    assert!(plugin.write_bytes("new.txt", b"x").await.is_ok());
+    assert!(plugin.set_cwd("/").is_err());
+
+    // 只有用户打开的文件才会放行
+    plugin.allow_path(&opened);
+    assert!(plugin.read_bytes(&opened).await.is_err());
+    SandboxedFs::new(&fs, Caller::User).allow_path(&opened);
+    assert!(plugin.read_bytes(&opened).await.is_ok());
+
+    // 设置变化后重新应用沙箱模式
+    fs.apply_settings(&AppSettings { fs_sandbox: Some("read_only_outside".to_string()), ..Default::default() });
+    assert!(fs.read_bytes(&outside).await.is_ok());
+    assert!(matches!(fs.write_bytes(&outside, b"z").await, Err(FsError::AccessDenied { .. })));
+    assert!(matches!(fs.write_bytes("link/o.txt", b"z").await, Err(FsError::AccessDenied { .. })));
+    fs.apply_settings(&AppSettings::default());
+    assert_eq!(fs.sandbox_mode(), SandboxMode::Unrestricted);
+    assert!(fs.write_bytes(&outside, b"z").await.is_ok());
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn callers_are_resolved_on_the_backend() {
+    let registry = CallerRegistry::default();
+    registry.set_agent_commands(["agent_chat".to_string()]);
+
+    // 未领取令牌或不带令牌的请求视为插件
+    assert_eq!(registry.resolve("main", "read_file", None), Caller::Plugin);
+    let token = registry.claim_user_token("main").unwrap();
+    assert!(registry.claim_user_token("main").is_err());
+    assert_eq!(registry.resolve("main", "read_file", Some(&token)), Caller::User);
+    assert_eq!(registry.resolve("main", "read_file", Some("forged")), Caller::Plugin);
+    assert_eq!(registry.resolve("main", "read_file", None), Caller::Plugin);
+    // 令牌只在发放它的窗口有效
+    assert_eq!(registry.resolve("output", "read_file", Some(&token)), Caller::Plugin);
+    // 原生 Chat Participant 的命令始终是 Agent
+    assert_eq!(registry.resolve("main", "agent_chat", Some(&token)), Caller::Agent);
+
+    // 页面重新加载后旧令牌作废
+    registry.reset("main");
+    assert_eq!(registry.resolve("main", "read_file", Some(&token)), Caller::Plugin);
+    let renewed = registry.claim_user_token("main").unwrap();
+    assert_ne!(renewed, token);
+    assert_eq!(registry.resolve("main", "read_file", Some(&renewed)), Caller::User);
+}
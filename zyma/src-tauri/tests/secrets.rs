@@ -0,0 +1,66 @@
+use zyma_lib::services::secrets::{FileVault, SecretBackendKind, SecretService};
+
+fn temp_vault(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("zyma-secrets-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir.join("secrets.vault")
+}
+
+#[test]
+fn vault_round_trip_and_wrong_passphrase() {
+    let path = temp_vault("roundtrip");
+    let vault = FileVault::new(path.clone());
+    assert!(vault.set("ai_api_key", "sk-test").is_err());
+    vault.unlock("correct horse").unwrap();
+    vault.set("ai_api_key", "sk-test").unwrap();
+    assert_eq!(vault.get("ai_api_key").unwrap().as_deref(), Some("sk-test"));
+
+    let raw = std::fs::read_to_string(&path).unwrap();
+    assert!(!raw.contains("sk-test"));
+
+    let reopened = FileVault::new(path.clone());
+    assert_eq!(reopened.unlock("wrong").unwrap_err(), "Wrong passphrase");
+    assert!(reopened.get("ai_api_key").is_err());
+    reopened.unlock("correct horse").unwrap();
+    assert_eq!(reopened.get("ai_api_key").unwrap().as_deref(), Some("sk-test"));
+    reopened.delete("ai_api_key").unwrap();
+    assert_eq!(reopened.get("ai_api_key").unwrap(), None);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn entries_are_bound_to_their_id() {
+    let path = temp_vault("aad");
+    let vault = FileVault::new(path.clone());
+    vault.unlock("pass").unwrap();
+    vault.set("a", "secret-a").unwrap();
+    // 把 a 的密文挪到 b 下无法解密
+    let mut raw: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let sealed = raw["entries"]["a"].clone();
+    raw["entries"]["b"] = sealed;
+    std::fs::write(&path, raw.to_string()).unwrap();
+    assert!(vault.get("b").is_err());
+    // 篡改密文同样无法解密
+    let data = raw["entries"]["a"]["data"].as_str().unwrap().to_string();
+    let (first, rest) = data.split_at(1);
+    let flipped = format!("{}{}", if first == "0" { "1" } else { "0" }, rest);
+    raw["entries"]["a"]["data"] = serde_json::Value::String(flipped);
+    std::fs::write(&path, raw.to_string()).unwrap();
+    assert!(vault.get("a").is_err());
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn service_reports_vault_backend_until_unlocked() {
+    let path = temp_vault("service");
+    let service = SecretService::new(path.clone(), false);
+    let status = service.status();
+    assert_eq!(status.backend, SecretBackendKind::Vault);
+    assert!(!status.unlocked && !status.vault_exists);
+    service.unlock("pass").unwrap();
+    service.set("ai_api_key", "sk").unwrap();
+    assert!(service.status().unlocked && service.status().vault_exists);
+    service.lock();
+    assert!(service.get("ai_api_key").is_err());
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
@@ -0,0 +1,139 @@
+use zyma_lib::models::WorkspaceFolder;
+use zyma_lib::services::settings::{self, SettingsLayer, SettingsScope, SettingsTarget};
+use serde_json::{json, Map, Value};
+
+fn map(v: Value) -> Map<String, Value> {
+    v.as_object().unwrap().clone()
+}
+
+fn layer(scope: SettingsScope, v: Value) -> SettingsLayer {
+    SettingsLayer { scope, values: map(v) }
+}
+
+#[test]
+fn layers_merge_in_order_with_sources() {
+    let layers = vec![
+        layer(SettingsScope::User, json!({ "theme": "light", "tab_size": 8, "[rust]": { "tab_size": 2 } })),
+        layer(SettingsScope::Workspace, json!({ "tab_size": 3, "ai_model": "local", "[rust]": { "font_size": 20 } })),
+        layer(SettingsScope::Folder, json!({ "tab_size": 5 })),
+    ];
+    let eff = settings::resolve(&layers, None).unwrap();
+    assert_eq!(eff.settings.theme, "light");
+    assert_eq!(eff.settings.tab_size, 5);
+    assert_eq!(eff.settings.ai_model.as_deref(), Some("local"));
+    assert_eq!(eff.sources["tab_size"].scope, SettingsScope::Folder);
+    assert_eq!(eff.sources["theme"].scope, SettingsScope::User);
+    assert!(!eff.sources.contains_key("font_size"));
+
+    let rust = settings::resolve(&layers, Some("rust")).unwrap();
+    assert_eq!(rust.settings.tab_size, 2);
+    assert_eq!(rust.settings.font_size, 20);
+    assert_eq!(rust.sources["tab_size"].language.as_deref(), Some("rust"));
+    assert_eq!(rust.sources["font_size"].scope, SettingsScope::Workspace);
+}
+
+#[test]
+fn workspace_cannot_override_user_only_or_break_types() {
+    let layers = vec![
+        layer(SettingsScope::User, json!({ "ai_base_url": "https://api.example.com" })),
+        layer(SettingsScope::Folder, json!({ "ai_base_url": "https://evil.example.com", "tab_size": "two", "font_size": 16 })),
+    ];
+    let eff = settings::resolve(&layers, None).unwrap();
+    assert_eq!(eff.settings.ai_base_url.as_deref(), Some("https://api.example.com"));
+    assert_eq!(eff.settings.tab_size, 4);
+    assert_eq!(eff.settings.font_size, 16);
+
+    let mut target = Map::new();
+    assert!(settings::apply_patch(&mut target, SettingsScope::Workspace, &map(json!({ "ai_api_key": "x" })), None).is_err());
+    // 仓库内的配置不能启用被禁用的插件，也不能切换 AI 服务商
+    for patch in [json!({ "disabled_plugins": [] }), json!({ "ai_provider": "custom" })] {
+        assert!(settings::apply_patch(&mut target, SettingsScope::Folder, &map(patch), None).is_err());
+    }
+}
+
+#[test]
+fn saving_effective_settings_keeps_workspace_values_out_of_user_file() {
+    let user = map(json!({ "theme": "dark", "font_size": 14 }));
+    let layers = vec![
+        layer(SettingsScope::User, Value::Object(user.clone())),
+        layer(SettingsScope::Workspace, json!({ "font_size": 18 })),
+    ];
+    let eff = settings::resolve(&layers, None).unwrap();
+    let mut incoming = match serde_json::to_value(&eff).unwrap() { Value::Object(m) => m, _ => unreachable!() };
+    incoming.insert("theme".into(), json!("light"));
+
+    let saved = settings::user_layer_from_effective(&incoming, &eff, &user).unwrap();
+    assert_eq!(saved["theme"], json!("light"));
+    assert_eq!(saved["font_size"], json!(14));
+    assert!(!saved.contains_key("sources"));
+}
+
+#[test]
+fn saving_settings_ignores_backend_owned_keys() {
+    let user = map(json!({
+        "theme": "dark",
+        "recent_workspaces": ["/new", "/old"],
+        "session": { "root_path": "/new", "open_files": [], "active_file": null },
+        "window_width": 1200.0,
+        "active_profile": "work"
+    }));
+    let layers = vec![layer(SettingsScope::User, Value::Object(user.clone()))];
+    let eff = settings::resolve(&layers, None).unwrap();
+    // 设置面板打开期间后端已更新了会话等状态，回传的是打开面板时的旧值
+    let incoming = map(json!({
+        "theme": "light",
+        "recent_workspaces": ["/old"],
+        "session": null,
+        "window_width": 800.0,
+        "window_x": 5,
+        "ai_api_key_ref": "other",
+        "profiles": {},
+        "active_profile": null
+    }));
+    let saved = settings::user_layer_from_effective(&incoming, &eff, &user).unwrap();
+    assert_eq!(saved["theme"], json!("light"));
+    for key in ["recent_workspaces", "session", "window_width", "active_profile"] {
+        assert_eq!(saved[key], user[key], "{} was overwritten", key);
+    }
+    for key in ["window_x", "ai_api_key_ref", "profiles"] {
+        assert!(!saved.contains_key(key), "{} was written", key);
+    }
+}
+
+#[test]
+fn patch_language_block_and_remove_with_null() {
+    let mut target = map(json!({ "tab_size": 2 }));
+    settings::apply_patch(&mut target, SettingsScope::Folder, &map(json!({ "tab_size": 4 })), Some("rust")).unwrap();
+    assert_eq!(target["[rust]"], json!({ "tab_size": 4 }));
+    settings::apply_patch(&mut target, SettingsScope::Folder, &map(json!({ "tab_size": null })), Some("rust")).unwrap();
+    settings::apply_patch(&mut target, SettingsScope::Folder, &map(json!({ "tab_size": null })), None).unwrap();
+    assert!(target.is_empty());
+}
+
+#[test]
+fn locate_picks_folder_of_resource_and_round_trips_files() {
+    let dir = std::env::temp_dir().join(format!("zyma-settings-{}", std::process::id()));
+    let a = dir.join("a");
+    let b = dir.join("b");
+    std::fs::create_dir_all(&a).unwrap();
+    std::fs::create_dir_all(&b).unwrap();
+    let root = |p: &std::path::Path| WorkspaceFolder { name: String::new(), path: p.to_string_lossy().replace('\\', "/") };
+    let roots = vec![root(&a), root(&b)];
+
+    let file = format!("{}/x.rs", roots[1].path);
+    let targets = settings::locate(&roots, None, Some(&file));
+    assert_eq!(targets[0], (SettingsScope::Workspace, SettingsTarget::File(a.join(".zyma/settings.json"))));
+    assert_eq!(targets[1], (SettingsScope::Folder, SettingsTarget::File(b.join(".zyma/settings.json"))));
+    assert_eq!(settings::locate(&roots, None, None).len(), 1);
+
+    let ws_file = dir.join("proj.zyma-workspace");
+    std::fs::write(&ws_file, r#"{"folders":[{"path":"a"}],"custom":1}"#).unwrap();
+    let target = SettingsTarget::WorkspaceFile(ws_file.clone());
+    target.write(&map(json!({ "tab_size": 2 }))).unwrap();
+    assert_eq!(target.read().unwrap()["tab_size"], json!(2));
+    let raw: Value = serde_json::from_str(&std::fs::read_to_string(&ws_file).unwrap()).unwrap();
+    assert_eq!(raw["custom"], json!(1));
+    assert_eq!(raw["folders"][0]["path"], json!("a"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use zyma_lib::models::SshProfile;
+use zyma_lib::services::mount::MountTable;
+use zyma_lib::services::sandbox::{Caller, SandboxedFs};
+use zyma_lib::services::sftp::{self, SftpFileSystem, SshCredentials, SshError};
+use zyma_lib::services::vfs::FsError;
+use zyma_lib::services::{FileSystem, MemoryFileSystem};
+
+#[tokio::test]
+async fn unconnected_paths_error() {
+    let table = MountTable::new(Box::new(MemoryFileSystem::new("/w".into())));
+    let sftp = Arc::new(SftpFileSystem::new());
+    table.mount("sftp", sftp.clone()).unwrap();
+    let e = table.read_dir("sftp://box/home").await.unwrap_err().to_string();
+    assert!(e.contains("Not connected: box"), "{}", e);
+    let e = table.read_dir("sftp:/home").await.unwrap_err().to_string();
+    assert!(e.contains("Invalid remote path"), "{}", e);
+    assert!(sftp.connections().is_empty());
+    let profile: SshProfile = serde_json::from_str(r#"{"id":"box","host":"127.0.0.1","port":1,"username":"u"}"#).unwrap();
+    assert_eq!(profile.port, 1);
+    assert!(matches!(sftp.connect(&profile, &SshCredentials::default(), false), Err(SshError::Other(_))));
+    let profile: SshProfile = serde_json::from_str(r#"{"id":"a/b","host":"h","username":"u"}"#).unwrap();
+    assert_eq!(profile.port, 22);
+    assert!(sftp.connect(&profile, &SshCredentials::default(), false).is_err());
+}
+
+#[test]
+fn known_hosts_are_appended() {
+    // 与 ssh-keygen -lf 的输出格式一致
+    assert_eq!(sftp::fingerprint(&[0u8; 32]), "SHA256:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+
+    let dir = std::env::temp_dir().join(format!("zyma-known-hosts-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let path = dir.join(".ssh/known_hosts");
+    sftp::append_known_host(&path, "a ssh-ed25519 AAAA").unwrap();
+    // 已有内容 (libssh2 无法解析的行、注释) 保持原样，缺少的换行会被补上
+    let existing = "@cert-authority *.corp ssh-ed25519 AAAAC3\n# keep me";
+    std::fs::write(&path, existing).unwrap();
+    sftp::append_known_host(&path, "[h]:2222 ssh-ed25519 BBBB").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), format!("{}\n[h]:2222 ssh-ed25519 BBBB\n", existing));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// 针对真实的 SFTP 服务器，例如：
+/// `docker run -p 2222:22 -d atmoz/sftp dev:secret:::upload`
+/// `ZYMA_TEST_SFTP=dev:secret@127.0.0.1:2222/upload cargo test --test sftp -- --ignored`
+#[tokio::test]
+#[ignore = "requires a local SFTP server (ZYMA_TEST_SFTP=user:password@host:port/dir)"]
+async fn local_sftp_server() {
+    let spec = std::env::var("ZYMA_TEST_SFTP").expect("ZYMA_TEST_SFTP is not set");
+    let (credentials, address) = spec.split_once('@').unwrap();
+    let (username, password) = credentials.split_once(':').unwrap();
+    let (address, root) = address.split_once('/').unwrap_or((address, "upload"));
+    let (host, port) = address.split_once(':').unwrap_or((address, "22"));
+    let profile: SshProfile = serde_json::from_value(serde_json::json!({
+        "id": "local", "host": host, "port": port.parse::<u16>().unwrap(), "username": username,
+        "auth": "password", "remote_root": format!("/{}", root)
+    })).unwrap();
+
+    // known_hosts 写到临时的 HOME 下
+    let home = std::env::temp_dir().join(format!("zyma-sftp-home-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&home);
+    std::fs::create_dir_all(home.join(".ssh")).unwrap();
+    std::fs::write(home.join(".ssh/known_hosts"), "@revoked * ssh-rsa AAAAB3\n").unwrap();
+    std::env::set_var("HOME", &home);
+
+    let sftp = Arc::new(SftpFileSystem::new());
+    let fingerprint = match sftp.connect(&profile, &SshCredentials::default(), false) {
+        Err(SshError::UnknownHostKey { fingerprint, .. }) => fingerprint,
+        other => panic!("expected unknown_host_key, got {:?}", other.map(|c| c.id)),
+    };
+    assert!(fingerprint.starts_with("SHA256:") && !fingerprint[7..].contains(':'), "{}", fingerprint);
+    assert!(matches!(sftp.connect(&profile, &SshCredentials::default(), true), Err(SshError::AuthRequired { .. })));
+    let known_hosts = std::fs::read_to_string(home.join(".ssh/known_hosts")).unwrap();
+    assert!(known_hosts.starts_with("@revoked * ssh-rsa AAAAB3\n"));
+    assert_eq!(known_hosts.lines().count(), 2);
+
+    // 主机密钥已记录，不需要再次确认
+    let credentials = SshCredentials { password: Some(password.to_string()), passphrase: None };
+    let info = sftp.connect(&profile, &credentials, false).unwrap();
+    assert_eq!(info.root, format!("sftp://local/{}", root));
+
+    let table = MountTable::new(Box::new(MemoryFileSystem::new("/w".into())));
+    table.mount("sftp", sftp.clone()).unwrap();
+    let file = format!("{}/zyma-test.txt", info.root);
+    table.write_bytes(&file, b"first").await.unwrap();
+    // 覆盖已有文件
+    table.write_bytes(&file, b"second version").await.unwrap();
+    assert_eq!(table.read_bytes(&file).await.unwrap(), b"second version");
+    assert_eq!(table.read_range(&file, 7, 3).await.unwrap(), b"ver");
+    table.write_range(&file, 0, b"SECOND").await.unwrap();
+    assert_eq!(table.read_bytes(&file).await.unwrap(), b"SECOND version");
+    assert_eq!(table.stat(&file).await.unwrap().size, 14);
+    let items = table.read_dir(&info.root).await.unwrap();
+    assert!(items.iter().any(|i| i.path == file));
+    // 没有残留的临时文件或备份
+    assert!(!items.iter().any(|i| i.name.contains("zyma-tmp") || i.name.contains("zyma-bak")));
+
+    // 远程主机只对用户开放
+    let plugin = SandboxedFs::new(&table, Caller::Plugin);
+    assert!(matches!(plugin.read_bytes(&file).await, Err(FsError::AccessDenied { .. })));
+
+    table.remove_item(&file).await.unwrap();
+    assert!(table.stat(&file).await.is_err());
+    assert!(sftp.disconnect("local"));
+    std::fs::remove_dir_all(&home).unwrap();
+}
@@ -0,0 +1,61 @@
+#![cfg(unix)]
+
+use zyma_lib::services::{FileSystem, LocalFileSystem};
+use zyma_lib::services::transfer::ConflictPolicy;
+use std::os::unix::fs::symlink;
+
+#[tokio::test]
+async fn symlink_listing_and_copy() {
+    let tmp = std::env::temp_dir().join(format!("zyma-symlink-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    let src = tmp.join("src");
+    std::fs::create_dir_all(src.join("sub")).unwrap();
+    std::fs::write(src.join("a.txt"), "a").unwrap();
+    std::fs::write(src.join(".hidden"), "h").unwrap();
+    symlink("a.txt", src.join("link.txt")).unwrap();
+    symlink("missing", src.join("broken")).unwrap();
+    symlink("..", src.join("sub").join("up")).unwrap();
+    symlink(src.join("sub"), src.join("sublink")).unwrap();
+    let fs = LocalFileSystem::new(tmp.clone());
+    let items = fs.read_dir(src.to_str().unwrap()).await.unwrap();
+    let get = |n: &str| items.iter().find(|i| i.name == n).unwrap().clone();
+    assert!(get("link.txt").attrs.is_symlink && !get("link.txt").attrs.broken);
+    assert_eq!(get("link.txt").attrs.target.as_deref(), Some("a.txt"));
+    assert!(get("broken").attrs.broken && !get("broken").is_dir);
+    assert!(get(".hidden").attrs.hidden);
+    assert_eq!(get("a.txt").attrs.mode.map(|m| m & 0o600), Some(0o600));
+    assert!(get("sublink").is_dir && get("sublink").attrs.is_symlink);
+    println!("{}", serde_json::to_string(&get("broken")).unwrap());
+    let st = fs.stat(src.join("broken").to_str().unwrap()).await.unwrap();
+    assert_eq!(st.file_type, "symlink");
+    assert_eq!(fs.read_link(src.join("broken").to_str().unwrap()).await.unwrap(), "missing");
+    let dst = tmp.join("dst");
+    fs.copy_item(src.to_str().unwrap(), dst.to_str().unwrap(), ConflictPolicy::Rename, "op", &|_| {}).await.unwrap();
+    assert!(dst.join("sublink").join("a").exists() || dst.join("sublink").is_dir());
+    assert!(!dst.join("sub").join("up").exists());
+    assert!(!dst.join("broken").exists());
+    fs.remove_item(src.join("broken").to_str().unwrap()).await.unwrap();
+    fs.remove_item(src.join("sublink").to_str().unwrap()).await.unwrap();
+    assert!(src.join("sub").is_dir());
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[tokio::test]
+async fn copy_stops_at_links_back_through_other_links() {
+    let tmp = std::env::temp_dir().join(format!("zyma-symlink-cycle-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(tmp.join("data/home/proj")).unwrap();
+    std::fs::write(tmp.join("data/home/proj/main.rs"), "fn main() {}").unwrap();
+    // home -> data/home，proj/back 经由 home 指回被复制的目录
+    symlink(tmp.join("data/home"), tmp.join("home")).unwrap();
+    symlink(tmp.join("home"), tmp.join("data/home/proj/back")).unwrap();
+    let fs = LocalFileSystem::new(tmp.clone());
+    assert_eq!(fs.real_path(tmp.join("data/home/proj/back").to_str().unwrap()).await.unwrap(),
+        std::fs::canonicalize(tmp.join("data/home")).unwrap().to_string_lossy());
+
+    let dst = tmp.join("copy");
+    fs.copy_item(tmp.join("data/home").to_str().unwrap(), dst.to_str().unwrap(), ConflictPolicy::Rename, "op", &|_| {}).await.unwrap();
+    assert_eq!(std::fs::read_to_string(dst.join("proj/main.rs")).unwrap(), "fn main() {}");
+    assert!(!dst.join("proj/back").exists());
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
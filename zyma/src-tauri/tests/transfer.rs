@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use async_trait::async_trait;
+use zyma_lib::models::FileItem;
+use zyma_lib::services::mount::MountTable;
+use zyma_lib::services::transfer::ConflictPolicy;
+use zyma_lib::services::vfs::{FileStat, FsError};
+use zyma_lib::services::{FileSystem, LocalFileSystem, MemoryFileSystem};
+
+fn no_progress(_: zyma_lib::services::transfer::TransferProgress) {}
+
+#[tokio::test]
+async fn copy_and_move_with_conflict_policies() {
+    let fs = MemoryFileSystem::new(PathBuf::from("/w"));
+    fs.insert_file("/w/d/a.txt", "a");
+    fs.insert_file("/w/d/sub/b.txt", "b");
+    let count = AtomicUsize::new(0);
+    let progress = |_| { count.fetch_add(1, Ordering::SeqCst); };
+
+    assert_eq!(fs.copy_item("/w/d", "/w/e", ConflictPolicy::Rename, "op", &progress).await.unwrap().as_deref(), Some("/w/e"));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+    assert_eq!(fs.read_bytes("/w/e/sub/b.txt").await.unwrap(), b"b");
+    assert_eq!(fs.copy_item("/w/d/a.txt", "/w/d/a.txt", ConflictPolicy::Rename, "op", &progress).await.unwrap().as_deref(), Some("/w/d/a copy.txt"));
+    assert_eq!(fs.copy_item("/w/d/a.txt", "/w/d/a.txt", ConflictPolicy::Rename, "op", &progress).await.unwrap().as_deref(), Some("/w/d/a copy 2.txt"));
+    assert_eq!(fs.copy_item("/w/d/a.txt", "/w/e/a.txt", ConflictPolicy::Skip, "op", &progress).await.unwrap(), None);
+
+    // 不能复制到自身内部，也不能覆盖包含源的目录
+    assert!(fs.copy_item("/w/d", "/w/d/sub/x", ConflictPolicy::Rename, "op", &progress).await.is_err());
+    assert!(fs.copy_item("/w/d", "/w/d/sub", ConflictPolicy::Overwrite, "op", &progress).await.is_err());
+    assert!(fs.read_bytes("/w/d/sub/b.txt").await.is_ok());
+
+    assert_eq!(fs.move_item("/w/e", "/w/d", ConflictPolicy::Rename, "op", &progress).await.unwrap().as_deref(), Some("/w/d copy"));
+    assert!(fs.stat("/w/e").await.is_err());
+}
+
+#[tokio::test]
+async fn overwrite_replaces_target_without_leftovers() {
+    let fs = MemoryFileSystem::new(PathBuf::from("/w"));
+    fs.insert_file("/w/src/new.txt", "new");
+    fs.insert_file("/w/dst/old.txt", "old");
+    fs.insert_file("/w/file.txt", "file");
+
+    fs.copy_item("/w/src", "/w/dst", ConflictPolicy::Overwrite, "op", &no_progress).await.unwrap();
+    assert_eq!(fs.read_bytes("/w/dst/new.txt").await.unwrap(), b"new");
+    assert!(fs.stat("/w/dst/old.txt").await.is_err());
+
+    // 文件可以替换目录
+    fs.move_item("/w/file.txt", "/w/dst", ConflictPolicy::Overwrite, "op", &no_progress).await.unwrap();
+    assert_eq!(fs.read_bytes("/w/dst").await.unwrap(), b"file");
+    let mut names: Vec<String> = fs.read_dir("/w").await.unwrap().into_iter().map(|i| i.name).collect();
+    names.sort();
+    assert_eq!(names, ["dst", "src"]);
+}
+
+#[tokio::test]
+async fn local_copy_overwrite_and_move() {
+    let root = std::env::temp_dir().join(format!("zyma-transfer-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("d/s")).unwrap();
+    std::fs::write(root.join("d/s/f.txt"), "f").unwrap();
+    let fs = LocalFileSystem::new(root.clone());
+
+    fs.copy_item("d", "e", ConflictPolicy::Overwrite, "op", &no_progress).await.unwrap();
+    std::fs::write(root.join("e/stale.txt"), "stale").unwrap();
+    fs.copy_item("d", "e", ConflictPolicy::Overwrite, "op", &no_progress).await.unwrap();
+    assert_eq!(std::fs::read_to_string(root.join("e/s/f.txt")).unwrap(), "f");
+    assert!(!root.join("e/stale.txt").exists());
+
+    fs.move_item("e", "g", ConflictPolicy::Rename, "op", &no_progress).await.unwrap();
+    assert!(root.join("g/s/f.txt").exists());
+    let mut names: Vec<String> = std::fs::read_dir(&root).unwrap().map(|e| e.unwrap().file_name().into_string().unwrap()).collect();
+    names.sort();
+    assert_eq!(names, ["d", "g"]);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn copy_into_itself_is_detected_through_dotdot_and_symlinks() {
+    let root = std::env::temp_dir().join(format!("zyma-transfer-self-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("d/s")).unwrap();
+    std::fs::write(root.join("d/s/f.txt"), "f").unwrap();
+    std::os::unix::fs::symlink(root.join("d"), root.join("alias")).unwrap();
+    let fs = LocalFileSystem::new(root.clone());
+
+    assert!(fs.copy_item("d", "d/../d/s/x", ConflictPolicy::Rename, "op", &no_progress).await.is_err());
+    assert!(fs.copy_item("d", "alias/s/x", ConflictPolicy::Rename, "op", &no_progress).await.is_err());
+    assert!(fs.move_item("d", "alias/x", ConflictPolicy::Rename, "op", &no_progress).await.is_err());
+    assert!(fs.copy_item("alias", "d", ConflictPolicy::Overwrite, "op", &no_progress).await.is_err());
+    assert!(!root.join("d/s/x").exists() && !root.join("d/x").exists());
+    // 名称相近的兄弟目录不受影响
+    fs.copy_item("d", "d2", ConflictPolicy::Rename, "op", &no_progress).await.unwrap();
+    assert!(root.join("d2/s/f.txt").exists());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn moves_across_providers_by_copying() {
+    let root = std::env::temp_dir().join(format!("zyma-transfer-mount-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    let fs = MountTable::new(Box::new(LocalFileSystem::new(root.clone())));
+    fs.create_dir("mem:/notes").await.unwrap();
+    fs.write_bytes("mem:/notes/a.txt", b"a").await.unwrap();
+
+    assert!(matches!(fs.rename_item("mem:/notes", "notes").await, Err(FsError::CrossDevice { .. })));
+    fs.move_item("mem:/notes", "notes", ConflictPolicy::Rename, "op", &no_progress).await.unwrap();
+    assert_eq!(std::fs::read_to_string(root.join("notes/a.txt")).unwrap(), "a");
+    assert!(fs.stat("mem:/notes").await.is_err());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// 在内存文件系统外包一层，按需让 rename 或读取失败
+struct Flaky {
+    inner: MemoryFileSystem,
+    fail_rename: bool,
+    fail_read: Option<&'static str>,
+}
+
+#[async_trait]
+impl FileSystem for Flaky {
+    async fn read_dir(&self, path: &str) -> Result<Vec<FileItem>, FsError> { self.inner.read_dir(path).await }
+    async fn read_bytes(&self, path: &str) -> Result<Vec<u8>, FsError> {
+        if self.fail_read.is_some_and(|p| path.ends_with(p)) {
+            return Err("read failed".to_string().into());
+        }
+        self.inner.read_bytes(path).await
+    }
+    async fn write_bytes(&self, path: &str, data: &[u8]) -> Result<(), FsError> { self.inner.write_bytes(path, data).await }
+    async fn create_file(&self, path: &str) -> Result<(), FsError> { self.inner.create_file(path).await }
+    async fn create_dir(&self, path: &str) -> Result<(), FsError> { self.inner.create_dir(path).await }
+    async fn remove_item(&self, path: &str) -> Result<(), FsError> { self.inner.remove_item(path).await }
+    async fn rename_item(&self, from: &str, to: &str) -> Result<(), FsError> {
+        if self.fail_rename {
+            return Err("Permission denied".to_string().into());
+        }
+        self.inner.rename_item(from, to).await
+    }
+    async fn stat(&self, path: &str) -> Result<FileStat, FsError> { self.inner.stat(path).await }
+    fn get_cwd(&self) -> String { self.inner.get_cwd() }
+    fn set_cwd(&self, path: &str) -> Result<(), FsError> { self.inner.set_cwd(path) }
+}
+
+#[tokio::test]
+async fn failed_overwrite_keeps_existing_target() {
+    let fs = Flaky { inner: MemoryFileSystem::new(PathBuf::from("/w")), fail_rename: false, fail_read: Some("bad.txt") };
+    fs.inner.insert_file("/w/src/good.txt", "good");
+    fs.inner.insert_file("/w/src/bad.txt", "bad");
+    fs.inner.insert_file("/w/dst/keep.txt", "keep");
+
+    assert!(fs.copy_item("/w/src", "/w/dst", ConflictPolicy::Overwrite, "op", &no_progress).await.is_err());
+    assert_eq!(fs.read_bytes("/w/dst/keep.txt").await.unwrap(), b"keep");
+    let names: Vec<String> = fs.read_dir("/w").await.unwrap().into_iter().map(|i| i.name).collect();
+    assert_eq!(names, ["dst", "src"]);
+}
+
+#[tokio::test]
+async fn move_only_falls_back_to_copy_across_devices() {
+    let fs = Flaky { inner: MemoryFileSystem::new(PathBuf::from("/w")), fail_rename: true, fail_read: None };
+    fs.inner.insert_file("/w/a.txt", "a");
+
+    // 普通的 rename 失败 (如权限不足) 直接报错，不会复制后删除源
+    assert!(fs.move_item("/w/a.txt", "/w/b.txt", ConflictPolicy::Rename, "op", &no_progress).await.is_err());
+    assert_eq!(fs.read_bytes("/w/a.txt").await.unwrap(), b"a");
+    assert!(fs.stat("/w/b.txt").await.is_err());
+}
@@ -0,0 +1,74 @@
+#![cfg(target_os = "linux")]
+
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use zyma_lib::services::trash;
+
+// 登记文件与主目录回收站都取决于进程级的环境变量，整个流程放在同一个测试中顺序执行
+fn setup() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("zyma-trash-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(root.join("home")).unwrap();
+    std::env::set_var("HOME", root.join("home"));
+    for var in ["XDG_DATA_HOME", "XDG_CONFIG_HOME", "XDG_CACHE_HOME"] {
+        std::env::remove_var(var);
+    }
+    root
+}
+
+fn registry(root: &Path) -> PathBuf {
+    root.join("home/.local/share/zyma/trash.json")
+}
+
+#[tokio::test]
+async fn trash_restore_and_empty() {
+    let root = setup();
+    let home_trash = root.join("home/.local/share/Trash");
+
+    // 同名文件两次删除得到不同的条目，trashinfo 中的路径按规范做百分号编码
+    let file = root.join("a b%.txt");
+    std::fs::write(&file, "first").unwrap();
+    let first = trash::move_to_trash(&file).await.unwrap();
+    assert!(!file.exists());
+    std::fs::write(&file, "second").unwrap();
+    let second = trash::move_to_trash(&file).await.unwrap();
+    assert_ne!(first.id, second.id);
+    let info = std::fs::read_to_string(home_trash.join("info").join(format!("{}.trashinfo", first.id))).unwrap();
+    assert!(info.contains("a%20b%25.txt"), "{}", info);
+    assert_eq!(trash::list().await.unwrap().len(), 2);
+
+    trash::restore(&first.id).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "first");
+    assert!(!home_trash.join("info").join(format!("{}.trashinfo", first.id)).exists());
+    assert_eq!(trash::empty(None).await.unwrap(), 1);
+    assert!(trash::list().await.unwrap().is_empty());
+
+    // 其他文件系统上的文件进入该挂载点的 .Trash-$uid，而不是复制到主目录回收站
+    let shm = Path::new("/dev/shm");
+    let other_device = std::fs::metadata(shm).map(|m| m.dev() != std::fs::metadata(&root).unwrap().dev()).unwrap_or(false);
+    if other_device {
+        let dir = shm.join(format!("zyma-trash-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let remote = dir.join("note.txt");
+        std::fs::write(&remote, "on tmpfs").unwrap();
+        let entry = trash::move_to_trash(&remote).await.unwrap();
+        let trashed = PathBuf::from(entry.trashed_path.clone().unwrap());
+        let topdir_trash = trashed.parent().and_then(Path::parent).unwrap().to_path_buf();
+        assert!(topdir_trash.file_name().unwrap().to_string_lossy().starts_with(".Trash"), "{}", trashed.display());
+        assert_eq!(std::fs::metadata(&trashed).unwrap().dev(), std::fs::metadata(shm).unwrap().dev());
+        // 挂载点回收站中记录的是相对路径
+        let info = std::fs::read_to_string(topdir_trash.join("info").join(format!("{}.trashinfo", trashed.file_name().unwrap().to_string_lossy()))).unwrap();
+        assert!(info.contains(&format!("Path=zyma-trash-{}/note.txt", std::process::id())), "{}", info);
+
+        trash::restore(&entry.id).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&remote).unwrap(), "on tmpfs");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    // 损坏的登记文件保留一份 .corrupt，之后从空表开始
+    std::fs::write(registry(&root), "{ not json").unwrap();
+    assert!(trash::list().await.unwrap().is_empty());
+    assert_eq!(std::fs::read_to_string(root.join("home/.local/share/zyma/trash.json.corrupt")).unwrap(), "{ not json");
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
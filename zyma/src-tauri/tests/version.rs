@@ -0,0 +1,27 @@
+use std::path::PathBuf;
+use zyma_lib::services::vfs::FsError;
+use zyma_lib::services::{FileSystem, MemoryFileSystem};
+
+#[tokio::test]
+async fn check_version_detects_external_changes() {
+    let fs = MemoryFileSystem::new(PathBuf::from("/w"));
+    fs.insert_file("f", "one");
+    let version = fs.read_file("f").await.unwrap().version.unwrap();
+    fs.check_version("f", &version).await.unwrap();
+
+    fs.insert_file("f", "two");
+    match fs.check_version("f", &version).await.unwrap_err() {
+        FsError::Conflict { current: Some(current), .. } => assert_eq!(current.content, "two"),
+        e => panic!("unexpected error: {}", e),
+    }
+
+    fs.remove_item("f").await.unwrap();
+    assert!(matches!(fs.check_version("f", &version).await, Err(FsError::Conflict { current: None, .. })));
+}
+
+#[test]
+fn io_errors_serialize_as_plain_strings() {
+    assert_eq!(serde_json::to_string(&FsError::Io("x".into())).unwrap(), "\"x\"");
+    let json = serde_json::to_value(FsError::NotWritable { path: "/a".into() }).unwrap();
+    assert_eq!(json["kind"], "not_writable");
+}
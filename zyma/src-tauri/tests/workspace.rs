@@ -0,0 +1,41 @@
+use zyma_lib::services::sandbox::{Caller, SandboxedFs};
+use zyma_lib::services::vfs::FsError;
+use zyma_lib::services::workspace;
+use zyma_lib::services::{FileSystem, LocalFileSystem};
+
+#[tokio::test]
+async fn multi_root_workspace() {
+    let tmp = std::env::temp_dir().join(format!("zyma-workspace-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&tmp);
+    for dir in ["proj/server/front", "web", "proj/other/server"] {
+        std::fs::create_dir_all(tmp.join(dir)).unwrap();
+    }
+    std::fs::write(tmp.join("web/index.html"), "web").unwrap();
+    std::fs::write(tmp.join("web/app.js"), "app").unwrap();
+    std::fs::write(tmp.join("proj/server/front/index.html"), "server").unwrap();
+    std::fs::write(tmp.join("proj/other/server/readme.md"), "other").unwrap();
+    let file = tmp.join("proj/app.zyma-workspace");
+    std::fs::write(&file, r#"{"folders":[{"path":"server"},{"name":"front","path":"../web"},{"path":"other/server"}],"settings":{"a":1}}"#).unwrap();
+
+    let folders = workspace::load(&file).unwrap();
+    assert_eq!(folders[2].name, "server (2)");
+    let fs = LocalFileSystem::new(tmp.clone());
+    fs.set_roots(folders.clone()).unwrap();
+    assert!(fs.get_cwd().ends_with("proj/server"));
+
+    // "根名称/..." 指向对应根目录，但主根目录下的同名目录优先
+    assert_eq!(fs.read_bytes("front/index.html").await.unwrap(), b"server");
+    assert!(fs.read_bytes("front/app.js").await.is_err());
+    assert_eq!(fs.read_bytes("server (2)/readme.md").await.unwrap(), b"other");
+
+    let plugin = SandboxedFs::new(&fs, Caller::Plugin);
+    assert!(plugin.read_bytes(&tmp.join("web/app.js").to_string_lossy()).await.is_ok());
+    assert!(plugin.read_bytes(&file.to_string_lossy()).await.is_err());
+    // 插件不能修改工作区根目录
+    assert!(matches!(plugin.set_roots(folders[..1].to_vec()), Err(FsError::AccessDenied { .. })));
+    assert_eq!(fs.roots().len(), 3);
+
+    workspace::save(&file, &fs.roots()).unwrap();
+    assert_eq!(workspace::load(&file).unwrap(), folders);
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
@@ -1,4 +1,7 @@
 import { commands } from '../components/CommandSystem/CommandRegistry';
+import { invoke } from '../utils/ipc';
+import toast from 'react-hot-toast';
+import { formatFsError } from '../utils/fsError';
 
 export function registerFileCommands(t: any, handlers: any) {
     commands.registerCommand({
@@ -21,4 +24,17 @@ export function registerFileCommands(t: any, handlers: any) {
         category: 'File',
         callback: () => handlers.handleSave(true)
     });
+
+    commands.registerCommand({
+        id: 'file.makeExecutable',
+        title: t('MakeExecutable'),
+        category: 'File',
+        callback: async (path?: string) => {
+            const target = path || handlers.getActiveFilePath();
+            if (!target) return;
+            try {
+                await invoke('fs_chmod', { path: target, mode: '+x' });
+            } catch (e) { toast.error(formatFsError(e, t)); }
+        }
+    });
 }
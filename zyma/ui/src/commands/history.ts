@@ -0,0 +1,48 @@
+import { commands } from '../components/CommandSystem/CommandRegistry';
+import { invoke } from '../utils/ipc';
+import { ask } from '@tauri-apps/plugin-dialog';
+import toast from 'react-hot-toast';
+import { formatFsError } from '../utils/fsError';
+
+interface HistoryEntry { id: string; timestamp: number; size: number; hash: string; source: string; }
+
+const OUTPUT_CHANNEL = 'Local History';
+
+export function registerHistoryCommands(t: any, handlers: any) {
+    commands.registerCommand({
+        id: 'file.localHistory',
+        title: t('LocalHistory'),
+        category: 'File',
+        callback: async (path?: string) => {
+            const target = path || handlers.getActiveFilePath();
+            if (!target) return;
+            try {
+                const entries = await invoke<HistoryEntry[]>('history_list', { path: target });
+                if (entries.length === 0) {
+                    toast(t('NoLocalHistory'));
+                    return;
+                }
+                const list = entries.map((e, i) => `${i + 1}. ${new Date(e.timestamp).toLocaleString()} (${e.source})`).join('\n');
+                const picked = prompt(t('SelectHistoryEntry', { list }), '1');
+                const entry = picked ? entries[parseInt(picked, 10) - 1] : undefined;
+                if (!entry) return;
+
+                // 差异以 unified diff 文本输出到输出面板
+                const diff = await invoke<{ unified: string }>('history_diff', { path: target, id: entry.id });
+                await invoke('output_clear', { channel: OUTPUT_CHANNEL });
+                await invoke('output_append', { channel: OUTPUT_CHANNEL, content: diff.unified });
+
+                const name = target.split(/[\\/]/).pop();
+                if (!await ask(t('RestoreHistoryConfirm', { name }), { title: t('LocalHistory'), kind: 'warning' })) return;
+                await invoke<string>('history_restore', { path: target, id: entry.id });
+                // 已打开的编辑器同步为恢复后的内容
+                const res = await invoke<any>('read_file', { path: target });
+                const content = (res.content || '').replace(/\r\n/g, '\n');
+                handlers.fm.setOpenFiles((prev: any[]) => prev.map(f => f.path === target
+                    ? { ...f, content, originalContent: content, isDirty: false, version: res.version }
+                    : f));
+                toast.success(t('HistoryRestored'));
+            } catch (e) { toast.error(formatFsError(e, t)); }
+        }
+    });
+}
@@ -0,0 +1,92 @@
+import { commands } from '../components/CommandSystem/CommandRegistry';
+import { invoke } from '../utils/ipc';
+import { ask, open, save } from '@tauri-apps/plugin-dialog';
+import toast from 'react-hot-toast';
+
+interface ProfilesInfo { active: string | null; profiles: Record<string, Record<string, unknown>>; }
+
+// 以编号列表让用户选择一个档案
+async function pickProfile(t: any, promptKey: string): Promise<string | null> {
+    const info = await invoke<ProfilesInfo>('profile_list');
+    const names = Object.keys(info.profiles);
+    if (names.length === 0) {
+        toast(t('NoProfiles'));
+        return null;
+    }
+    const list = names.map((n, i) => `${i + 1}. ${n}${n === info.active ? ` (${t('Active')})` : ''}`).join('\n');
+    const picked = prompt(t(promptKey, { list }), '1');
+    return picked ? names[parseInt(picked, 10) - 1] ?? null : null;
+}
+
+export function registerProfileCommands(t: any) {
+    commands.registerCommand({
+        id: 'profiles.create',
+        title: t('CreateProfile'),
+        category: 'Profiles',
+        callback: async () => {
+            const name = prompt(t('ProfileName'));
+            if (!name) return;
+            try {
+                await invoke('profile_create', { name });
+                toast.success(t('ProfileCreated', { name }));
+            } catch (e) { toast.error(String(e)); }
+        }
+    });
+
+    commands.registerCommand({
+        id: 'profiles.switch',
+        title: t('SwitchProfile'),
+        category: 'Profiles',
+        callback: async (name?: string) => {
+            try {
+                const target = name || await pickProfile(t, 'SelectProfile');
+                if (!target) return;
+                // 设置变化通过 settings-changed 事件同步到界面与插件
+                await invoke('profile_switch', { name: target });
+                toast.success(t('ProfileSwitched', { name: target }));
+            } catch (e) { toast.error(String(e)); }
+        }
+    });
+
+    commands.registerCommand({
+        id: 'profiles.delete',
+        title: t('DeleteProfile'),
+        category: 'Profiles',
+        callback: async () => {
+            try {
+                const target = await pickProfile(t, 'SelectProfile');
+                if (!target) return;
+                if (!await ask(t('DeleteProfileConfirm', { name: target }), { title: t('DeleteProfile'), kind: 'warning' })) return;
+                await invoke('profile_delete', { name: target });
+            } catch (e) { toast.error(String(e)); }
+        }
+    });
+
+    commands.registerCommand({
+        id: 'profiles.export',
+        title: t('ExportProfiles'),
+        category: 'Profiles',
+        callback: async () => {
+            const path = await save({ defaultPath: 'zyma-profiles.json', filters: [{ name: 'JSON', extensions: ['json'] }] });
+            if (!path) return;
+            try {
+                await invoke('profile_export', { path });
+                toast.success(t('ProfilesExported'));
+            } catch (e) { toast.error(String(e)); }
+        }
+    });
+
+    commands.registerCommand({
+        id: 'profiles.import',
+        title: t('ImportProfiles'),
+        category: 'Profiles',
+        callback: async () => {
+            const path = await open({ filters: [{ name: 'JSON', extensions: ['json'] }] });
+            if (!path || Array.isArray(path)) return;
+            try {
+                const names = await invoke<string[]>('profile_import', { path });
+                toast.success(t('ProfilesImported', { names: names.join(', ') }));
+            } catch (e) { toast.error(String(e)); }
+        }
+    });
+}
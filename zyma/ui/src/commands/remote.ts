@@ -0,0 +1,92 @@
+import { commands } from '../components/CommandSystem/CommandRegistry';
+import { authRegistry, type Credentials } from '../components/PluginSystem/AuthRegistry';
+import { invoke } from '../utils/ipc';
+import { listen } from '@tauri-apps/api/event';
+import { ask } from '@tauri-apps/plugin-dialog';
+import toast from 'react-hot-toast';
+
+interface RemoteConnection { id: string; name: string; username: string; host: string; root: string; }
+
+// 建立 SSH 连接；按需确认未知主机密钥、询问密码或私钥口令 (仅用于本次连接，不保存)
+// 连接配置指定了 auth_provider 时先向该认证提供者索取凭据，拿不到或被拒绝时再询问用户
+export async function connectRemote(t: any, profileId: string, secrets: Credentials = {}, acceptHostKey = false, providerTried = false): Promise<RemoteConnection | null> {
+    try {
+        return await invoke<RemoteConnection>('ssh_connect', { profileId, ...secrets, acceptHostKey });
+    } catch (e: any) {
+        if (e?.kind === 'unknown_host_key') {
+            const ok = await ask(t('SshUnknownHostKey', { host: e.host, fingerprint: e.fingerprint }), { title: 'SSH', kind: 'warning' });
+            return ok ? connectRemote(t, profileId, secrets, true, providerTried) : null;
+        }
+        if (e?.kind === 'auth_required' && e.provider && !providerTried) {
+            const provider = authRegistry.getProvider(e.provider);
+            const provided = await provider?.getCredentials?.({ kind: 'ssh', profile: profileId, method: e.method }).catch(() => null);
+            if (provided) return connectRemote(t, profileId, provided, acceptHostKey, true);
+        }
+        if (e?.kind === 'auth_required') {
+            const isPassword = e.method === 'password';
+            const secret = prompt(t(isPassword ? 'SshEnterPassword' : 'SshEnterPassphrase', { profile: profileId }));
+            if (secret === null) return null;
+            return connectRemote(t, profileId, isPassword ? { password: secret } : { passphrase: secret }, acceptHostKey, true);
+        }
+        if (e?.kind === 'host_key_mismatch') {
+            toast.error(t('SshHostKeyMismatch', { host: e.host }));
+        } else {
+            toast.error(String(e));
+        }
+        return null;
+    }
+}
+
+export function registerRemoteCommands(t: any) {
+    // 每个连接配置作为一个账号提供者，登录即连接、注销即断开
+    const syncProfiles = async () => {
+        const settings = await invoke<any>('load_settings').catch(() => ({}));
+        (settings.ssh_profiles || []).forEach((p: any) => {
+            authRegistry.registerProvider({
+                id: `ssh:${p.id}`,
+                label: `SSH: ${p.name || `${p.username}@${p.host}`}`,
+                onLogin: async () => { await connectRemote(t, p.id); },
+                onLogout: async () => { await invoke('ssh_disconnect', { profileId: p.id }).catch(console.warn); }
+            });
+        });
+    };
+    const syncAccounts = (list: RemoteConnection[]) => {
+        authRegistry.getProviders().filter(p => p.id.startsWith('ssh:')).forEach(p => {
+            const conn = list.find(c => `ssh:${c.id}` === p.id);
+            authRegistry.updateAccount(p.id, conn ? `${conn.username}@${conn.host}` : undefined);
+        });
+    };
+    syncProfiles().then(() => invoke<RemoteConnection[]>('ssh_list_connections').then(syncAccounts)).catch(console.warn);
+    listen<RemoteConnection[]>('ssh_connections_changed', e => syncAccounts(e.payload));
+
+    commands.registerCommand({
+        id: 'remote.connect',
+        title: t('ConnectToRemote'),
+        category: 'Remote',
+        callback: async (profileId?: string) => {
+            const settings = await invoke<any>('load_settings').catch(() => ({}));
+            const profiles: any[] = settings.ssh_profiles || [];
+            if (profiles.length === 0) {
+                toast.error(t('NoSshProfiles'));
+                return;
+            }
+            const id = profileId || (profiles.length === 1
+                ? profiles[0].id
+                : prompt(t('SelectSshProfile', { profiles: profiles.map(p => p.id).join(', ') }), profiles[0].id));
+            if (!id) return;
+            const conn = await connectRemote(t, id);
+            if (conn) toast.success(t('RemoteConnected', { name: conn.name }));
+        }
+    });
+
+    commands.registerCommand({
+        id: 'remote.disconnect',
+        title: t('DisconnectRemote'),
+        category: 'Remote',
+        callback: async (profileId?: string) => {
+            const list = await invoke<RemoteConnection[]>('ssh_list_connections');
+            const id = profileId || (list.length === 1 ? list[0].id : prompt(t('SelectSshProfile', { profiles: list.map(c => c.id).join(', ') })));
+            if (id) await invoke('ssh_disconnect', { profileId: id }).catch(e => toast.error(String(e)));
+        }
+    });
+}
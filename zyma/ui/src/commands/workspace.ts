@@ -1,6 +1,6 @@
 import { commands } from '../components/CommandSystem/CommandRegistry';
-import { open } from '@tauri-apps/plugin-dialog';
-import { invoke } from '@tauri-apps/api/core';
+import { open, save } from '@tauri-apps/plugin-dialog';
+import { invoke } from '../utils/ipc';
 
 export function registerWorkspaceCommands(t: any, handlers: any) {
     commands.registerCommand({
@@ -14,7 +14,8 @@ export function registerWorkspaceCommands(t: any, handlers: any) {
                     const newPath = sel as string;
                     console.log("[Workspace] Switching to:", newPath);
                     
-                    // 1. 立即清理 UI 状态 (优化体验，防止闪烁)
+                    // 1. 保存离开的会话后立即清理 UI 状态 (优化体验，防止闪烁)
+                    await commands.executeCommand('workbench.saveSession');
                     handlers.fm.setOpenFiles([]);
                     handlers.fm.setActiveFilePath(null);
                     handlers.setActiveTabId(null);
@@ -31,4 +32,52 @@ export function registerWorkspaceCommands(t: any, handlers: any) {
             }
         }
     });
+
+    commands.registerCommand({
+        id: 'workspace.openWorkspace',
+        title: t('OpenWorkspace'),
+        category: 'Workspace',
+        callback: async () => {
+            try {
+                const sel = await open({ filters: [{ name: 'Zyma Workspace', extensions: ['zyma-workspace'] }] });
+                if (sel) {
+                    await commands.executeCommand('workbench.saveSession');
+                    handlers.fm.setOpenFiles([]);
+                    handlers.fm.setActiveFilePath(null);
+                    handlers.setActiveTabId(null);
+                    await invoke('workspace_open', { path: sel as string });
+                }
+            } catch (e) {
+                console.error("[Workspace] Failed to open workspace:", e);
+            }
+        }
+    });
+
+    commands.registerCommand({
+        id: 'workspace.addFolder',
+        title: t('AddFolderToWorkspace'),
+        category: 'Workspace',
+        callback: async () => {
+            try {
+                const sel = await open({ directory: true });
+                if (sel) await invoke('workspace_add_folder', { path: sel as string });
+            } catch (e) {
+                console.error("[Workspace] Failed to add folder:", e);
+            }
+        }
+    });
+
+    commands.registerCommand({
+        id: 'workspace.saveAs',
+        title: t('SaveWorkspaceAs'),
+        category: 'Workspace',
+        callback: async () => {
+            try {
+                const target = await save({ filters: [{ name: 'Zyma Workspace', extensions: ['zyma-workspace'] }] });
+                if (target) await invoke('workspace_save', { path: target });
+            } catch (e) {
+                console.error("[Workspace] Failed to save workspace:", e);
+            }
+        }
+    });
 }
@@ -1,7 +1,7 @@
 import React, { useState, useEffect } from 'react';
 import { ExternalLink, RefreshCw, Download } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import { check } from '@tauri-apps/plugin-updater';
 import Modal from '../Common/Modal';
 
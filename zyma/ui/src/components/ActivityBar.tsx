@@ -5,7 +5,7 @@ import { authRegistry } from './PluginSystem/AuthRegistry';
 import AccountMenu from './PluginSystem/AccountMenu';
 import { DynamicIcon } from './Common/DynamicIcon';
 import { useTranslation } from 'react-i18next';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import { listen, emit } from '@tauri-apps/api/event';
 import { commands } from './CommandSystem/CommandRegistry';
 import { slotRegistry } from '../core/SlotRegistry';
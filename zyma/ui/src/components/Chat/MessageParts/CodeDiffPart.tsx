@@ -1,6 +1,6 @@
 import React, { useState } from 'react';
 import { Check, FileCode } from 'lucide-react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../../utils/ipc';
 
 interface CodeDiffPartProps {
     original: string;
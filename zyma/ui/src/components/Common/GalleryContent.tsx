@@ -1,5 +1,5 @@
 import React, { useEffect, useState } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import { DynamicIcon } from './DynamicIcon';
 import { listen } from '@tauri-apps/api/event';
 
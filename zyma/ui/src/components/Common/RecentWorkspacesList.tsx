@@ -1,5 +1,5 @@
 import React from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import { DynamicIcon } from './DynamicIcon';
 import { useTranslation } from 'react-i18next';
 import { commands } from '../CommandSystem/CommandRegistry';
@@ -13,10 +13,9 @@ export const RecentWorkspacesList: React.FC = () => {
     const handleRemoveRecent = async (e: React.MouseEvent, pathToRemove: string) => {
         e.stopPropagation();
         try {
-            const newRecents = recents.filter((p: string) => p !== pathToRemove);
-            const newSettings = { ...settings, recent_workspaces: newRecents };
-            await invoke('save_settings', { settings: newSettings });
-            if (setSettings) setSettings(newSettings);
+            // 最近工作区由后端维护，save_settings 会忽略该键
+            const newRecents = await invoke<string[]>('workspace_forget_recent', { path: pathToRemove });
+            if (setSettings) setSettings({ ...settings, recent_workspaces: newRecents });
         } catch (e) { console.error("Failed to remove recent workspace:", e); }
     };
 
@@ -1,6 +1,7 @@
 import React, { useEffect } from 'react';
 import { useCodeMirror } from './useCodeMirror';
 import { statusBar } from '../StatusBar/StatusBarRegistry';
+import { viewStateCache } from '../../utils/viewStateCache';
 
 interface EditorProps {
     content: string;
@@ -17,9 +18,21 @@ const Editor: React.FC<EditorProps> = (props) => {
         ...props,
         onCursorUpdate: (line, col) => {
             statusBar.setCursorPosition(line, col);
+            if (viewRef.current) viewStateCache.capture(props.filePath, viewRef.current);
         }
     });
 
+    // 记录滚动位置，并恢复会话中保存的光标与滚动 (搜索跳转优先)
+    useEffect(() => {
+        const view = viewRef.current;
+        if (!view) return;
+        const jump = (window as any).__pendingLineJump;
+        if (!jump || jump.path !== props.filePath) viewStateCache.apply(props.filePath, view);
+        const onScroll = () => viewStateCache.capture(props.filePath, view);
+        view.scrollDOM.addEventListener('scroll', onScroll, { passive: true });
+        return () => view.scrollDOM.removeEventListener('scroll', onScroll);
+    }, [viewRef.current, props.filePath]);
+
     useEffect(() => {
         if (props.editorRef) {
             props.editorRef.current = viewRef.current;
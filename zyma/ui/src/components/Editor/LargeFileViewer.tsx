@@ -0,0 +1,99 @@
+import React, { useCallback, useEffect, useState } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { useTranslation } from 'react-i18next';
+import { invoke } from '../../utils/ipc';
+import { formatFsError } from '../../utils/fsError';
+
+// 每页行数 (后端单次最多返回 10000 行)
+const PAGE_LINES = 1000;
+
+interface LargeFileViewerProps {
+    path: string;
+    fontSize: number;
+}
+
+// 超大文件的只读分页视图：按行分页读取，后台建立行索引后可以快速跳转到任意行
+const LargeFileViewer: React.FC<LargeFileViewerProps> = ({ path, fontSize }) => {
+    const { t } = useTranslation();
+    const [startLine, setStartLine] = useState(0);
+    const [lines, setLines] = useState<string[]>([]);
+    const [truncated, setTruncated] = useState<Set<number>>(new Set());
+    const [eof, setEof] = useState(false);
+    const [totalLines, setTotalLines] = useState<number | null>(null);
+    const [progress, setProgress] = useState(0);
+    const [error, setError] = useState<string | null>(null);
+    const [jumpTo, setJumpTo] = useState('');
+
+    useEffect(() => {
+        let disposed = false;
+        let unlisten: (() => void) | undefined;
+        const onStatus = (s: any) => {
+            if (s.path !== path) return;
+            setProgress(s.size ? Math.floor(s.bytes_indexed * 100 / s.size) : 100);
+            if (s.total_lines != null) setTotalLines(s.total_lines);
+        };
+        listen<any>('line_index_progress', e => onStatus(e.payload)).then(u => {
+            if (disposed) u(); else unlisten = u;
+        });
+        invoke<any>('fs_build_line_index', { path }).then(onStatus).catch(e => setError(formatFsError(e, t)));
+        return () => { disposed = true; unlisten?.(); };
+    }, [path, t]);
+
+    useEffect(() => {
+        let cancelled = false;
+        invoke<any>('fs_read_lines', { path, startLine, count: PAGE_LINES })
+            .then(res => {
+                if (cancelled) return;
+                setLines(res.lines);
+                setTruncated(new Set(res.truncated_lines));
+                setEof(res.eof);
+                if (res.total_lines != null) setTotalLines(res.total_lines);
+                setError(null);
+            })
+            .catch(e => { if (!cancelled) setError(formatFsError(e, t)); });
+        return () => { cancelled = true; };
+    }, [path, startLine, t]);
+
+    const goTo = useCallback((line: number) => {
+        const last = totalLines != null ? Math.max(0, totalLines - 1) : Number.MAX_SAFE_INTEGER;
+        setStartLine(Math.min(Math.max(0, line), last));
+    }, [totalLines]);
+
+    const onJump = (e: React.FormEvent) => {
+        e.preventDefault();
+        const line = parseInt(jumpTo, 10);
+        if (!isNaN(line)) goTo(line - 1);
+    };
+
+    const buttonStyle: React.CSSProperties = { padding: '2px 10px', cursor: 'pointer' };
+
+    return (
+        <div style={{ display: 'flex', flexDirection: 'column', height: '100%', backgroundColor: 'var(--bg-editor)', color: 'var(--text-primary)' }}>
+            <div style={{ display: 'flex', alignItems: 'center', gap: '8px', padding: '6px 12px', borderBottom: '1px solid var(--border-color)', fontSize: '12px' }}>
+                <span style={{ opacity: 0.8 }}>{t('LargeFileReadOnly')}</span>
+                <button style={buttonStyle} disabled={startLine === 0} onClick={() => goTo(startLine - PAGE_LINES)}>{t('LargeFilePreviousPage')}</button>
+                <button style={buttonStyle} disabled={eof} onClick={() => goTo(startLine + Math.max(1, lines.length))}>{t('LargeFileNextPage')}</button>
+                <span>{t('LargeFileLines', { from: startLine + 1, to: startLine + lines.length, total: totalLines ?? '?' })}</span>
+                <form onSubmit={onJump} style={{ marginLeft: 'auto', display: 'flex', gap: '4px' }}>
+                    <input value={jumpTo} onChange={e => setJumpTo(e.target.value)} placeholder={t('LargeFileGoToLine')} style={{ width: '110px' }} />
+                </form>
+                {totalLines == null && <span style={{ opacity: 0.6 }}>{t('LargeFileIndexing', { percent: progress })}</span>}
+            </div>
+            {error ? (
+                <div style={{ padding: '12px', color: 'var(--error-color, #f14c4c)' }}>{error}</div>
+            ) : (
+                <div style={{ flex: 1, overflow: 'auto', fontFamily: 'var(--font-mono, monospace)', fontSize: `${fontSize}px`, lineHeight: 1.5, padding: '4px 0' }}>
+                    {lines.map((line, i) => (
+                        <div key={startLine + i} style={{ display: 'flex', whiteSpace: 'pre' }}>
+                            <span style={{ minWidth: '80px', paddingRight: '12px', textAlign: 'right', opacity: 0.5, userSelect: 'none' }}>{startLine + i + 1}</span>
+                            <span>{line}</span>
+                            {truncated.has(startLine + i) && <span style={{ opacity: 0.5, paddingLeft: '8px' }}>{t('LargeFileLineTruncated')}</span>}
+                        </div>
+                    ))}
+                </div>
+            )}
+        </div>
+    );
+};
+
+export default LargeFileViewer;
@@ -1,9 +1,23 @@
+// 向认证提供者索取连接凭据 (如 SSH 连接配置的 auth_provider 指向该提供者时)
+export interface CredentialRequest {
+    kind: 'ssh';
+    profile: string;
+    method: string;
+}
+
+export interface Credentials {
+    password?: string;
+    passphrase?: string;
+}
+
 export interface AuthProvider {
     id: string;
     label: string;
     accountName?: string;
     onLogin: () => Promise<void>;
     onLogout: () => Promise<void>;
+    // 返回 null 表示无法提供，由调用方询问用户
+    getCredentials?: (request: CredentialRequest) => Promise<Credentials | null>;
 }
 
 export class AuthRegistry {
@@ -29,7 +43,8 @@ export class AuthRegistry {
             existing.label === p.label && 
             existing.accountName === p.accountName && 
             existing.onLogin === p.onLogin && 
-            existing.onLogout === p.onLogout) {
+            existing.onLogout === p.onLogout &&
+            existing.getCredentials === p.getCredentials) {
             return;
         }
         this.providers.set(p.id, p);
@@ -49,6 +64,10 @@ export class AuthRegistry {
         }
     }
 
+    getProvider(id: string) {
+        return this.providers.get(id);
+    }
+
     getProviders() {
         return Array.from(this.providers.values());
     }
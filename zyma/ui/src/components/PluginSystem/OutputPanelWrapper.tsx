@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import { listen } from '@tauri-apps/api/event';
 import OutputPanel from './OutputPanel';
 
@@ -1,3 +1,4 @@
+// 插件一律使用不带用户令牌的 invoke，后端据此按插件身份处理 (见 utils/ipc.ts)
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { commands } from '../CommandSystem/CommandRegistry';
@@ -43,7 +44,7 @@ export class PluginAPIBuilder {
                 writeFile: (path: string, content: string) => invoke('write_file', { path, content }),
                 stat: (path: string) => invoke('fs_stat', { path }),
                 readDirectory: (path: string) => invoke('read_dir', { path }),
-                findFiles: (baseDir: string, include: string, exclude?: string) => invoke('find_files', { baseDir, include, exclude }),
+                findFiles: (baseDir: string, include: string, exclude?: string) => invoke('fs_find_files', { baseDir, include, exclude }),
                 createFileSystemWatcher: (path: string): FileSystemWatcher => ({
                     onDidCreate: (handler: any) => listen('fs-create:' + path, (e) => handler(e.payload)),
                     onDidChange: (handler: any) => listen('fs-change:' + path, (e) => handler(e.payload)),
@@ -140,6 +141,7 @@ export class PluginAPIBuilder {
             },
             system: {
                 version: "0.9.5",
+                // 以插件身份调用：文件类命令被限制在工作区内，执行程序、修改设置等命令会被拒绝
                 invoke: (cmd: string, args?: any) => invoke(cmd, args),
                 getEnv: (name: string) => invoke('system_get_env', { name }),
                 exec: (command: string, args: string[]) => invoke('system_exec', { program: command, args })
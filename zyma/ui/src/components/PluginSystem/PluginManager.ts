@@ -1,5 +1,5 @@
-import { invoke } from '@tauri-apps/api/core';
-import type { UnlistenFn } from '@tauri-apps/api/event';
+import { invoke } from '../../utils/ipc';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type { PluginManifest } from './types';
 import { PluginAPIBuilder } from './PluginAPIBuilder';
 import { ContributionRegistry } from './ContributionRegistry';
@@ -12,6 +12,8 @@ export class PluginManager {
     private contributionRegistry: ContributionRegistry;
     private listeners: (() => void)[] = [];
     private callbacks: any;
+    // 已禁用的插件，保存在设置的 disabled_plugins 中 (随配置档案切换)
+    private disabled: string[] = [];
 
     constructor(callbacks: any) {
         this.callbacks = callbacks;
@@ -19,6 +21,34 @@ export class PluginManager {
             components: callbacks.components,
             addFileMenuItem: (item) => callbacks.addFileMenuItem(item)
         });
+        // 切换配置档案或手动修改配置后重新加载插件
+        listen<{ scope: string, keys: string[] }>('settings-changed', async (event) => {
+            if (!event.payload.keys.includes('disabled_plugins')) return;
+            const disabled = await this.readDisabled();
+            if (JSON.stringify(disabled) !== JSON.stringify(this.disabled)) await this.loadAll();
+        });
+    }
+
+    private async readDisabled(): Promise<string[]> {
+        const settings = await invoke<{ disabled_plugins?: string[] }>('load_settings').catch(() => null);
+        const disabled = settings?.disabled_plugins || [];
+        // 旧版保存在 localStorage 中，迁移一次
+        const legacy = localStorage.getItem('zyma_disabled_plugins');
+        if (legacy !== null) {
+            localStorage.removeItem('zyma_disabled_plugins');
+            const merged = Array.from(new Set([...disabled, ...JSON.parse(legacy || '[]')]));
+            if (merged.length !== disabled.length) {
+                await this.saveDisabled(merged);
+                return merged;
+            }
+        }
+        return disabled;
+    }
+
+    private async saveDisabled(disabled: string[]) {
+        this.disabled = disabled;
+        await invoke('settings_update', { scope: 'user', values: { disabled_plugins: disabled } })
+            .catch(e => console.error('[PluginManager] Failed to save disabled plugins', e));
     }
 
     subscribe(listener: () => void) {
@@ -45,15 +75,14 @@ export class PluginManager {
     }
 
     getLoadedPlugins() {
-        const disabled = JSON.parse(localStorage.getItem('zyma_disabled_plugins') || '[]');
         return Array.from(this.manifests.values()).map(m => ({
-            ...m, id: m.name, enabled: !disabled.includes(m.name)
+            ...m, id: m.name, enabled: !this.disabled.includes(m.name)
         }));
     }
 
     async loadAll() {
         try {
-            const disabledPlugins = JSON.parse(localStorage.getItem('zyma_disabled_plugins') || '[]');
+            this.disabled = await this.readDisabled();
             const pluginList = await invoke<[string, PluginManifest, boolean][]>('list_plugins');
             
             // 清理
@@ -61,7 +90,7 @@ export class PluginManager {
             this.manifests.clear();
 
             for (const [dirPath, manifest, isBuiltin] of pluginList) {
-                const isEnabled = !disabledPlugins.includes(manifest.name);
+                const isEnabled = !this.disabled.includes(manifest.name);
                 this.manifests.set(manifest.name, { ...manifest, path: dirPath, isBuiltin });
                 
                 if (isEnabled) {
@@ -125,17 +154,14 @@ export class PluginManager {
     }
 
     async enablePlugin(name: string) {
-        const disabled = JSON.parse(localStorage.getItem('zyma_disabled_plugins') || '[]');
-        localStorage.setItem('zyma_disabled_plugins', JSON.stringify(disabled.filter((n: string) => n !== name)));
+        await this.saveDisabled(this.disabled.filter(n => n !== name));
         await this.loadAll();
     }
 
     async disablePlugin(name: string) {
         await this.unloadPlugin(name, true);
-        const disabled = JSON.parse(localStorage.getItem('zyma_disabled_plugins') || '[]');
-        if (!disabled.includes(name)) {
-            disabled.push(name);
-            localStorage.setItem('zyma_disabled_plugins', JSON.stringify(disabled));
+        if (!this.disabled.includes(name)) {
+            await this.saveDisabled([...this.disabled, name]);
         }
         this.notifyUI();
     }
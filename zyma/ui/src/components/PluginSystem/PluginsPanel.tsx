@@ -1,7 +1,7 @@
 import React, { useState, useMemo, useEffect } from 'react';
 import { Search, Trash2, Ban, Play } from 'lucide-react';
 import { useTranslation } from 'react-i18next';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import { ask } from '@tauri-apps/plugin-dialog';
 import { DynamicIcon } from '../Common/DynamicIcon';
 
@@ -51,7 +51,7 @@ const PluginsPanel: React.FC<PluginsPanelProps> = ({ pluginManager, onUpdate })
             try {
                 await pluginManager.unloadPlugin(p.name);
                 if (p.path) {
-                    await invoke('remove_item', { path: p.path });
+                    await invoke('remove_item', { path: p.path, permanent: true });
                     await pluginManager.loadAll();
                     onUpdate();
                 }
@@ -117,7 +117,9 @@ export interface ZymaAPI {
             label: string,
             accountName?: string,
             onLogin: () => Promise<void>,
-            onLogout: () => Promise<void>
+            onLogout: () => Promise<void>,
+            // SSH 连接配置的 auth_provider 指向该提供者时调用，返回 null 则询问用户
+            getCredentials?: (request: { kind: 'ssh', profile: string, method: string }) => Promise<{ password?: string, passphrase?: string } | null>
         }) => void;
         unregisterAuthenticationProvider: (id: string) => void;
     };
@@ -1,5 +1,5 @@
 import React, { useState, useMemo, useCallback, useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import {
     ChevronRight, ChevronDown, FileCode,
     X, RefreshCw, Layers, CaseSensitive,
@@ -115,8 +115,8 @@ const SearchPanel: React.FC<SearchPanelProps> = () => {
         }
         setIsSearching(true);
         try {
+            // 不指定 root：后端会搜索工作区的全部根目录
             const data = await invoke<SearchResult[]>('search_in_dir', { 
-                root: rootPath, 
                 pattern: query,
                 case_sensitive: caseSensitive,
                 whole_word: wholeWord,
@@ -1,6 +1,7 @@
-import React from 'react';
+import React, { useEffect, useState } from 'react';
 import { useTranslation } from 'react-i18next';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
+import toast from 'react-hot-toast';
 import Modal from '../Common/Modal';
 
 export interface AppSettings {
@@ -27,9 +28,14 @@ export interface AppSettings {
     recent_workspaces?: string[];
     // AI Settings
     ai_provider?: string;
+    // 只用于提交新密钥，读取设置时始终为空
     ai_api_key?: string;
+    // 密钥存储中的引用，存在即表示已保存密钥
+    ai_api_key_ref?: string | null;
     ai_base_url?: string;
     ai_model?: string;
+    // 各键的生效来源 (user / workspace / folder)，只读
+    sources?: Record<string, { scope: string; language?: string }>;
 }
 
 interface SettingsModalProps {
@@ -42,6 +48,36 @@ interface SettingsModalProps {
 const SettingsModal: React.FC<SettingsModalProps> = ({ currentSettings, onSave, onClose, platform }) => {
     const { t } = useTranslation();
     const isWindows = platform === 'windows' || platform === 'win32';
+    const [apiKey, setApiKey] = useState('');
+    const [passphrase, setPassphrase] = useState('');
+    const [secrets, setSecrets] = useState<{ backend: 'keyring' | 'vault'; vault_exists: boolean; unlocked: boolean } | null>(null);
+
+    useEffect(() => {
+        invoke<typeof secrets>('secrets_status').then(setSecrets).catch(console.error);
+    }, []);
+
+    // 密钥在失焦时才提交，避免逐字写入密钥存储
+    const commitApiKey = () => {
+        if (!apiKey) return;
+        updateSetting('ai_api_key', apiKey);
+        setApiKey('');
+    };
+
+    const unlockSecrets = async () => {
+        try {
+            setSecrets(await invoke<typeof secrets>('secrets_unlock', { passphrase }));
+            setPassphrase('');
+        } catch (e) {
+            toast.error(String(e));
+        }
+    };
+
+    // 被工作区或文件夹设置覆盖的项，在此修改的是用户设置
+    const scopeHint = (key: keyof AppSettings) => {
+        const scope = currentSettings.sources?.[key]?.scope;
+        if (scope !== 'workspace' && scope !== 'folder') return null;
+        return <span style={{ fontWeight: 'normal', color: 'var(--accent-color)', marginLeft: '6px' }}>({t('OverriddenByWorkspace')})</span>;
+    };
 
     const updateSetting = <K extends keyof AppSettings>(key: K, value: AppSettings[K]) => {
         const newSettings = { ...currentSettings, [key]: value };
@@ -84,7 +120,7 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ currentSettings, onSave,
                 </div>
 
                 <div style={{ display: 'flex', flexDirection: 'column', gap: '5px' }}>
-                    <label style={{ fontSize: 'var(--ui-font-size)', fontWeight: 'bold', color: 'var(--text-secondary)' }}>{t('Theme')}</label>
+                    <label style={{ fontSize: 'var(--ui-font-size)', fontWeight: 'bold', color: 'var(--text-secondary)' }}>{t('Theme')}{scopeHint('theme')}</label>
                     <select 
                         value={currentSettings.theme}
                         onChange={(e) => updateSetting('theme', e.target.value as 'dark' | 'light' | 'abyss')}
@@ -98,7 +134,7 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ currentSettings, onSave,
 
                 <div style={{ display: 'grid', gridTemplateColumns: '1fr 1fr', gap: '15px' }}>
                     <div style={{ display: 'flex', flexDirection: 'column', gap: '5px' }}>
-                        <label style={{ fontSize: 'var(--ui-font-size)', fontWeight: 'bold', color: 'var(--text-secondary)' }}>{t('FontSize')} (Editor)</label>
+                        <label style={{ fontSize: 'var(--ui-font-size)', fontWeight: 'bold', color: 'var(--text-secondary)' }}>{t('FontSize')} (Editor){scopeHint('font_size')}</label>
                         <input 
                             type="number" value={currentSettings.font_size}
                             onChange={(e) => updateSetting('font_size', parseInt(e.target.value) || 12)}
@@ -106,7 +142,7 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ currentSettings, onSave,
                         />
                     </div>
                     <div style={{ display: 'flex', flexDirection: 'column', gap: '5px' }}>
-                        <label style={{ fontSize: 'var(--ui-font-size)', fontWeight: 'bold', color: 'var(--text-secondary)' }}>{t('UIFontSize')}</label>
+                        <label style={{ fontSize: 'var(--ui-font-size)', fontWeight: 'bold', color: 'var(--text-secondary)' }}>{t('UIFontSize')}{scopeHint('ui_font_size')}</label>
                         <input 
                             type="number" value={currentSettings.ui_font_size || 13}
                             onChange={(e) => updateSetting('ui_font_size', parseInt(e.target.value) || 13)}
@@ -160,16 +196,46 @@ const SettingsModal: React.FC<SettingsModalProps> = ({ currentSettings, onSave,
 
                 <div style={{ display: 'flex', flexDirection: 'column', gap: '5px' }}>
                     <label style={{ fontSize: '11px', color: 'var(--text-secondary)' }}>{t('APIKey')}</label>
-                    <input 
-                        type="password" value={currentSettings.ai_api_key || ''}
-                        placeholder="sk-..."
-                        onChange={(e) => updateSetting('ai_api_key', e.target.value)}
-                        style={{ padding: '8px', backgroundColor: 'var(--input-bg)', color: 'var(--text-primary)', border: '1px solid var(--input-border)', borderRadius: '4px', outline: 'none', fontSize: 'var(--ui-font-size)' }}
-                    />
+                    {secrets?.backend === 'vault' && !secrets.unlocked ? (
+                        <div style={{ display: 'flex', gap: '8px' }}>
+                            <input 
+                                type="password" value={passphrase}
+                                placeholder={t(secrets.vault_exists ? 'VaultPassphrase' : 'VaultCreatePassphrase')}
+                                onChange={(e) => setPassphrase(e.target.value)}
+                                onKeyDown={(e) => { if (e.key === 'Enter') unlockSecrets(); }}
+                                style={{ flex: 1, padding: '8px', backgroundColor: 'var(--input-bg)', color: 'var(--text-primary)', border: '1px solid var(--input-border)', borderRadius: '4px', outline: 'none', fontSize: 'var(--ui-font-size)' }}
+                            />
+                            <button 
+                                onClick={unlockSecrets} disabled={!passphrase}
+                                style={{ padding: '6px 12px', backgroundColor: 'var(--accent-color)', border: 'none', color: 'var(--accent-foreground)', borderRadius: '4px', cursor: 'pointer' }}
+                            >
+                                {t('Unlock')}
+                            </button>
+                        </div>
+                    ) : (
+                        <div style={{ display: 'flex', gap: '8px' }}>
+                            <input 
+                                type="password" value={apiKey}
+                                placeholder={currentSettings.ai_api_key_ref ? t('APIKeyStored') : 'sk-...'}
+                                onChange={(e) => setApiKey(e.target.value)}
+                                onBlur={commitApiKey}
+                                onKeyDown={(e) => { if (e.key === 'Enter') commitApiKey(); }}
+                                style={{ flex: 1, padding: '8px', backgroundColor: 'var(--input-bg)', color: 'var(--text-primary)', border: '1px solid var(--input-border)', borderRadius: '4px', outline: 'none', fontSize: 'var(--ui-font-size)' }}
+                            />
+                            {currentSettings.ai_api_key_ref && (
+                                <button 
+                                    onClick={() => updateSetting('ai_api_key', '')}
+                                    style={{ padding: '6px 12px', backgroundColor: 'transparent', border: '1px solid var(--input-border)', color: 'var(--text-primary)', borderRadius: '4px', cursor: 'pointer' }}
+                                >
+                                    {t('Clear')}
+                                </button>
+                            )}
+                        </div>
+                    )}
                 </div>
 
                 <div style={{ display: 'flex', flexDirection: 'column', gap: '5px' }}>
-                    <label style={{ fontSize: '11px', color: 'var(--text-secondary)' }}>{t('AIModel')}</label>
+                    <label style={{ fontSize: '11px', color: 'var(--text-secondary)' }}>{t('AIModel')}{scopeHint('ai_model')}</label>
                     <input 
                         type="text" value={currentSettings.ai_model || 'gpt-4o'}
                         placeholder="gpt-4o"
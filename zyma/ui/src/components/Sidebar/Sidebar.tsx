@@ -1,8 +1,10 @@
 import React, { useState, useEffect, useCallback, useRef, useMemo } from 'react';
 import { ChevronRight, ChevronDown, File, Folder } from 'lucide-react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../../utils/ipc';
 import { listen } from '@tauri-apps/api/event';
 import { useTranslation } from 'react-i18next';
+import toast from 'react-hot-toast';
+import { formatFsError } from '../../utils/fsError';
 import ContextMenu from '../ContextMenu/ContextMenu';
 import type { MenuItem } from '../ContextMenu/ContextMenu';
 import FileTreeItem from './components/FileTreeItem';
@@ -71,6 +73,10 @@ const Sidebar: React.FC<SidebarProps> = ({ pluginMenuItems = [] }) => {
   const [rootFiles, setRootFiles] = useState<FileItemData[]>([]);
   const [isRootOpen, setIsRootOpen] = useState(true);
   const [isLoading, setIsLoading] = useState(false);
+  // 多根工作区中除主根目录以外的其他根目录
+  const [extraRoots, setExtraRoots] = useState<FileItemData[]>([]);
+  // 已连接的 SSH 远程根目录 (sftp://<id>/...)
+  const [remoteRoots, setRemoteRoots] = useState<(FileItemData & { id: string })[]>([]);
   const [contextMenu, setContextMenu] = useState<{ x: number, y: number, items: MenuItem[] } | null>(null);
 
   const projectName = useMemo(() => {
@@ -131,6 +137,25 @@ const Sidebar: React.FC<SidebarProps> = ({ pluginMenuItems = [] }) => {
     return () => { isMounted = false; };
   }, [rootPath]);
 
+  useEffect(() => {
+    const loadFolders = async () => {
+        try {
+          const folders = await invoke<{ name: string, path: string }[]>('workspace_get_folders');
+          setExtraRoots(folders.slice(1).map(f => ({ name: f.name, path: f.path, is_dir: true })));
+        } catch (error) { setExtraRoots([]); }
+    };
+    loadFolders();
+    const unlisten = listen('workspace_folders_changed', loadFolders);
+    return () => { unlisten.then(fn => fn()); };
+  }, [rootPath]);
+
+  useEffect(() => {
+    const toRoots = (list: any[]) => list.map(c => ({ id: c.id, name: c.name, path: c.root, is_dir: true }));
+    invoke<any[]>('ssh_list_connections').then(list => setRemoteRoots(toRoots(list))).catch(() => setRemoteRoots([]));
+    const unlisten = listen<any[]>('ssh_connections_changed', e => setRemoteRoots(toRoots(e.payload)));
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
   useEffect(() => {
       const handleClick = () => setContextMenu(null);
       window.addEventListener('click', handleClick);
@@ -145,9 +170,23 @@ const Sidebar: React.FC<SidebarProps> = ({ pluginMenuItems = [] }) => {
           { label: t('NewFolder'), action: () => { setEditing({ parentPath, type: 'dir' }); setIsRootOpen(true); } },
           { label: '', action: () => {}, separator: true }
       );
-      if (path !== rootPath) {
+      const remote = remoteRoots.find(r => r.path === path);
+      if (remote) {
+          items.push(
+              { label: t('DisconnectRemote'), action: () => { invoke('ssh_disconnect', { profileId: remote.id }).catch(console.warn); } }
+          );
+      } else if (extraRoots.some(r => r.path === path)) {
+          items.push(
+              { label: t('RemoveFolderFromWorkspace'), action: () => { invoke('workspace_remove_folder', { path }).catch(console.warn); } }
+          );
+      } else if (path !== rootPath) {
           items.push(
               { label: t('Rename'), action: () => setEditing({ parentPath, type: 'rename', oldPath: path, oldName: name }) },
+              { label: t('ChangePermissions'), action: async () => {
+                  const mode = prompt(t('EnterMode', { name }), '+x');
+                  if (mode) invoke('fs_chmod', { path, mode }).catch(e => toast.error(formatFsError(e, t)));
+              } },
+              ...(isDir ? [] : [{ label: t('LocalHistory'), action: () => { import('../CommandSystem/CommandRegistry').then(m => { m.commands.executeCommand('file.localHistory', path); }); } }]),
               { label: t('Delete'), action: () => handleDelete(path, name, loadRoot, rootPath), danger: true }
           );
       }
@@ -169,7 +208,7 @@ const Sidebar: React.FC<SidebarProps> = ({ pluginMenuItems = [] }) => {
       const isDir = item ? item.is_dir : true;
       const name = item ? item.name : projectName;
       setContextMenu({ x: e.clientX, y: e.clientY, items: getMenuItems(targetPath, isDir, name) });
-  }, [rootPath, projectName, t, handleDelete]);
+  }, [rootPath, projectName, t, handleDelete, extraRoots, remoteRoots]);
 
   return (
     <div style={{ width: '100%', height: '100%', backgroundColor: 'var(--bg-sidebar)', borderRight: '1px solid var(--border-color)', color: 'var(--text-primary)', display: 'flex', flexDirection: 'column', userSelect: 'none' }} onContextMenu={(e) => handleContextMenu(e)}>
@@ -200,6 +239,32 @@ const Sidebar: React.FC<SidebarProps> = ({ pluginMenuItems = [] }) => {
                 ))}
             </div>
         )}
+        {extraRoots.map((folder) => (
+            <FileTreeItem 
+                key={folder.path} 
+                item={folder} 
+                onFileSelect={handleFileSelect} 
+                onContextMenu={handleContextMenu} 
+                activeFilePath={activeFilePath} 
+                level={0}
+                editing={editing}
+                onInlineSubmit={onInlineSubmit}
+                setEditing={setEditing}
+            />
+        ))}
+        {remoteRoots.map((folder) => (
+            <FileTreeItem 
+                key={folder.path} 
+                item={folder} 
+                onFileSelect={handleFileSelect} 
+                onContextMenu={handleContextMenu} 
+                activeFilePath={activeFilePath} 
+                level={0}
+                editing={editing}
+                onInlineSubmit={onInlineSubmit}
+                setEditing={setEditing}
+            />
+        ))}
       </div>
       {contextMenu && <ContextMenu x={contextMenu.x} y={contextMenu.y} items={contextMenu.items} onClose={() => setContextMenu(null)} />}
     </div>
@@ -1,6 +1,7 @@
 import React, { useState, memo } from 'react';
 import { File, Folder, ChevronRight, ChevronDown, FileCode, FileJson, FileType, FileText, Image as ImageIcon } from 'lucide-react';
-import { invoke } from '@tauri-apps/api/core';
+import { ask } from '@tauri-apps/plugin-dialog';
+import { invoke } from '../../../utils/ipc';
 import { useTranslation } from 'react-i18next';
 import { InlineInput } from '../Sidebar';
 
@@ -8,9 +9,17 @@ export interface FileItemData {
   name: string;
   path: string;
   is_dir: boolean;
+  is_symlink?: boolean;
+  target?: string | null;
+  broken?: boolean;
+  hidden?: boolean;
+  readonly?: boolean;
   children?: FileItemData[];
 }
 
+// 可像目录一样展开浏览的压缩包 (内部路径为 zip:/a.zip!/inner)
+const ARCHIVE_RE = /\.(zip|jar|war|ear|apk|vsix|nupkg|whl|tar|tgz|tar\.gz)$/i;
+
 interface FileTreeItemProps {
   item: FileItemData;
   onFileSelect: (path: string, name: string, line?: number) => void;
@@ -34,14 +43,26 @@ const FileTreeItemComponent: React.FC<FileTreeItemProps> = ({
   const isActive = activeFilePath === item.path;
   const isRenaming = editing?.type === 'rename' && editing?.oldPath === item.path;
   const isAddingUnderMe = editing?.type !== 'rename' && editing?.parentPath === item.path;
+  const isArchive = !item.is_dir && !item.path.startsWith('zip:') && ARCHIVE_RE.test(item.name);
+  const expandable = item.is_dir || isArchive;
 
   const handleToggle = async (e: React.MouseEvent) => {
     e.stopPropagation();
-    if (item.is_dir) {
+    if (expandable) {
       if (!isOpen && children.length === 0) {
         setIsLoading(true);
         try {
-          const items = await invoke<FileItemData[]>('read_dir', { path: item.path });
+          const path = isArchive ? `zip:${item.path}!/` : item.path;
+          let items: FileItemData[];
+          try {
+            items = await invoke<FileItemData[]>('read_dir', { path });
+          } catch (error: any) {
+            // 超大的压缩包需整体载入内存，用户确认后才展开
+            if (!isArchive || error?.kind !== 'file_too_large') throw error;
+            const proceed = await ask(t('ArchiveTooLargeConfirm', { name: item.name, size: Math.round(error.size / 1024 / 1024) }), { title: t('File'), kind: 'warning' });
+            if (!proceed) return;
+            items = await invoke<FileItemData[]>('read_dir', { path, force: true });
+          }
           setChildren(items);
         } catch (error) {} finally { setIsLoading(false); }
       }
@@ -92,14 +113,17 @@ const FileTreeItemComponent: React.FC<FileTreeItemProps> = ({
     <div className="sidebar-item-container">
       <div style={itemStyle} className="file-item-hover" onClick={handleToggle} onContextMenu={(e) => onContextMenu(e, item)}>
         <div style={{ display: 'flex', alignItems: 'center', flex: 1, overflow: 'hidden' }}>
-            <span style={{ marginRight: '5px', opacity: 0.8, display: 'flex', alignItems: 'center' }}>{item.is_dir && (isOpen ? <ChevronDown size={14} /> : <ChevronRight size={14} />)}{!item.is_dir && <span style={{ width: '14px' }}></span>}</span>
+            <span style={{ marginRight: '5px', opacity: 0.8, display: 'flex', alignItems: 'center' }}>{expandable && (isOpen ? <ChevronDown size={14} /> : <ChevronRight size={14} />)}{!expandable && <span style={{ width: '14px' }}></span>}</span>
             <span style={{ marginRight: '5px', opacity: 0.8 }}>
                 {item.is_dir ? <Folder size={14} fill={isOpen ? 'var(--accent-color)' : 'none'} color={isOpen ? 'var(--accent-color)' : 'var(--text-secondary)'} /> : getFileIcon()}
             </span>
-            <span style={{ whiteSpace: 'nowrap', overflow: 'hidden', textOverflow: 'ellipsis', fontWeight: isActive ? 600 : 400 }}>{item.name}</span>
+            <span
+                title={item.is_symlink ? `${item.name} → ${item.target ?? ''}` : undefined}
+                style={{ whiteSpace: 'nowrap', overflow: 'hidden', textOverflow: 'ellipsis', fontWeight: isActive ? 600 : 400, opacity: item.hidden ? 0.6 : 1, textDecoration: item.broken ? 'line-through' : 'none', fontStyle: item.is_symlink ? 'italic' : 'normal' }}
+            >{item.name}</span>
         </div>
       </div>
-      {item.is_dir && isOpen && (
+      {expandable && isOpen && (
         <div>
           {isLoading && <div style={{ paddingLeft: `${20 + level * 10}px`, fontSize: 'calc(var(--ui-font-size) - 2px)', color: 'var(--text-muted)' }}>{t('Loading')}...</div>}
           
@@ -18,7 +18,7 @@ import { useWorkbench } from './WorkbenchContext';
 import { useWorkbenchCommands } from '../hooks/useWorkbenchCommands';
 import { useBottomPanelResize } from '../components/BottomPanel/useBottomPanelResize';
 import { Toaster } from 'react-hot-toast';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import type { WorkbenchLogic } from '../hooks/useWorkbenchLogic';
 import type { FileManagement } from '../hooks/useFileManagement';
 
@@ -81,7 +81,7 @@ const Workbench: React.FC<WorkbenchProps> = (props) => {
                         case 'exit': requestExit(); break;
                         case 'toggle_theme': commands.executeCommand('view.toggleTheme'); break;
                         case 'open_folder': commands.executeCommand('workspace.openFolder'); break;
-                        case 'workspace.open_recent': if (params) { fm.setOpenFiles([]); invoke('fs_set_cwd', { path: params }); } break;
+                        case 'workspace.open_recent': if (params) { commands.executeCommand('workbench.saveSession').then(() => { fm.setOpenFiles([]); invoke('fs_set_cwd', { path: params }); }); } break;
                         case 'save': commands.executeCommand('file.save'); break;
                         case 'save_as': commands.executeCommand('file.saveAs'); break;
                         case 'new_file': commands.executeCommand('file.new'); break;
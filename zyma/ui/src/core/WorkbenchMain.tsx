@@ -1,5 +1,6 @@
 import React from 'react';
 import Editor from '../components/Editor/Editor';
+import LargeFileViewer from '../components/Editor/LargeFileViewer';
 import Preview from '../components/Preview/Preview';
 import { slotRegistry } from './SlotRegistry';
 import { WelcomeScreen } from '../components/Common/WelcomeScreen';
@@ -33,7 +34,11 @@ const WorkbenchMain: React.FC<WorkbenchMainProps> = ({
 
     return (
         <div className="main-content-area" style={{ flex: 1, display: 'flex', overflow: 'hidden', position: 'relative' }}>
-            {activeTab?.type === 'file' && activeFile ? (
+            {activeTab?.type === 'file' && activeFile?.largeFile ? (
+                <div className="editor-instance-wrapper" style={{ flex: 1, height: '100%', overflow: 'hidden' }} key={activeFile.uid}>
+                    <LargeFileViewer path={activeFile.path} fontSize={settings.font_size} />
+                </div>
+            ) : activeTab?.type === 'file' && activeFile ? (
                 <div className="editor-instance-wrapper" style={{ flex: 1, height: '100%', overflow: 'hidden' }} key={activeFile.uid}>
                     <Editor 
                         content={activeFile.content} 
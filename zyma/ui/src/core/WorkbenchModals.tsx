@@ -4,7 +4,7 @@ import AboutModal from '../components/AboutModal/AboutModal';
 import CommandPalette from '../components/CommandSystem/CommandPalette';
 import ConfirmModal from '../components/ConfirmModal/ConfirmModal';
 import { useTranslation } from 'react-i18next';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import type { WorkbenchLogic } from '../hooks/useWorkbenchLogic';
 
 interface WorkbenchModalsProps {
@@ -5,6 +5,9 @@ import { statusBar } from '../components/StatusBar/StatusBarRegistry';
 import { registerFileCommands } from '../commands/file';
 import { registerViewCommands } from '../commands/view';
 import { registerWorkspaceCommands } from '../commands/workspace';
+import { registerRemoteCommands } from '../commands/remote';
+import { registerHistoryCommands } from '../commands/history';
+import { registerProfileCommands } from '../commands/profiles';
 import { slotRegistry } from './SlotRegistry';
 import OutputPanelWrapper from '../components/PluginSystem/OutputPanelWrapper';
 import type { CustomViewRequest } from '../hooks/useTabSystem';
@@ -20,6 +23,7 @@ export function setupWorkbench(t: (key: string) => string, handlers: {
     toggleSidebar: () => void,
     setRootPath: (path: string) => void,
     fm: any,
+    getActiveFilePath: () => string | null,
     setActiveTabId: (id: string | null) => void,
     components: {
         Sidebar: React.ReactNode,
@@ -33,6 +37,9 @@ export function setupWorkbench(t: (key: string) => string, handlers: {
     registerFileCommands(t, handlers);
     registerViewCommands(t, handlers);
     registerWorkspaceCommands(t, handlers);
+    registerRemoteCommands(t);
+    registerHistoryCommands(t, handlers);
+    registerProfileCommands(t);
 
     // 2. 注册视图
     views.registerView({ id: 'explorer', title: t('Workspace'), icon: <Files size={24} />, component: handlers.components.Sidebar, order: 1 });
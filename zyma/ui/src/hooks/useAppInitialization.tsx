@@ -1,7 +1,9 @@
 import { useState, useEffect, useRef, useCallback, useMemo } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
+import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { ask } from '@tauri-apps/plugin-dialog';
+import toast from 'react-hot-toast';
 import { PluginManager } from '../components/PluginSystem/PluginManager';
 import type { AppSettings } from '../components/SettingsModal/SettingsModal';
 import { useNativeExtensions } from './useNativeExtensions';
@@ -33,6 +35,12 @@ export function useAppInitialization(fm: any, i18n: any, openCustomView?: (reque
                     invoke<string>('get_product_name')
                 ]);
                 setSettings(saved);
+                // 配置文件有错误时只忽略出错的键，提示用户原文件已另存为 .corrupt
+                invoke<{ key?: string, message: string, line: number, column: number }[]>('settings_diagnostics').then(issues => {
+                    if (issues.length === 0 || !i18n?.t) return;
+                    const first = issues[0];
+                    toast.error(i18n.t('SettingsFileHasErrors', { count: issues.length, key: first.key || '-', line: first.line, column: first.column }), { duration: 8000 });
+                }).catch(() => {});
                 setAppVersion(version);
                 setProductName(rawName.toLowerCase().replace(/[^a-z0-9]/g, '_'));
                 if (i18n && typeof i18n.changeLanguage === 'function') {
@@ -49,6 +57,19 @@ export function useAppInitialization(fm: any, i18n: any, openCustomView?: (reque
         initSystem();
     }, [i18n]);
 
+    // 设置在其他窗口保存、被手动编辑或工作区设置文件修改后重新加载
+    useEffect(() => {
+        const unlisten = listen<{ scope: string, keys: string[] }>('settings-changed', async (event) => {
+            const latest = await invoke<AppSettings>('load_settings').catch(() => null);
+            if (!latest) return;
+            setSettings(latest);
+            if (event.payload.keys.includes('language') && i18n && typeof i18n.changeLanguage === 'function') {
+                i18n.changeLanguage(latest.language);
+            }
+        });
+        return () => { unlisten.then(fn => fn()); };
+    }, [i18n]);
+
     // 2. 发现原生扩展 (已拆分)
     useNativeExtensions(ready, openCustomView);
 
@@ -1,8 +1,9 @@
 import { useState, useCallback, useRef, useMemo } from 'react';
 import type { EditorView } from '@codemirror/view';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import { save, ask } from '@tauri-apps/plugin-dialog';
 import { pathUtils } from '../utils/pathUtils';
+import { formatFsError } from '../utils/fsError';
 import { useTranslation } from 'react-i18next';
 import toast from 'react-hot-toast';
 
@@ -15,14 +16,28 @@ export interface FileData {
     originalContent: string;
     isDirty: boolean;
     encoding?: string;
+    hasBom?: boolean;
+    lineEnding?: string;
+    version?: string; // 磁盘版本标记，用于检测外部修改
+    largeFile?: boolean; // 超大文件以只读分页方式查看，内容不载入编辑器
 }
 
 const generateUid = () => Math.random().toString(36).substring(2, 11);
 const normalize = (str: string) => (str || '').replace(/\r\n/g, '\n');
 
 // 提取为普通的异步函数，避免 Hook 复杂度
-const fsReadFile = (path: string) => invoke<any>('read_file', { path });
-const fsWriteFile = (path: string, content: string) => invoke<void>('write_file', { path, content });
+const fsReadFile = (path: string, force?: boolean) => invoke<any>('read_file', { path, force });
+const fsWriteFile = (path: string, content: string, file?: FileData, expectedVersion?: string) => invoke<string>('write_file', {
+    path,
+    content,
+    // 按原文件的编码、BOM 与换行风格写回，避免保存时被静默转换
+    options: file ? { encoding: file.encoding, has_bom: !!file.hasBom, line_ending: file.lineEnding } : undefined,
+    expectedVersion
+});
+const isConflict = (e: any) => e && typeof e === 'object' && e.kind === 'conflict';
+const isTooLarge = (e: any) => e && typeof e === 'object' && e.kind === 'file_too_large';
+const isBinary = (e: any) => e && typeof e === 'object' && e.kind === 'binary_file';
+const isNotWritable = (e: any) => e && typeof e === 'object' && e.kind === 'not_writable';
 
 export interface FileManagement {
     openFiles: FileData[];
@@ -75,7 +90,31 @@ export function useFileManagement(): FileManagement {
 
                 try {
 
-                    const res = await fsReadFile(path);
+                    let res;
+                    try {
+                        res = await fsReadFile(path);
+                    } catch (e) {
+                        if (isBinary(e)) {
+                            // 二进制文件默认不按文本打开，用户确认后以 UTF-8 强制解码
+                            const proceed = await ask(t('BinaryFileConfirm', { name }), { title: t('File'), kind: 'warning' });
+                            if (!proceed) return;
+                            res = await invoke<any>('read_file_with_encoding', { path, encoding: 'UTF-8' });
+                        } else {
+                            // 超大文件默认以只读分页方式查看，用户选择后才整体加载
+                            if (!isTooLarge(e)) throw e;
+                            const sizeMb = Math.round((e as any).size / 1024 / 1024);
+                            const full = await ask(t('FileTooLargeConfirm', { name, size: sizeMb }), {
+                                title: t('File'), kind: 'warning', okLabel: t('LargeFileOpenFull'), cancelLabel: t('LargeFileOpenPaged')
+                            });
+                            if (!full) {
+                                const paged: FileData = { id: path, uid: generateUid(), name, path, content: '', originalContent: '', isDirty: false, largeFile: true };
+                                setOpenFiles(prev => prev.some(f => f.path === path) ? prev : [...prev, paged]);
+                                setActiveFilePath(path);
+                                return;
+                            }
+                            res = await fsReadFile(path, true);
+                        }
+                    }
 
                     const content = normalize(res.content);
 
@@ -95,7 +134,13 @@ export function useFileManagement(): FileManagement {
 
                         isDirty: false,
 
-                        encoding: res.encoding 
+                        encoding: res.encoding,
+
+                        hasBom: res.has_bom,
+
+                        lineEnding: res.line_ending,
+
+                        version: res.version
 
                     };
 
@@ -111,11 +156,12 @@ export function useFileManagement(): FileManagement {
         } finally {
             pendingFiles.current.delete(path);
         }
-    }, [openFiles, activeFilePath]);
+    }, [openFiles, activeFilePath, t]);
 
     const doSave = useCallback(async (file: FileData | null, force: boolean = false) => {
         const target = file || openFiles.find(f => f.id === activeFilePath);
         if (!target) return false;
+        if (target.largeFile) return true;
 
         const currentText = editorViewRef.current?.state.doc.toString() || target.content;
         const normalizedCurrent = normalize(currentText);
@@ -132,20 +178,40 @@ export function useFileManagement(): FileManagement {
             if (!targetPath) return false;
             if (targetPath === target.path && normalizedCurrent === target.originalContent) return true; 
 
-            await fsWriteFile(targetPath, currentText);
+            let version: string;
+            try {
+                // 仅在覆盖原文件时校验版本，另存为新路径无需检查
+                version = await fsWriteFile(targetPath, currentText, target, targetPath === target.path ? target.version : undefined);
+            } catch (e) {
+                if (isNotWritable(e)) {
+                    // 无写权限时可经由 pkexec/sudo 以管理员身份保存
+                    const elevate = await ask(t('SaveAsRootConfirm', { name: pathUtils.getFileName(targetPath) }), { title: t('File'), kind: 'warning' });
+                    if (!elevate) return false;
+                    version = await invoke<string>('write_file_elevated', {
+                        path: targetPath,
+                        content: currentText,
+                        options: { encoding: target.encoding, has_bom: !!target.hasBom, line_ending: target.lineEnding }
+                    });
+                } else {
+                    if (!isConflict(e)) throw e;
+                    const overwrite = await ask(t('FileModifiedOnDisk', { name: target.name }), { title: t('File'), kind: 'warning' });
+                    if (!overwrite) return false;
+                    version = await fsWriteFile(targetPath, currentText, target);
+                }
+            }
             const fileName = pathUtils.getFileName(targetPath);
             const finalPath = targetPath;
             setOpenFiles(prev => prev.map(f => f.id === target.id ? { 
-                ...f, id: finalPath, path: finalPath, name: fileName, content: currentText, originalContent: normalizedCurrent, isDirty: false 
+                ...f, id: finalPath, path: finalPath, name: fileName, content: currentText, originalContent: normalizedCurrent, isDirty: false, version 
             } : f));
             if (activeFilePath === target.id) setActiveFilePath(finalPath);
             return true;
         } catch (e) { 
             console.error("Save/Dialog Error:", e);
-            toast.error(`Save failed: ${String(e)}`);
+            toast.error(`Save failed: ${formatFsError(e, t)}`);
             return false; 
         }
-    }, [activeFilePath, openFiles]);
+    }, [activeFilePath, openFiles, t]);
 
     // --- 新增：磁盘写操作 (合并自 useFileIO) ---
     const handleCreate = useCallback(async (targetPath: string, type: 'file' | 'dir', reload?: (path: string) => void, rootPath?: string, name?: string) => {
@@ -156,7 +222,7 @@ export function useFileManagement(): FileManagement {
             if (type === 'file') await invoke('create_file', { path });
             else await invoke('create_dir', { path });
             if (reload && rootPath) reload(rootPath);
-        } catch (e) { toast.error(String(e)); }
+        } catch (e) { toast.error(formatFsError(e, t)); }
     }, [t]);
 
     const handleRename = useCallback(async (oldPath: string, oldName: string, reload?: (path: string) => void, rootPath?: string, newName?: string) => {
@@ -169,7 +235,7 @@ export function useFileManagement(): FileManagement {
         try {
             await invoke('rename_item', { at: oldPath, to: finalPath });
             if (reload && rootPath) reload(rootPath);
-        } catch (e) { toast.error(String(e)); }
+        } catch (e) { toast.error(formatFsError(e, t)); }
     }, [t]);
 
     const handleDelete = useCallback(async (path: string, name: string, reload?: (path: string) => void, rootPath?: string) => {
@@ -179,7 +245,7 @@ export function useFileManagement(): FileManagement {
                 await invoke('remove_item', { path });
                 setOpenFiles(prev => prev.filter(f => f.id !== path));
                 if (reload && rootPath) reload(rootPath);
-            } catch (e) { toast.error(String(e)); }
+            } catch (e) { toast.error(formatFsError(e, t)); }
         }
     }, [t]);
 
@@ -198,7 +264,12 @@ export function useFileManagement(): FileManagement {
     }, [activeFilePath]);
 
     const closeFile = useCallback((id: string) => {
-        setOpenFiles(prev => prev.filter(f => f.id !== id));
+        setOpenFiles(prev => {
+            // 分页查看的文件关闭后释放后台的行索引
+            const closing = prev.find(f => f.id === id);
+            if (closing?.largeFile && closing.path) invoke('fs_drop_line_index', { path: closing.path }).catch(() => {});
+            return prev.filter(f => f.id !== id);
+        });
     }, []);
 
     const handleNewFile = useCallback(() => {
@@ -0,0 +1,60 @@
+import { useEffect, useRef } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { invoke } from '../utils/ipc';
+
+// 停止输入多久后备份未保存的内容
+const BACKUP_DELAY = 1000;
+
+// 热退出：把脏缓冲区 (含未命名文件) 推送给后端备份，保存或关闭后丢弃备份
+export function useHotExit(ready: boolean, fm: any) {
+    // 已备份的缓冲区 id -> 内容，避免重复写入
+    const backedUp = useRef<Map<string, string>>(new Map());
+    const filesRef = useRef<any[]>(fm.openFiles);
+    useEffect(() => { filesRef.current = fm.openFiles; }, [fm.openFiles]);
+
+    // 把当前的脏缓冲区与已备份的内容对齐，全部写入完成后返回
+    const sync = useRef(async () => {
+        const dirty = new Map<string, any>(filesRef.current.filter((f: any) => f.isDirty).map((f: any) => [f.path || f.id, f]));
+        const pending: Promise<unknown>[] = [];
+        dirty.forEach((f, id) => {
+            if (backedUp.current.get(id) === f.content) return;
+            backedUp.current.set(id, f.content);
+            pending.push(invoke('backup_buffer', {
+                buffer: {
+                    id,
+                    path: f.path,
+                    name: f.name,
+                    content: f.content,
+                    encoding: f.encoding,
+                    has_bom: !!f.hasBom,
+                    line_ending: f.lineEnding,
+                    version: f.version
+                }
+            }).catch(console.warn));
+        });
+        Array.from(backedUp.current.keys()).filter(id => !dirty.has(id)).forEach(id => {
+            backedUp.current.delete(id);
+            pending.push(invoke('backup_discard', { id }).catch(console.warn));
+        });
+        await Promise.all(pending);
+    });
+
+    useEffect(() => {
+        if (!ready) return;
+        const timer = setTimeout(() => { sync.current(); }, BACKUP_DELAY);
+        return () => clearTimeout(timer);
+    }, [ready, fm.openFiles]);
+
+    // 关闭窗口时后端先通知前端，立即写入尚在防抖中的备份后再退出
+    useEffect(() => {
+        let disposed = false;
+        let unlisten: (() => void) | undefined;
+        listen('hot_exit_flush', async () => {
+            await sync.current();
+            invoke('exit_app').catch(console.warn);
+        }).then(u => {
+            if (disposed) u(); else unlisten = u;
+        });
+        return () => { disposed = true; unlisten?.(); };
+    }, []);
+}
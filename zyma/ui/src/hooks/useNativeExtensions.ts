@@ -1,5 +1,5 @@
 import { useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import { listen } from '@tauri-apps/api/event';
 import { authRegistry } from '../components/PluginSystem/AuthRegistry';
 import { chatRegistry } from '../components/Chat/Registry/ChatRegistry';
@@ -54,7 +54,10 @@ export function useNativeExtensions(ready: boolean, openCustomView?: (request: a
                                     localStorage.removeItem(`auth_${p.id}_user`);
                                     authRegistry.updateAccount(p.id, undefined);
                                 } catch(e) {}
-                            }
+                            },
+                            getCredentials: p.credentials_command
+                                ? (request) => invoke<any>(p.credentials_command, { request }).catch(() => null)
+                                : undefined
                         });
                         if (p.auth_event) {
                             listen(p.auth_event, (e: any) => authRegistry.updateAccount(p.id, (e.payload as any).username));
@@ -1,6 +1,9 @@
-import { useEffect, useCallback } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { useEffect, useCallback, useRef } from 'react';
+import { invoke } from '../utils/ipc';
+import { listen } from '@tauri-apps/api/event';
 import { pathUtils } from '../utils/pathUtils';
+import { viewStateCache } from '../utils/viewStateCache';
+import { commands } from '../components/CommandSystem/CommandRegistry';
 
 interface SessionManagementProps {
     ready: boolean;
@@ -8,6 +11,12 @@ interface SessionManagementProps {
     setRootPath: (path: string) => void;
     fm: any;
     appInit: any;
+    ui: {
+        sidebarTab: string;
+        setSidebarTab: (id: string) => void;
+        showSidebar: boolean;
+        setShowSidebar: (show: boolean) => void;
+    };
 }
 
 export function useSessionManagement({
@@ -15,49 +24,115 @@ export function useSessionManagement({
     rootPath,
     setRootPath,
     fm,
-    appInit
+    appInit,
+    ui
 }: SessionManagementProps) {
-    // --- 逻辑：恢复上次会话 ---
-    const restoreSession = useCallback(async () => {
-        const settings = appInit.settings;
-        if (settings?.session) {
-            const { root_path: savedRoot, open_files, active_file } = settings.session;
-            
-            // 1. 恢复工作区
-            // 如果当前没有设置有效根目录，则从会话恢复
-            let targetRoot = rootPath;
-            if ((targetRoot === "." || targetRoot === "./") && savedRoot && savedRoot !== ".") {
-                targetRoot = savedRoot;
-                setRootPath(savedRoot);
-            }
+    // 最近一次加载的会话，保存时保留前端尚未管理的字段 (分栏布局、面板尺寸等)
+    const loadedSession = useRef<any>(null);
 
-            if (!targetRoot || targetRoot === "." || targetRoot === "./") return;
-
-            const normRoot = pathUtils.normalize(targetRoot);
-            const normRootWithSlash = normRoot.endsWith('/') ? normRoot : normRoot + '/';
-
-            // 2. 恢复打开的文件 (增强：仅恢复属于当前工作区的文件)
-            if (open_files && open_files.length > 0) {
-                // 按顺序恢复，避免 Tab 顺序混乱
-                for (const path of open_files) {
-                    const normPath = pathUtils.normalize(path);
-                    // 校验：文件路径必须在根路径之下
-                    if (normPath === normRoot || normPath.startsWith(normRootWithSlash)) {
-                        const name = pathUtils.getFileName(path);
-                        await fm.handleFileSelect(path, name);
-                    }
-                }
+    // --- 逻辑：把会话应用到界面 (启动恢复与切换工作区共用) ---
+    const applySession = useCallback(async (session: any, targetRoot: string) => {
+        loadedSession.current = session;
+        const { open_files, active_file, dirty_buffers, editors, panels } = session;
+
+        // 热退出：先恢复上次未保存的内容 (不限于当前工作区，避免丢失编辑)
+        for (const buffer of dirty_buffers || []) {
+            const disk = buffer.path ? await invoke<any>('read_file', { path: buffer.path }).catch(() => null) : null;
+            fm.setOpenFiles((prev: any[]) => {
+                const existing = prev.find(f => f.id === buffer.id);
+                const restored = {
+                    uid: Math.random().toString(36).substring(2, 11),
+                    id: buffer.id,
+                    name: buffer.name,
+                    path: buffer.path || null,
+                    originalContent: disk ? (disk.content || '').replace(/\r\n/g, '\n') : '',
+                    ...existing,
+                    content: buffer.content,
+                    isDirty: true,
+                    encoding: buffer.encoding,
+                    hasBom: buffer.has_bom,
+                    lineEnding: buffer.line_ending,
+                    // 沿用编辑开始时的版本标记，磁盘若已被外部修改，保存时仍会提示冲突
+                    version: buffer.version
+                };
+                return existing ? prev.map(f => f.id === buffer.id ? restored : f) : [...prev, restored];
+            });
+        }
+
+        if (panels) {
+            if (panels.sidebar_view) ui.setSidebarTab(panels.sidebar_view);
+            ui.setShowSidebar(panels.sidebar_visible !== false);
+        }
+
+        if (!targetRoot || targetRoot === "." || targetRoot === "./") return;
+
+        // 多根工作区：文件属于任一根目录即可恢复
+        const folders = await invoke<{ name: string, path: string }[]>('workspace_get_folders').catch(() => []);
+        const roots = [targetRoot, ...folders.map(f => f.path)].map(r => pathUtils.normalize(r));
+        const inWorkspace = (p: string) => {
+            const normPath = pathUtils.normalize(p);
+            return roots.some(root => normPath === root || normPath.startsWith(root.endsWith('/') ? root : root + '/'));
+        };
+
+        // 光标、选区与滚动位置在编辑器创建时应用
+        viewStateCache.restore(editors || []);
+
+        // 恢复打开的文件 (仅恢复属于当前工作区的文件，按顺序避免 Tab 顺序混乱)
+        for (const path of open_files || []) {
+            if (inWorkspace(path)) {
+                await fm.handleFileSelect(path, pathUtils.getFileName(path));
             }
+        }
 
-            // 3. 恢复活动文件 (同样需要校验)
-            if (active_file) {
-                const normActive = pathUtils.normalize(active_file);
-                if (normActive === normRoot || normActive.startsWith(normRootWithSlash)) {
-                    fm.setActiveFilePath(active_file);
+        // 恢复活动文件 (同样需要校验)
+        if (active_file && inWorkspace(active_file)) {
+            fm.setActiveFilePath(active_file);
+        }
+    }, [fm, ui]);
+
+    // --- 逻辑：恢复上次会话 ---
+    const restoreSession = useCallback(async () => {
+        // 如果当前没有设置有效根目录，则从上次的工作区恢复
+        const savedRoot = appInit.settings?.session?.root_path;
+        let targetRoot = rootPath;
+        if ((targetRoot === "." || targetRoot === "./") && savedRoot && savedRoot !== ".") {
+            targetRoot = savedRoot;
+            setRootPath(savedRoot);
+        }
+        const session = await invoke<any>('session_load').catch(() => null);
+        if (session) await applySession(session, targetRoot);
+    }, [appInit.settings, rootPath, setRootPath, applySession]);
+
+    // --- 逻辑：保存当前工作区的会话 ---
+    const saveSession = useCallback(async () => {
+        if (fm.editorViewRef.current && fm.activeFilePath) {
+            const active = fm.openFiles.find((f: any) => f.id === fm.activeFilePath);
+            if (active) viewStateCache.capture(active.path || active.id, fm.editorViewRef.current);
+        }
+        const openFilePaths = fm.openFiles.map((f: any) => f.path).filter(Boolean);
+        const previous = loadedSession.current || {};
+        try {
+            await invoke('session_save', {
+                session: {
+                    ...previous,
+                    root_path: rootPath,
+                    open_files: openFilePaths,
+                    active_file: fm.activeFilePath,
+                    editors: viewStateCache.collect(openFilePaths),
+                    layout: previous.layout ?? null,
+                    panels: { ...previous.panels, sidebar_view: ui.sidebarTab, sidebar_visible: ui.showSidebar },
+                    dirty_buffers: []
                 }
-            }
+            });
+        } catch (e) {
+            console.warn("[Session] Save failed:", e);
         }
-    }, [appInit.settings, fm, rootPath, setRootPath]);
+    }, [fm, rootPath, ui]);
+
+    const saveSessionRef = useRef(saveSession);
+    saveSessionRef.current = saveSession;
+    const applySessionRef = useRef(applySession);
+    applySessionRef.current = applySession;
 
     // --- 生命周期：初始化恢复 ---
     useEffect(() => {
@@ -66,34 +141,24 @@ export function useSessionManagement({
         }
     }, [ready]); // 仅在准备就绪时执行一次
 
-    // --- 生命周期：自动保存 (Debounced) ---
+    // --- 生命周期：切换工作区后由后端推送新工作区的会话 ---
     useEffect(() => {
         if (!ready) return;
+        const unlisten = listen<any>('session_loaded', e => { applySessionRef.current(e.payload, e.payload.root_path); });
+        // 切换工作区前立即保存，避免丢失防抖期间的变化
+        commands.registerCommand({ id: 'workbench.saveSession', title: 'Save Session', category: 'Workbench', callback: () => saveSessionRef.current() });
+        return () => { unlisten.then(fn => fn()); };
+    }, [ready]);
 
-        const timer = setTimeout(async () => {
-            try {
-                const currentSettings = await invoke<any>('load_settings');
-                const openFilePaths = fm.openFiles.map((f: any) => f.path).filter(Boolean);
-                
-                await invoke('save_settings', {
-                    settings: {
-                        ...currentSettings,
-                        session: {
-                            root_path: rootPath,
-                            open_files: openFilePaths,
-                            active_file: fm.activeFilePath
-                        }
-                    }
-                });
-            } catch (e) {
-                console.warn("[Session] Auto-save failed:", e);
-            }
-        }, 3000);
-
+    // --- 生命周期：自动保存 (Debounced) ---
+    useEffect(() => {
+        if (!ready) return;
+        const timer = setTimeout(() => saveSessionRef.current(), 3000);
         return () => clearTimeout(timer);
-    }, [rootPath, fm.openFiles.length, fm.activeFilePath, ready]);
+    }, [rootPath, fm.openFiles.length, fm.activeFilePath, ui.sidebarTab, ui.showSidebar, ready]);
 
     return {
-        restoreSession
+        restoreSession,
+        saveSession
     };
 }
@@ -1,5 +1,5 @@
 import { useEffect, useRef } from 'react';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import type { TFunction } from 'i18next';
 import Sidebar from '../components/Sidebar/Sidebar';
 import SearchPanel from '../components/SearchPanel/SearchPanel';
@@ -70,6 +70,7 @@ export function useWorkbenchCommands(props: UseWorkbenchCommandsProps) {
             toggleSidebar: () => handlersRef.current.toggleSidebar(),
             setRootPath: (p: string) => handlersRef.current.setRootPath(p),
             fm: handlersRef.current.fm,
+            getActiveFilePath: () => handlersRef.current.fm.activeFilePath,
             setActiveTabId: (id: string | null) => handlersRef.current.setActiveTabId(id),
             components: handlersRef.current.components,
             openCustomView: (r: any) => handlersRef.current.openCustomView(r)
@@ -3,10 +3,11 @@ import { useTranslation } from 'react-i18next';
 import { views } from '../components/ViewSystem/ViewRegistry';
 import { statusBar as statusBarRegistry } from '../components/StatusBar/StatusBarRegistry';
 import { pathUtils } from '../utils/pathUtils';
-import { invoke } from '@tauri-apps/api/core';
+import { invoke } from '../utils/ipc';
 import { listen } from '@tauri-apps/api/event';
 
 import { useSessionManagement } from './useSessionManagement';
+import { useHotExit } from './useHotExit';
 import { useUIState } from './useUIState';
 import type { AppSettings } from '../components/SettingsModal/SettingsModal';
 
@@ -90,15 +91,24 @@ export function useWorkbenchLogic({ fm, tabSystem, appInit }: WorkbenchLogicProp
         }
     }, [ready]);
 
-    // 3. 开启 Watcher
+    // 3. 开启 Watcher (监听工作区的全部根目录)
     useEffect(() => {
-        if (ready && rootPath && rootPath !== '.') {
-            invoke('fs_watch', { path: rootPath }).catch(console.warn);
+        if (!ready) return;
+        if (rootPath && rootPath !== '.') {
+            invoke('fs_watch_workspace').catch(console.warn);
         }
+        const unlisten = listen('workspace_folders_changed', () => {
+            invoke('fs_watch_workspace').catch(console.warn);
+        });
+        return () => { unlisten.then(fn => fn()); };
     }, [ready, rootPath]);
 
     // 使用会话管理
-    useSessionManagement({ ready, rootPath, setRootPath, fm, appInit });
+    useSessionManagement({
+        ready, rootPath, setRootPath, fm, appInit,
+        ui: { sidebarTab, setSidebarTab, showSidebar: uiState.showSidebar, setShowSidebar: uiState.setShowSidebar }
+    });
+    useHotExit(ready, fm);
 
     useEffect(() => {
         const unsubViews = views.subscribe(() => forceUpdate(n => n + 1));
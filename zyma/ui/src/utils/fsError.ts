@@ -0,0 +1,28 @@
+// 后端的结构化 FsError 序列化为 { kind, ...字段 }，普通 IO 错误仍是字符串
+// 直接 String(e) 会得到 "[object Object]"，这里按 kind 生成可读的提示
+export function formatFsError(e: any, t: (key: string, options?: any) => string): string {
+    if (!e || typeof e !== 'object') return String(e);
+    if (e instanceof Error) return e.message;
+    switch (e.kind) {
+        case 'unsupported_encoding':
+            return t('FsErrorUnsupportedEncoding', { encoding: e.encoding });
+        case 'unmappable': {
+            const first = e.positions?.[0];
+            return t('FsErrorUnmappable', { count: e.count, encoding: e.encoding, line: first?.line ?? '-', column: first?.column ?? '-' });
+        }
+        case 'conflict':
+            return t(e.current ? 'FsErrorConflict' : 'FsErrorDeleted', { path: e.path });
+        case 'access_denied':
+            return t('FsErrorAccessDenied', { path: e.path, access: e.access, mode: e.mode });
+        case 'file_too_large':
+            return t('FsErrorTooLarge', { path: e.path, size: e.size });
+        case 'binary_file':
+            return t('FsErrorBinary', { path: e.path });
+        case 'not_writable':
+            return t('FsErrorNotWritable', { path: e.path });
+        case 'cross_device':
+            return t('FsErrorCrossDevice', { from: e.from, to: e.to });
+        default:
+            return typeof e.message === 'string' ? e.message : JSON.stringify(e);
+    }
+}
@@ -0,0 +1,40 @@
+import type { InvokeArgs, InvokeOptions } from '@tauri-apps/api/core';
+
+// 界面自身发起的 IPC 调用统一经由这里：请求头带上启动时领取的用户令牌，后端据此认定调用方为用户
+// 插件 API 直接使用 @tauri-apps/api/core 的 invoke，不带令牌，后端按插件权限处理
+export const CALLER_HEADER = 'zyma-caller';
+
+// 插件与界面运行在同一个页面中，令牌只保存在本模块的闭包里，不经过可被替换的全局对象：
+// 在任何插件加载前锁定 IPC 传输途中会用到的全局入口，插件无法再挂钩它们来窥探请求头
+const internals = (window as any).__TAURI_INTERNALS__;
+const lockGlobals = () => {
+    Object.freeze(internals);
+    for (const name of ['fetch', 'ipc']) {
+        const value = (window as any)[name];
+        if (value === undefined) continue;
+        if (typeof value === 'object') Object.freeze(value);
+        try {
+            Object.defineProperty(window, name, { value, writable: false, configurable: false });
+        } catch (e) { console.warn(`Failed to lock window.${name}:`, e); }
+    }
+    Object.freeze(JSON);
+};
+lockGlobals();
+const rawInvoke: (cmd: string, args?: InvokeArgs, options?: InvokeOptions) => Promise<any> = internals.invoke.bind(internals);
+
+// 每次页面加载只能领取一次 (后端在页面重新加载时作废旧令牌)
+let userToken: string | null = null;
+const claimed: Promise<void> = rawInvoke('caller_claim_token').then(
+    (token: string) => { userToken = token; },
+    (e: any) => { console.error('Failed to claim the user token, requests will run with plugin permissions:', e); }
+);
+
+export async function invoke<T>(cmd: string, args: InvokeArgs = {}, options?: InvokeOptions): Promise<T> {
+    await claimed;
+    if (!userToken) return rawInvoke(cmd, args, options);
+    // 使用普通对象而不是 Headers，避免经过可被插件替换的 Headers.prototype
+    const headers: Record<string, string> = {};
+    if (options?.headers) new Headers(options.headers).forEach((value, key) => { headers[key] = value; });
+    headers[CALLER_HEADER] = userToken;
+    return rawInvoke(cmd, args, { ...options, headers });
+}
@@ -0,0 +1,52 @@
+import { EditorSelection } from '@codemirror/state';
+import type { EditorView } from '@codemirror/view';
+
+export interface EditorViewState {
+    path: string;
+    selections: { anchor: number, head: number }[];
+    scroll_top: number;
+    scroll_left: number;
+    pinned: boolean;
+}
+
+// 各文件最近一次的光标、选区与滚动位置 (随会话保存)
+const states = new Map<string, EditorViewState>();
+// 会话恢复后尚未应用到编辑器的文件
+const pending = new Set<string>();
+
+export const viewStateCache = {
+    capture(path: string, view: EditorView) {
+        const prev = states.get(path);
+        states.set(path, {
+            path,
+            selections: view.state.selection.ranges.map(r => ({ anchor: r.anchor, head: r.head })),
+            scroll_top: view.scrollDOM.scrollTop,
+            scroll_left: view.scrollDOM.scrollLeft,
+            pinned: prev?.pinned ?? false
+        });
+    },
+
+    /** 按打开顺序导出，已关闭的文件不再保留 */
+    collect(paths: string[]): EditorViewState[] {
+        return paths.map(p => states.get(p)).filter((s): s is EditorViewState => !!s);
+    },
+
+    restore(list: EditorViewState[]) {
+        states.clear();
+        pending.clear();
+        list.forEach(s => { states.set(s.path, s); pending.add(s.path); });
+    },
+
+    /** 编辑器创建后调用，仅对会话恢复的文件生效一次 */
+    apply(path: string, view: EditorView) {
+        const state = states.get(path);
+        if (!state || !pending.delete(path)) return;
+        const len = view.state.doc.length;
+        const ranges = state.selections.map(s => EditorSelection.range(Math.min(s.anchor, len), Math.min(s.head, len)));
+        if (ranges.length > 0) view.dispatch({ selection: EditorSelection.create(ranges, 0) });
+        requestAnimationFrame(() => {
+            view.scrollDOM.scrollTop = state.scroll_top;
+            view.scrollDOM.scrollLeft = state.scroll_left;
+        });
+    }
+};